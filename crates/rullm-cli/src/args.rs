@@ -1,7 +1,7 @@
 use etcetera::BaseStrategy;
 use std::path::{Path, PathBuf};
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use clap_complete::CompletionCandidate;
 use clap_complete::engine::ArgValueCompleter;
 use std::ffi::OsStr;
@@ -11,6 +11,7 @@ use crate::commands::models::load_models_cache;
 use crate::commands::{Commands, ModelsCache};
 use crate::config::{self, Config};
 use crate::constants::{BINARY_NAME, KEYS_CONFIG_FILE};
+use crate::sessions::SessionStore;
 use crate::templates::TemplateStore;
 
 // Example strings for after_long_help
@@ -22,6 +23,9 @@ const CLI_EXAMPLES: &str = r#"EXAMPLES:
   rullm -m gpt4 "Code a web server"               # Stream tokens as they arrive (default)
   rullm -t code-review "Review this code"         # Use template for query
   rullm -t greeting "Hello"                     # Template with input parameter
+  rullm --attach chart.png "Describe this chart"  # Attach an image, PDF or text file
+  rullm --format json "Hi"                        # Print text, model, usage and finish reason as JSON
+  rullm --json-schema invoice.json "Extract..."   # Return JSON validated against a schema
   rullm chat                                      # Start interactive chat
   rullm chat -m gemini/gemini-pro                # Chat with specific model
   rullm chat --no-streaming -m claude            # Interactive chat without streaming"#;
@@ -80,6 +84,16 @@ fn parse_key_val(s: &str) -> anyhow::Result<(String, String)> {
     Ok((key, unquote_value(value)))
 }
 
+/// How quick-query responses are printed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Bare response text
+    #[default]
+    Text,
+    /// JSON envelope with text, model, usage and finish reason
+    Json,
+}
+
 pub struct CliConfig {
     pub config_base_path: PathBuf,
     pub data_base_path: PathBuf,
@@ -132,7 +146,21 @@ pub struct Cli {
     #[arg(short, long, add = ArgValueCompleter::new(template_completer))]
     pub template: Option<String>,
 
-    /// Set options in format: --option key value (e.g., --option temperature 0.1 --option max_tokens 2096)
+    /// Attach a file to the query: images and PDFs are sent as-is, text files are inlined (repeatable)
+    #[arg(long, value_name = "PATH")]
+    pub attach: Vec<PathBuf>,
+
+    /// Output format for quick queries
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Return JSON matching the schema in this file; exits non-zero if the model's output does not validate
+    #[arg(long, value_name = "PATH", conflicts_with = "format")]
+    pub json_schema: Option<PathBuf>,
+
+    /// Set options in format: --option key value (e.g., --option temperature 0.1 --option max_tokens 2096).
+    /// Available: temperature, max_tokens, top_p, top_k, stop, seed, frequency_penalty,
+    /// presence_penalty, logit_bias (support varies by provider)
     #[arg(long, value_parser = parse_key_val, global = true)]
     pub option: Vec<(String, String)>,
 
@@ -266,6 +294,24 @@ pub fn template_completer(current: &std::ffi::OsStr) -> Vec<clap_complete::Compl
     candidates
 }
 
+pub fn session_completer(current: &OsStr) -> Vec<CompletionCandidate> {
+    let cur_str = current.to_string_lossy();
+    let Ok(strategy) = etcetera::choose_base_strategy() else {
+        return Vec::new();
+    };
+    let store = SessionStore::new(&strategy.data_dir().join(BINARY_NAME));
+    store
+        .list()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|session| session.id.starts_with(cur_str.as_ref()))
+        .map(|session| {
+            let title = session.title();
+            CompletionCandidate::new(session.id).help(Some(title.into()))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
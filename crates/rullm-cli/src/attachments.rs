@@ -0,0 +1,216 @@
+//! File attachments for queries and chat messages
+//!
+//! Images and PDFs are base64-encoded into content parts that each provider
+//! client maps onto its own image/document types. Text files are inlined into
+//! the prompt as fenced code blocks.
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use rullm_core::ContentPart;
+use std::path::Path;
+
+/// Largest file accepted as an attachment (20 MiB)
+const MAX_ATTACHMENT_BYTES: u64 = 20 * 1024 * 1024;
+
+/// Attachments collected for the next user message
+#[derive(Debug, Clone, Default)]
+pub struct Attachments {
+    /// Fenced code blocks for attached text files
+    blocks: Vec<String>,
+    /// Base64-encoded images and documents
+    parts: Vec<ContentPart>,
+    /// Display names of everything attached, in order
+    names: Vec<String>,
+}
+
+impl Attachments {
+    /// Load every path in order
+    pub fn load<P: AsRef<Path>>(paths: &[P]) -> Result<Self> {
+        let mut attachments = Self::default();
+        for path in paths {
+            attachments.add(path.as_ref())?;
+        }
+        Ok(attachments)
+    }
+
+    /// Read a file and attach it, returning its detected MIME type
+    pub fn add(&mut self, path: &Path) -> Result<&'static str> {
+        let size = std::fs::metadata(path)
+            .with_context(|| format!("Cannot read attachment {}", path.display()))?
+            .len();
+        if size > MAX_ATTACHMENT_BYTES {
+            anyhow::bail!(
+                "Attachment {} is too large ({size} bytes, limit {MAX_ATTACHMENT_BYTES})",
+                path.display()
+            );
+        }
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("Cannot read attachment {}", path.display()))?;
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+
+        let mime = sniff_mime(&bytes).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unsupported attachment {}: only images, PDFs and text files can be attached",
+                path.display()
+            )
+        })?;
+
+        match mime {
+            "text/plain" => {
+                // sniff_mime only reports text for valid UTF-8
+                let text = String::from_utf8(bytes).expect("text attachment is UTF-8");
+                self.blocks.push(fenced_block(path, &name, &text));
+            }
+            "application/pdf" => self.parts.push(ContentPart::Document {
+                media_type: mime.to_string(),
+                data: STANDARD.encode(&bytes),
+                name: Some(name.clone()),
+            }),
+            _ => self
+                .parts
+                .push(ContentPart::image_base64(mime, STANDARD.encode(&bytes))),
+        }
+        self.names.push(name);
+        Ok(mime)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Display names of the attached files
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Images and documents to send alongside the message text
+    pub fn parts(&self) -> &[ContentPart] {
+        &self.parts
+    }
+
+    /// Prefix the message with the inlined text files
+    pub fn apply(&self, message: &str) -> String {
+        if self.blocks.is_empty() {
+            return message.to_string();
+        }
+        format!("{}\n\n{message}", self.blocks.join("\n\n"))
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Detect an attachment's MIME type from its contents
+///
+/// Returns `None` for binary formats that cannot be sent to a provider.
+pub fn sniff_mime(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if bytes.starts_with(b"%PDF-") {
+        Some("application/pdf")
+    } else if !bytes.contains(&0) && std::str::from_utf8(bytes).is_ok() {
+        Some("text/plain")
+    } else {
+        None
+    }
+}
+
+/// Wrap a text file in a fence long enough not to clash with its contents
+fn fenced_block(path: &Path, name: &str, text: &str) -> String {
+    let lang = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let longest_run = text
+        .lines()
+        .map(|line| line.trim_start().chars().take_while(|c| *c == '`').count())
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    let body = text.strip_suffix('\n').unwrap_or(text);
+    format!("{name}:\n{fence}{lang}\n{body}\n{fence}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::Builder;
+
+    fn write_temp(suffix: &str, bytes: &[u8]) -> tempfile::NamedTempFile {
+        let mut file = Builder::new().suffix(suffix).tempfile().unwrap();
+        file.write_all(bytes).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_sniff_mime() {
+        assert_eq!(sniff_mime(b"\x89PNG\r\n\x1a\n...."), Some("image/png"));
+        assert_eq!(sniff_mime(&[0xFF, 0xD8, 0xFF, 0xE0]), Some("image/jpeg"));
+        assert_eq!(sniff_mime(b"GIF89a..."), Some("image/gif"));
+        assert_eq!(sniff_mime(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(sniff_mime(b"%PDF-1.7\n"), Some("application/pdf"));
+        assert_eq!(sniff_mime(b"fn main() {}\n"), Some("text/plain"));
+        assert_eq!(sniff_mime(&[0x7F, b'E', b'L', b'F', 0, 1]), None);
+    }
+
+    #[test]
+    fn test_text_file_is_inlined_as_fenced_block() {
+        let file = write_temp(".rs", b"fn main() {}\n");
+        let attachments = Attachments::load(&[file.path()]).unwrap();
+
+        assert!(attachments.parts().is_empty());
+        let name = file.path().file_name().unwrap().to_string_lossy();
+        assert_eq!(
+            attachments.apply("explain this"),
+            format!("{name}:\n```rs\nfn main() {{}}\n```\n\nexplain this")
+        );
+    }
+
+    #[test]
+    fn test_fence_outgrows_backticks_in_file() {
+        let block = fenced_block(Path::new("README.md"), "README.md", "```sh\nls\n```\n");
+        assert_eq!(block, "README.md:\n````md\n```sh\nls\n```\n````");
+    }
+
+    #[test]
+    fn test_images_and_pdfs_become_base64_parts() {
+        let png = write_temp(".png", b"\x89PNG\r\n\x1a\nxyz");
+        let pdf = write_temp(".pdf", b"%PDF-1.4 abc");
+        let attachments = Attachments::load(&[png.path(), pdf.path()]).unwrap();
+
+        assert_eq!(attachments.apply("describe"), "describe");
+        assert_eq!(attachments.names().len(), 2);
+        match &attachments.parts()[0] {
+            ContentPart::ImageBase64 { media_type, data } => {
+                assert_eq!(media_type, "image/png");
+                assert_eq!(STANDARD.decode(data).unwrap(), b"\x89PNG\r\n\x1a\nxyz");
+            }
+            other => panic!("unexpected part {other:?}"),
+        }
+        match &attachments.parts()[1] {
+            ContentPart::Document {
+                media_type, name, ..
+            } => {
+                assert_eq!(media_type, "application/pdf");
+                assert!(name.as_deref().unwrap().ends_with(".pdf"));
+            }
+            other => panic!("unexpected part {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_binary_and_missing_files_are_rejected() {
+        let binary = write_temp(".bin", &[0x00, 0x01, 0x02]);
+        assert!(Attachments::load(&[binary.path()]).is_err());
+        assert!(Attachments::load(&[Path::new("/definitely/not/here.png")]).is_err());
+    }
+}
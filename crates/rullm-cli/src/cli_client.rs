@@ -3,18 +3,264 @@
 //! This module provides a simple enum wrapper for CLI usage that supports
 //! basic chat operations without exposing the full complexity of each provider's API.
 
-use futures::StreamExt;
 use rullm_core::config::{AnthropicConfig, GoogleAiConfig, OpenAICompatibleConfig, OpenAIConfig};
 use rullm_core::error::LlmError;
-use rullm_core::providers::openai_compatible::{OpenAICompatibleProvider, identities};
+use rullm_core::providers::openai_compatible::{
+    OpenAICompatibleProvider, ProviderIdentity, identities,
+};
 use rullm_core::providers::{AnthropicClient, GoogleClient, OpenAIClient};
-use std::pin::Pin;
+use std::collections::HashMap;
+
+use crate::config::{CustomProvider, WireFormat};
+
+use rullm_core::{
+    CancellationToken, ChatCompletion, ChatRequest, ChatRequestBuilder, ChatResponse,
+    ChatStreamEvent, ContentPart, StreamConfig, StreamResult,
+};
+
+/// Sampling options accepted by `--option` and `/set`
+pub const OPTION_NAMES: &[&str] = &[
+    "temperature",
+    "max_tokens",
+    "top_p",
+    "top_k",
+    "stop",
+    "seed",
+    "frequency_penalty",
+    "presence_penalty",
+    "logit_bias",
+];
+
+/// Options each provider accepts, by provider name
+fn supported_options(provider: &str) -> &'static [&'static str] {
+    match provider {
+        "openai" => &[
+            "temperature",
+            "max_tokens",
+            "top_p",
+            "stop",
+            "seed",
+            "frequency_penalty",
+            "presence_penalty",
+            "logit_bias",
+        ],
+        "anthropic" | "google" => &["temperature", "max_tokens", "top_p", "top_k", "stop"],
+        "groq" => &[
+            "temperature",
+            "max_tokens",
+            "top_p",
+            "stop",
+            "seed",
+            "frequency_penalty",
+            "presence_penalty",
+        ],
+        _ => OPTION_NAMES,
+    }
+}
+
+/// Map provider-specific spellings onto the CLI option name
+fn canonical_option(key: &str) -> Option<&'static str> {
+    match key {
+        "stop_sequences" => Some("stop"),
+        "max_output_tokens" => Some("max_tokens"),
+        _ => OPTION_NAMES.iter().copied().find(|name| *name == key),
+    }
+}
 
 /// Simple configuration for CLI adapter
 #[derive(Debug, Clone, Default)]
 pub struct CliConfig {
     pub temperature: Option<f32>,
     pub max_tokens: Option<u32>,
+    pub top_p: Option<f32>,
+    pub top_k: Option<u32>,
+    /// Sequences that end the response
+    pub stop: Option<Vec<String>>,
+    pub seed: Option<i64>,
+    pub frequency_penalty: Option<f32>,
+    pub presence_penalty: Option<f32>,
+    /// Token id to bias, from -100 to 100
+    pub logit_bias: Option<HashMap<String, i32>>,
+}
+
+fn parse_option<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, LlmError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| LlmError::validation(format!("Invalid value '{value}' for {key}")))
+}
+
+fn parse_penalty(key: &str, value: &str) -> Result<f32, LlmError> {
+    let penalty: f32 = parse_option(key, value)?;
+    if !(-2.0..=2.0).contains(&penalty) {
+        return Err(LlmError::validation(format!(
+            "{key} must be between -2 and 2"
+        )));
+    }
+    Ok(penalty)
+}
+
+impl CliConfig {
+    /// Set an option from its string value, or reset it to the provider
+    /// default when `value` is `None`
+    ///
+    /// `stop` takes a single sequence or a JSON array of sequences, and
+    /// `logit_bias` a JSON object of token ids to biases.
+    pub fn set(&mut self, key: &str, value: Option<&str>) -> Result<(), LlmError> {
+        let key = canonical_option(key).ok_or_else(|| {
+            LlmError::validation(format!(
+                "Unknown option '{key}'. Available options: {}",
+                OPTION_NAMES.join(", ")
+            ))
+        })?;
+        let Some(v) = value else {
+            match key {
+                "temperature" => self.temperature = None,
+                "max_tokens" => self.max_tokens = None,
+                "top_p" => self.top_p = None,
+                "top_k" => self.top_k = None,
+                "stop" => self.stop = None,
+                "seed" => self.seed = None,
+                "frequency_penalty" => self.frequency_penalty = None,
+                "presence_penalty" => self.presence_penalty = None,
+                _ => self.logit_bias = None,
+            }
+            return Ok(());
+        };
+
+        match key {
+            "temperature" => {
+                let temperature: f32 = parse_option(key, v)?;
+                if !temperature.is_finite() || temperature < 0.0 {
+                    return Err(LlmError::validation(
+                        "temperature must be a non-negative number",
+                    ));
+                }
+                self.temperature = Some(temperature);
+            }
+            "max_tokens" => {
+                let max_tokens: u32 = parse_option(key, v)?;
+                if max_tokens == 0 {
+                    return Err(LlmError::validation("max_tokens must be greater than 0"));
+                }
+                self.max_tokens = Some(max_tokens);
+            }
+            "top_p" => {
+                let top_p: f32 = parse_option(key, v)?;
+                if !(0.0..=1.0).contains(&top_p) {
+                    return Err(LlmError::validation("top_p must be between 0 and 1"));
+                }
+                self.top_p = Some(top_p);
+            }
+            "top_k" => self.top_k = Some(parse_option(key, v)?),
+            "stop" => {
+                let stop = if v.trim_start().starts_with('[') {
+                    serde_json::from_str(v).map_err(|_| {
+                        LlmError::validation(format!(
+                            "Invalid value '{v}' for stop: expected a JSON array of strings"
+                        ))
+                    })?
+                } else {
+                    vec![v.to_string()]
+                };
+                self.stop = Some(stop);
+            }
+            "seed" => self.seed = Some(parse_option(key, v)?),
+            "frequency_penalty" => self.frequency_penalty = Some(parse_penalty(key, v)?),
+            "presence_penalty" => self.presence_penalty = Some(parse_penalty(key, v)?),
+            _ => {
+                let bias: HashMap<String, i32> = serde_json::from_str(v).map_err(|_| {
+                    LlmError::validation(format!(
+                        "Invalid value '{v}' for logit_bias: expected a JSON object like {{\"50256\": -100}}"
+                    ))
+                })?;
+                if bias.values().any(|b| !(-100..=100).contains(b)) {
+                    return Err(LlmError::validation(
+                        "logit_bias values must be between -100 and 100",
+                    ));
+                }
+                self.logit_bias = Some(bias);
+            }
+        }
+        Ok(())
+    }
+
+    /// Every option with its value, `None` when the provider default applies
+    pub fn entries(&self) -> Vec<(&'static str, Option<String>)> {
+        vec![
+            ("temperature", self.temperature.map(|v| v.to_string())),
+            ("max_tokens", self.max_tokens.map(|v| v.to_string())),
+            ("top_p", self.top_p.map(|v| v.to_string())),
+            ("top_k", self.top_k.map(|v| v.to_string())),
+            (
+                "stop",
+                self.stop
+                    .as_ref()
+                    .map(|v| serde_json::to_string(v).unwrap_or_default()),
+            ),
+            ("seed", self.seed.map(|v| v.to_string())),
+            (
+                "frequency_penalty",
+                self.frequency_penalty.map(|v| v.to_string()),
+            ),
+            (
+                "presence_penalty",
+                self.presence_penalty.map(|v| v.to_string()),
+            ),
+            (
+                "logit_bias",
+                self.logit_bias
+                    .as_ref()
+                    .map(|v| serde_json::to_string(v).unwrap_or_default()),
+            ),
+        ]
+    }
+
+    /// Whether `provider` accepts the option `key`
+    pub fn supports(provider: &str, key: &str) -> bool {
+        canonical_option(key).is_some_and(|key| supported_options(provider).contains(&key))
+    }
+
+    /// Options that are set but that `provider` does not accept
+    pub fn unsupported(&self, provider: &str) -> Vec<&'static str> {
+        self.entries()
+            .into_iter()
+            .filter(|(name, value)| value.is_some() && !Self::supports(provider, name))
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// Apply the options to a provider-neutral request
+    fn apply(&self, mut builder: ChatRequestBuilder) -> ChatRequestBuilder {
+        if let Some(temp) = self.temperature {
+            builder = builder.temperature(temp);
+        }
+        if let Some(max) = self.max_tokens {
+            builder = builder.max_tokens(max);
+        }
+        if let Some(top_p) = self.top_p {
+            builder = builder.top_p(top_p);
+        }
+        if let Some(top_k) = self.top_k {
+            builder = builder.top_k(top_k);
+        }
+        if let Some(stop) = &self.stop {
+            builder = builder.stop(stop.clone());
+        }
+        if let Some(seed) = self.seed {
+            builder = builder.seed(seed);
+        }
+        if let Some(penalty) = self.frequency_penalty {
+            builder = builder.frequency_penalty(penalty);
+        }
+        if let Some(penalty) = self.presence_penalty {
+            builder = builder.presence_penalty(penalty);
+        }
+        if let Some(bias) = &self.logit_bias {
+            builder = builder.logit_bias(bias.clone());
+        }
+        builder
+    }
 }
 
 /// CLI adapter enum that wraps concrete provider clients
@@ -44,6 +290,41 @@ pub enum CliClient {
         model: String,
         config: CliConfig,
     },
+    /// Endpoint from a `[providers.<name>]` section of the config file
+    Custom {
+        name: String,
+        format: WireFormat,
+        client: Box<dyn ChatCompletion>,
+        model: String,
+        config: CliConfig,
+    },
+}
+
+/// Index of the last user message, which carries the attachments
+fn last_user_index(messages: &[(String, String)]) -> Option<usize> {
+    messages.iter().rposition(|(role, _)| role == "user")
+}
+
+/// Build a provider-neutral request from `(role, content)` pairs
+///
+/// Each provider's [`ChatCompletion`] impl translates it into the native
+/// request, see [`rullm_core::translate`].
+fn chat_request(
+    config: &CliConfig,
+    messages: &[(String, String)],
+    attachments: &[ContentPart],
+) -> ChatRequest {
+    let last_user = last_user_index(messages);
+    let mut builder = ChatRequestBuilder::new();
+    for (i, (role, content)) in messages.iter().enumerate() {
+        builder = match role.as_str() {
+            _ if Some(i) == last_user => builder.user_with_parts(content, attachments.to_vec()),
+            "system" => builder.system(content),
+            "assistant" => builder.assistant(content),
+            _ => builder.user(content),
+        };
+    }
+    config.apply(builder).build()
 }
 
 impl CliClient {
@@ -122,339 +403,186 @@ impl CliClient {
         })
     }
 
-    /// Simple chat - send a message and get a response
-    pub async fn chat(&self, message: &str) -> Result<String, LlmError> {
-        match self {
-            Self::OpenAI {
-                client,
-                model,
-                config,
-            } => {
-                use rullm_core::providers::openai::{ChatCompletionRequest, ChatMessage};
-
-                let mut request =
-                    ChatCompletionRequest::new(model, vec![ChatMessage::user(message)]);
-
-                if let Some(temp) = config.temperature {
-                    request.temperature = Some(temp);
-                }
-                if let Some(max) = config.max_tokens {
-                    request.max_tokens = Some(max);
-                }
-
-                let response = client.chat_completion(request).await?;
-                let content = response
-                    .choices
-                    .first()
-                    .and_then(|c| c.message.content.as_ref())
-                    .and_then(|c| match c {
-                        rullm_core::providers::openai::MessageContent::Text(t) => Some(t.clone()),
-                        _ => None,
-                    })
-                    .ok_or_else(|| LlmError::model("No content in response"))?;
-
-                Ok(content)
-            }
-            Self::Anthropic {
-                client,
-                model,
-                config,
-            } => {
-                use rullm_core::providers::anthropic::{Message, MessagesRequest};
-
-                let max_tokens = config.max_tokens.unwrap_or(1024);
-                let mut request =
-                    MessagesRequest::new(model, vec![Message::user(message)], max_tokens);
-
-                if let Some(temp) = config.temperature {
-                    request.temperature = Some(temp);
-                }
-
-                let response = client.messages(request).await?;
-                let content = response
-                    .content
-                    .iter()
-                    .filter_map(|block| match block {
-                        rullm_core::providers::anthropic::ContentBlock::Text { text } => {
-                            Some(text.clone())
-                        }
-                        _ => None,
-                    })
-                    .collect::<Vec<_>>()
-                    .join("");
-
-                Ok(content)
-            }
-            Self::Google {
-                client,
-                model,
-                config,
-            } => {
-                use rullm_core::providers::google::{
-                    Content, GenerateContentRequest, GenerationConfig,
+    /// Create a client for a user-configured endpoint
+    ///
+    /// Without an API key no `Authorization` header is sent, which only the
+    /// OpenAI format allows.
+    pub fn custom(
+        name: &str,
+        provider: &CustomProvider,
+        api_key: Option<String>,
+        model: impl Into<String>,
+        config: CliConfig,
+    ) -> Result<Self, LlmError> {
+        let client: Box<dyn ChatCompletion> = match provider.format {
+            WireFormat::OpenAI => {
+                let client_config = match api_key {
+                    Some(api_key) => {
+                        OpenAICompatibleConfig::new(api_key).with_base_url(&provider.base_url)
+                    }
+                    None => OpenAICompatibleConfig::without_api_key(&provider.base_url),
                 };
-
-                let mut request = GenerateContentRequest::new(vec![Content::user(message)]);
-
-                if config.temperature.is_some() || config.max_tokens.is_some() {
-                    let gen_config = GenerationConfig {
-                        temperature: config.temperature,
-                        max_output_tokens: config.max_tokens,
-                        stop_sequences: None,
-                        top_p: None,
-                        top_k: None,
-                        response_mime_type: None,
-                        response_schema: None,
-                    };
-                    request.generation_config = Some(gen_config);
-                }
-
-                let response = client.generate_content(model, request).await?;
-                let content = response
-                    .candidates
-                    .first()
-                    .map(|c| {
-                        c.content
-                            .parts
-                            .iter()
-                            .filter_map(|part| match part {
-                                rullm_core::providers::google::Part::Text { text } => {
-                                    Some(text.clone())
-                                }
-                                _ => None,
-                            })
-                            .collect::<Vec<_>>()
-                            .join("")
-                    })
-                    .ok_or_else(|| LlmError::model("No content in response"))?;
-
-                Ok(content)
+                let client_config = provider
+                    .headers
+                    .iter()
+                    .fold(client_config, |config, (name, value)| {
+                        config.with_header(name, value)
+                    });
+                let identity = ProviderIdentity::custom(
+                    name,
+                    provider.api_key_env.clone(),
+                    &provider.base_url,
+                );
+                Box::new(OpenAICompatibleProvider::new(client_config, identity)?)
             }
-            Self::Groq {
-                client,
-                model,
-                config,
+            WireFormat::Anthropic => {
+                let api_key = api_key.ok_or_else(|| {
+                    LlmError::configuration(format!(
+                        "Provider '{name}' uses the anthropic format, which needs api_key_env"
+                    ))
+                })?;
+                let client_config = provider.headers.iter().fold(
+                    AnthropicConfig::new(api_key).with_base_url(&provider.base_url),
+                    |config, (name, value)| config.with_header(name, value),
+                );
+                Box::new(AnthropicClient::new(client_config)?)
             }
-            | Self::OpenRouter {
-                client,
-                model,
-                config,
-            } => {
-                use rullm_core::{ChatRequestBuilder, ChatRole};
-
-                let mut request = ChatRequestBuilder::new().add_message(ChatRole::User, message);
-
-                if let Some(temp) = config.temperature {
-                    request = request.temperature(temp);
-                }
-                if let Some(max) = config.max_tokens {
-                    request = request.max_tokens(max);
-                }
+        };
+        Ok(Self::Custom {
+            name: name.to_string(),
+            format: provider.format,
+            client,
+            model: model.into(),
+            config,
+        })
+    }
 
-                let response = client.chat_completion(request.build(), model).await?;
-                Ok(response.message.content)
-            }
-        }
+    /// Simple chat - send a message, with an optional system prompt and any
+    /// attached images or documents, and get a response
+    pub async fn chat(
+        &self,
+        message: &str,
+        system: Option<&str>,
+        attachments: &[ContentPart],
+    ) -> Result<String, LlmError> {
+        let request = self.query_request(message, system, attachments);
+        Ok(self.chat_response(request).await?.message.content)
     }
 
     /// Stream chat - for interactive chat mode
+    ///
+    /// System messages become each provider's system prompt and attachments
+    /// are sent with the last user message. Text arrives as
+    /// [`ChatStreamEvent::Token`], and [`ChatStreamEvent::Usage`] reports token
+    /// counts when the provider sends them. Cancelling `cancel` ends the
+    /// stream and drops the connection.
     pub async fn stream_chat_raw(
         &self,
         messages: Vec<(String, String)>, // (role, content) pairs
-    ) -> Result<Pin<Box<dyn futures::Stream<Item = Result<String, LlmError>> + Send>>, LlmError>
-    {
-        match self {
-            Self::OpenAI {
-                client,
-                model,
-                config,
-            } => {
-                use rullm_core::providers::openai::{ChatCompletionRequest, ChatMessage, Role};
-
-                let msgs: Vec<ChatMessage> = messages
-                    .iter()
-                    .map(|(role, content)| {
-                        let r = match role.as_str() {
-                            "system" => Role::System,
-                            "user" => Role::User,
-                            "assistant" => Role::Assistant,
-                            _ => Role::User,
-                        };
-                        ChatMessage {
-                            role: r,
-                            content: Some(rullm_core::providers::openai::MessageContent::Text(
-                                content.clone(),
-                            )),
-                            name: None,
-                            tool_calls: None,
-                            tool_call_id: None,
-                        }
-                    })
-                    .collect();
-
-                let mut request = ChatCompletionRequest::new(model, msgs);
-                if let Some(temp) = config.temperature {
-                    request.temperature = Some(temp);
-                }
-                if let Some(max) = config.max_tokens {
-                    request.max_tokens = Some(max);
-                }
+        attachments: &[ContentPart],
+        cancel: Option<CancellationToken>,
+    ) -> Result<StreamResult<ChatStreamEvent>, LlmError> {
+        let request = chat_request(self.config(), &messages, attachments);
+        let config = StreamConfig {
+            cancel,
+            ..StreamConfig::default()
+        };
+        Ok(self
+            .as_chat_completion()
+            .chat_completion_stream(request, self.model_name(), Some(config))
+            .await)
+    }
 
-                let stream = client.chat_completion_stream(request).await?;
-                Ok(Box::pin(stream.filter_map(|chunk_result| async move {
-                    match chunk_result {
-                        Ok(chunk) => chunk
-                            .choices
-                            .first()
-                            .and_then(|choice| choice.delta.content.clone().map(Ok)),
-                        Err(e) => Some(Err(e)),
-                    }
-                })))
-            }
-            Self::Anthropic {
-                client,
-                model,
-                config,
-            } => {
-                use rullm_core::providers::anthropic::{Message, MessagesRequest};
-
-                let msgs: Vec<Message> = messages
-                    .iter()
-                    .filter_map(|(role, content)| {
-                        match role.as_str() {
-                            "user" => Some(Message::user(content)),
-                            "assistant" => Some(Message::assistant(content)),
-                            _ => None, // Skip system messages for now
-                        }
-                    })
-                    .collect();
-
-                let max_tokens = config.max_tokens.unwrap_or(1024);
-                let mut request = MessagesRequest::new(model, msgs, max_tokens);
-                if let Some(temp) = config.temperature {
-                    request.temperature = Some(temp);
-                }
+    /// Build a provider-neutral request from `(role, content)` pairs, using
+    /// the configured sampling options
+    pub fn messages_request(
+        &self,
+        messages: &[(String, String)],
+        attachments: &[ContentPart],
+    ) -> ChatRequest {
+        chat_request(self.config(), messages, attachments)
+    }
 
-                let stream = client.messages_stream(request).await?;
-                Ok(Box::pin(stream.filter_map(|event_result| async move {
-                    match event_result {
-                        Ok(rullm_core::providers::anthropic::StreamEvent::ContentBlockDelta {
-                            delta: rullm_core::providers::anthropic::Delta::TextDelta { text },
-                            ..
-                        }) => Some(Ok(text)),
-                        Ok(_) => None,
-                        Err(e) => Some(Err(e)),
-                    }
-                })))
-            }
-            Self::Google {
-                client,
-                model,
-                config,
-            } => {
-                use rullm_core::providers::google::{
-                    Content, GenerateContentRequest, GenerationConfig,
-                };
+    /// Build a provider-neutral request for a single query, using the
+    /// configured sampling options
+    pub fn query_request(
+        &self,
+        message: &str,
+        system: Option<&str>,
+        attachments: &[ContentPart],
+    ) -> ChatRequest {
+        let messages: Vec<(String, String)> = system
+            .map(|system| ("system".to_string(), system.to_string()))
+            .into_iter()
+            .chain(std::iter::once(("user".to_string(), message.to_string())))
+            .collect();
+        chat_request(self.config(), &messages, attachments)
+    }
 
-                let contents: Vec<Content> = messages
-                    .iter()
-                    .map(|(role, content)| match role.as_str() {
-                        "user" => Content::user(content),
-                        _ => Content::model(content),
-                    })
-                    .collect();
-
-                let mut request = GenerateContentRequest::new(contents);
-                if config.temperature.is_some() || config.max_tokens.is_some() {
-                    request.generation_config = Some(GenerationConfig {
-                        temperature: config.temperature,
-                        max_output_tokens: config.max_tokens,
-                        stop_sequences: None,
-                        top_p: None,
-                        top_k: None,
-                        response_mime_type: None,
-                        response_schema: None,
-                    });
-                }
+    /// Send a provider-neutral request and return the full response
+    pub async fn chat_response(&self, request: ChatRequest) -> Result<ChatResponse, LlmError> {
+        self.as_chat_completion()
+            .chat_completion(request, self.model_name())
+            .await
+    }
 
-                let stream = client.stream_generate_content(model, request).await?;
-                Ok(Box::pin(stream.filter_map(|response_result| async move {
-                    match response_result {
-                        Ok(response) => response
-                            .candidates
-                            .first()
-                            .map(|candidate| {
-                                let text = candidate
-                                    .content
-                                    .parts
-                                    .iter()
-                                    .filter_map(|part| match part {
-                                        rullm_core::providers::google::Part::Text { text } => {
-                                            Some(text.clone())
-                                        }
-                                        _ => None,
-                                    })
-                                    .collect::<Vec<_>>()
-                                    .join("");
-                                Ok(text)
-                            })
-                            .filter(|s| matches!(s, Ok(t) if !t.is_empty())),
-                        Err(e) => Some(Err(e)),
-                    }
-                })))
-            }
-            Self::Groq {
-                client,
-                model,
-                config,
-            }
-            | Self::OpenRouter {
-                client,
-                model,
-                config,
-            } => {
-                use rullm_core::{ChatRequestBuilder, ChatRole, ChatStreamEvent};
-
-                let mut builder = ChatRequestBuilder::new();
-                for (role, content) in messages {
-                    let r = match role.as_str() {
-                        "system" => ChatRole::System,
-                        "user" => ChatRole::User,
-                        "assistant" => ChatRole::Assistant,
-                        _ => ChatRole::User,
-                    };
-                    builder = builder.add_message(r, content);
-                }
+    /// The wrapped client as a provider-neutral [`ChatCompletion`]
+    pub fn as_chat_completion(&self) -> &dyn ChatCompletion {
+        match self {
+            Self::OpenAI { client, .. } => client,
+            Self::Anthropic { client, .. } => client,
+            Self::Google { client, .. } => client,
+            Self::Groq { client, .. } | Self::OpenRouter { client, .. } => client,
+            Self::Custom { client, .. } => client.as_ref(),
+        }
+    }
 
-                if let Some(temp) = config.temperature {
-                    builder = builder.temperature(temp);
-                }
-                if let Some(max) = config.max_tokens {
-                    builder = builder.max_tokens(max);
-                }
+    /// Get sampling configuration
+    pub fn config(&self) -> &CliConfig {
+        match self {
+            Self::OpenAI { config, .. }
+            | Self::Anthropic { config, .. }
+            | Self::Google { config, .. }
+            | Self::Groq { config, .. }
+            | Self::OpenRouter { config, .. }
+            | Self::Custom { config, .. } => config,
+        }
+    }
 
-                let stream = client
-                    .chat_completion_stream(builder.build(), model, None)
-                    .await;
-                Ok(Box::pin(stream.filter_map(|event_result| async move {
-                    match event_result {
-                        Ok(ChatStreamEvent::Token(token)) => Some(Ok(token)),
-                        Ok(_) => None,
-                        Err(e) => Some(Err(e)),
-                    }
-                })))
-            }
+    /// Get mutable sampling configuration
+    pub fn config_mut(&mut self) -> &mut CliConfig {
+        match self {
+            Self::OpenAI { config, .. }
+            | Self::Anthropic { config, .. }
+            | Self::Google { config, .. }
+            | Self::Groq { config, .. }
+            | Self::OpenRouter { config, .. }
+            | Self::Custom { config, .. } => config,
         }
     }
 
     /// Get provider name
-    pub fn provider_name(&self) -> &'static str {
+    pub fn provider_name(&self) -> &str {
         match self {
             Self::OpenAI { .. } => "openai",
             Self::Anthropic { .. } => "anthropic",
             Self::Google { .. } => "google",
             Self::Groq { .. } => "groq",
             Self::OpenRouter { .. } => "openrouter",
+            Self::Custom { name, .. } => name,
+        }
+    }
+
+    /// Provider whose option table applies to this client
+    ///
+    /// Custom endpoints accept the options of the API format they speak.
+    pub fn options_provider(&self) -> &str {
+        match self {
+            Self::Custom { format, .. } => match format {
+                WireFormat::OpenAI => "openai",
+                WireFormat::Anthropic => "anthropic",
+            },
+            _ => self.provider_name(),
         }
     }
 
@@ -465,7 +593,296 @@ impl CliClient {
             | Self::Anthropic { model, .. }
             | Self::Google { model, .. }
             | Self::Groq { model, .. }
-            | Self::OpenRouter { model, .. } => model,
+            | Self::OpenRouter { model, .. }
+            | Self::Custom { model, .. } => model,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rullm_core::translate::{
+        to_anthropic_request, to_compatible_request, to_google_request, to_openai_request,
+    };
+    use serde_json::json;
+
+    fn conversation() -> Vec<(String, String)> {
+        vec![
+            ("system".to_string(), "Answer in French".to_string()),
+            ("user".to_string(), "Hi".to_string()),
+            ("assistant".to_string(), "Salut".to_string()),
+            ("user".to_string(), "Thanks".to_string()),
+        ]
+    }
+
+    /// The request body each client would send for `messages`
+    fn recorded_request(client: &CliClient, messages: &[(String, String)]) -> serde_json::Value {
+        let request = chat_request(client.config(), messages, &[]);
+        let model = client.model_name();
+        let value = match client {
+            CliClient::OpenAI { .. } => {
+                serde_json::to_value(to_openai_request(request, model).unwrap())
+            }
+            CliClient::Anthropic { .. } => {
+                serde_json::to_value(to_anthropic_request(request, model).unwrap())
+            }
+            CliClient::Google { .. } => serde_json::to_value(to_google_request(request).unwrap()),
+            CliClient::Custom {
+                format: WireFormat::Anthropic,
+                ..
+            } => serde_json::to_value(to_anthropic_request(request, model).unwrap()),
+            CliClient::Groq { .. } | CliClient::OpenRouter { .. } | CliClient::Custom { .. } => {
+                Ok(to_compatible_request(&request, model, client.provider_name()).unwrap())
+            }
+        };
+        value.unwrap()
+    }
+
+    fn clients() -> Vec<CliClient> {
+        let config = CliConfig::default();
+        vec![
+            CliClient::openai("key", "gpt-4o", config.clone()).unwrap(),
+            CliClient::anthropic("key", "claude-3-haiku", config.clone()).unwrap(),
+            CliClient::google("key", "gemini-2.0-flash", config.clone()).unwrap(),
+            CliClient::groq("key", "llama-3.1-8b-instant", config.clone()).unwrap(),
+            CliClient::openrouter("key", "openai/gpt-4o", config).unwrap(),
+        ]
+    }
+
+    #[test]
+    fn test_system_prompt_reaches_every_provider() {
+        for client in clients() {
+            let body = recorded_request(&client, &conversation());
+            let (system, turns) = match client.provider_name() {
+                "openai" => (body["messages"][0].clone(), body["messages"].clone()),
+                "anthropic" => (body["system"].clone(), body["messages"].clone()),
+                "google" => (
+                    body["systemInstruction"]["parts"][0]["text"].clone(),
+                    body["contents"].clone(),
+                ),
+                _ => (body["messages"][0].clone(), body["messages"].clone()),
+            };
+            let system = system.to_string();
+            assert!(
+                system.contains("Answer in French"),
+                "{} dropped the system prompt: {body}",
+                client.provider_name()
+            );
+
+            // Providers with a separate system field only get the turns
+            let expected_turns = match client.provider_name() {
+                "anthropic" | "google" => 3,
+                _ => 4,
+            };
+            assert_eq!(
+                turns.as_array().unwrap().len(),
+                expected_turns,
+                "{}: {body}",
+                client.provider_name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_recorded_requests() {
+        let messages = conversation();
+        let mut config = CliConfig::default();
+        config.set("max_tokens", Some("64")).unwrap();
+
+        let request = || chat_request(&config, &messages, &[]);
+
+        let openai = serde_json::to_value(to_openai_request(request(), "gpt-4o").unwrap()).unwrap();
+        assert_eq!(
+            openai["messages"],
+            json!([
+                {"role": "system", "content": "Answer in French"},
+                {"role": "user", "content": "Hi"},
+                {"role": "assistant", "content": "Salut"},
+                {"role": "user", "content": "Thanks"}
+            ])
+        );
+
+        let anthropic =
+            serde_json::to_value(to_anthropic_request(request(), "claude-3-haiku").unwrap())
+                .unwrap();
+        assert_eq!(anthropic["system"], json!("Answer in French"));
+        assert_eq!(anthropic["max_tokens"], json!(64));
+        assert_eq!(
+            anthropic["messages"],
+            json!([
+                {"role": "user", "content": "Hi"},
+                {"role": "assistant", "content": "Salut"},
+                {"role": "user", "content": "Thanks"}
+            ])
+        );
+
+        let google = serde_json::to_value(to_google_request(request()).unwrap()).unwrap();
+        assert_eq!(
+            google["systemInstruction"]["parts"],
+            json!([{"text": "Answer in French"}])
+        );
+        let roles: Vec<&str> = google["contents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["role"].as_str().unwrap())
+            .collect();
+        assert_eq!(roles, vec!["user", "model", "user"]);
+
+        let compat = to_compatible_request(&request(), "llama-3.1-8b-instant", "groq").unwrap();
+        assert_eq!(compat["messages"], openai["messages"]);
+        assert_eq!(compat["max_tokens"], json!(64));
+    }
+
+    #[test]
+    fn test_attachments_go_on_last_user_message() {
+        let parts = vec![ContentPart::image_base64("image/png", "AAAA")];
+        let request = chat_request(&CliConfig::default(), &conversation(), &parts);
+        let request = to_anthropic_request(request, "claude-3-haiku").unwrap();
+        let body = serde_json::to_value(request).unwrap();
+        assert_eq!(body["messages"][0]["content"], json!("Hi"));
+        assert_eq!(body["messages"][2]["content"][1]["type"], json!("image"));
+    }
+
+    #[test]
+    fn test_set_parses_and_resets_options() {
+        let mut config = CliConfig::default();
+        config.set("temperature", Some("0.2")).unwrap();
+        config.set("top_p", Some("0.9")).unwrap();
+        config.set("seed", Some("42")).unwrap();
+        config.set("stop", Some("END")).unwrap();
+        assert_eq!(config.temperature, Some(0.2));
+        assert_eq!(config.top_p, Some(0.9));
+        assert_eq!(config.seed, Some(42));
+        assert_eq!(config.stop, Some(vec!["END".to_string()]));
+
+        config.set("stop", Some(r#"["a", "b"]"#)).unwrap();
+        assert_eq!(config.stop, Some(vec!["a".to_string(), "b".to_string()]));
+
+        config.set("temperature", None).unwrap();
+        assert_eq!(config.temperature, None);
+        assert_eq!(config.entries()[0], ("temperature", None));
+        assert_eq!(config.entries()[5], ("seed", Some("42".to_string())));
+    }
+
+    #[test]
+    fn test_provider_spellings_and_extra_options() {
+        let mut config = CliConfig::default();
+        config.set("stop_sequences", Some("###")).unwrap();
+        config.set("max_output_tokens", Some("256")).unwrap();
+        config.set("top_k", Some("40")).unwrap();
+        config.set("frequency_penalty", Some("0.5")).unwrap();
+        config
+            .set("logit_bias", Some(r#"{"50256": -100}"#))
+            .unwrap();
+        assert_eq!(config.stop, Some(vec!["###".to_string()]));
+        assert_eq!(config.max_tokens, Some(256));
+        assert_eq!(config.logit_bias.as_ref().unwrap()["50256"], -100);
+
+        assert!(config.set("presence_penalty", Some("3")).is_err());
+        assert!(config.set("logit_bias", Some(r#"{"1": 200}"#)).is_err());
+        assert!(config.set("logit_bias", Some("1:2")).is_err());
+    }
+
+    #[test]
+    fn test_unsupported_options_per_provider() {
+        let mut config = CliConfig::default();
+        config.set("temperature", Some("0.5")).unwrap();
+        config.set("top_k", Some("40")).unwrap();
+        config.set("seed", Some("7")).unwrap();
+        config.set("logit_bias", Some(r#"{"1": 5}"#)).unwrap();
+
+        assert_eq!(config.unsupported("openai"), vec!["top_k"]);
+        assert_eq!(config.unsupported("anthropic"), vec!["seed", "logit_bias"]);
+        assert_eq!(config.unsupported("google"), vec!["seed", "logit_bias"]);
+        assert_eq!(config.unsupported("groq"), vec!["top_k", "logit_bias"]);
+        assert!(config.unsupported("openrouter").is_empty());
+        assert!(CliConfig::supports("anthropic", "stop_sequences"));
+    }
+
+    #[test]
+    fn test_native_requests_carry_translated_options() {
+        let mut config = CliConfig::default();
+        config.set("max_tokens", Some("100")).unwrap();
+        config.set("top_k", Some("40")).unwrap();
+        config.set("stop", Some("END")).unwrap();
+        config.set("presence_penalty", Some("0.1")).unwrap();
+
+        let request =
+            |config: &CliConfig| config.apply(ChatRequestBuilder::new().user("Hi")).build();
+
+        let openai = to_openai_request(request(&config), "gpt-4o");
+        assert!(openai.unwrap_err().to_string().contains("top_k"));
+
+        config.set("top_k", None).unwrap();
+        let openai = to_openai_request(request(&config), "gpt-4o").unwrap();
+        assert_eq!(openai.max_tokens, Some(100));
+        assert_eq!(openai.stop, Some(vec!["END".to_string()]));
+        assert_eq!(openai.presence_penalty, Some(0.1));
+
+        config.set("top_k", Some("40")).unwrap();
+        config.set("presence_penalty", None).unwrap();
+        let anthropic = to_anthropic_request(request(&config), "claude").unwrap();
+        assert_eq!(anthropic.max_tokens, 100);
+        assert_eq!(anthropic.top_k, Some(40));
+        assert_eq!(anthropic.stop_sequences, Some(vec!["END".to_string()]));
+
+        let google = to_google_request(request(&config)).unwrap();
+        let generation = google.generation_config.unwrap();
+        assert_eq!(generation.max_output_tokens, Some(100));
+        assert_eq!(generation.top_k, Some(40));
+        assert_eq!(generation.stop_sequences, Some(vec!["END".to_string()]));
+    }
+
+    #[test]
+    fn test_custom_client_follows_wire_format() {
+        let mut provider = CustomProvider {
+            base_url: "http://localhost:8000/v1".to_string(),
+            api_key_env: None,
+            headers: Default::default(),
+            format: WireFormat::OpenAI,
+        };
+        let mut config = CliConfig::default();
+        config.set("top_k", Some("40")).unwrap();
+        config.set("seed", Some("7")).unwrap();
+
+        let client = CliClient::custom("mylab", &provider, None, "qwen2", config.clone()).unwrap();
+        assert_eq!(client.provider_name(), "mylab");
+        assert_eq!(client.as_chat_completion().name(), "mylab");
+        assert_eq!(config.unsupported(client.options_provider()), vec!["top_k"]);
+        let body = recorded_request(&client, &conversation());
+        assert_eq!(body["messages"][0]["content"], json!("Answer in French"));
+
+        provider.format = WireFormat::Anthropic;
+        assert!(CliClient::custom("gateway", &provider, None, "claude", config.clone()).is_err());
+        let mut client = CliClient::custom(
+            "gateway",
+            &provider,
+            Some("key".to_string()),
+            "claude",
+            config.clone(),
+        )
+        .unwrap();
+        assert_eq!(client.provider_name(), "gateway");
+        assert_eq!(config.unsupported(client.options_provider()), vec!["seed"]);
+        client.config_mut().set("seed", None).unwrap();
+        let body = recorded_request(&client, &conversation());
+        assert_eq!(body["system"], json!("Answer in French"));
+    }
+
+    #[test]
+    fn test_set_rejects_unknown_and_invalid_options() {
+        let mut config = CliConfig::default();
+        assert!(config.set("temprature", Some("0.2")).is_err());
+        assert!(config.set("temperature", Some("warm")).is_err());
+        assert!(config.set("temperature", Some("inf")).is_err());
+        assert!(config.set("top_p", Some("1.5")).is_err());
+        assert!(config.set("max_tokens", Some("0")).is_err());
+        assert!(config.set("stop", Some("[1, 2]")).is_err());
+        assert_eq!(
+            config.entries().iter().filter(|(_, v)| v.is_some()).count(),
+            0
+        );
+    }
+}
@@ -2,52 +2,94 @@ use super::provider::Provider;
 use crate::api_keys::ApiKeys;
 use crate::args::{Cli, CliConfig};
 use crate::cli_client::{CliClient, CliConfig as CoreCliConfig};
+use crate::config::CustomProvider;
 use crate::constants;
 use anyhow::{Context, Result};
 
 use rullm_core::LlmError;
 
+/// Sampling options given with `--option key=value`
+fn cli_options(cli: &Cli) -> Result<CoreCliConfig, LlmError> {
+    let mut config = CoreCliConfig::default();
+    for (key, value) in &cli.option {
+        config.set(key, Some(value))?;
+    }
+    Ok(config)
+}
+
 pub fn create_client(
     provider: &Provider,
     api_key: &str,
-    _base_url: Option<&str>,
     cli: &Cli,
     model_name: &str,
 ) -> Result<CliClient, LlmError> {
-    // Build CoreCliConfig based on CLI args
-    let mut config = CoreCliConfig::default();
-
-    // Parse options from --option key value format
-    for (key, value) in &cli.option {
-        match key.as_str() {
-            "temperature" => {
-                if let Ok(temp) = value.parse::<f32>() {
-                    config.temperature = Some(temp);
-                }
-            }
-            "max_tokens" => {
-                if let Ok(max_tokens) = value.parse::<u32>() {
-                    config.max_tokens = Some(max_tokens);
-                }
-            }
-            _ => {
-                // Ignore unknown options for now
-            }
-        }
-    }
-
-    match provider {
+    let config = cli_options(cli)?;
+    let client = match provider {
         Provider::OpenAI => CliClient::openai(api_key, model_name, config),
         Provider::Groq => CliClient::groq(api_key, model_name, config),
         Provider::OpenRouter => CliClient::openrouter(api_key, model_name, config),
         Provider::Anthropic => CliClient::anthropic(api_key, model_name, config),
         Provider::Google => CliClient::google(api_key, model_name, config),
+    }?;
+    check_options(client)
+}
+
+/// Create a client for a `[providers.<name>]` endpoint from the config file
+pub fn create_custom_client(
+    name: &str,
+    provider: &CustomProvider,
+    cli: &Cli,
+    model_name: &str,
+) -> Result<CliClient, LlmError> {
+    if Provider::from_alias(name).is_some() {
+        return Err(LlmError::configuration(format!(
+            "[providers.{name}] in {} shadows a built-in provider, pick another name",
+            constants::CONFIG_FILE_NAME
+        )));
     }
+    if model_name.trim().is_empty() {
+        return Err(LlmError::validation("Model name cannot be empty"));
+    }
+
+    // Local servers usually run without authentication
+    let api_key = match &provider.api_key_env {
+        Some(env_key) => Some(std::env::var(env_key).map_err(|_| {
+            LlmError::configuration(format!(
+                "API key required. Set {env_key} environment variable for provider '{name}'"
+            ))
+        })?),
+        None => None,
+    };
+
+    let config = cli_options(cli)?;
+    let client = CliClient::custom(name, provider, api_key, model_name, config)?;
+    check_options(client)
+}
+
+/// Reject `--option`s the provider does not accept
+fn check_options(client: CliClient) -> Result<CliClient, LlmError> {
+    let unsupported = client.config().unsupported(client.options_provider());
+    if !unsupported.is_empty() {
+        return Err(LlmError::validation(format!(
+            "{} does not support option(s): {}",
+            client.provider_name(),
+            unsupported.join(", ")
+        )));
+    }
+    Ok(client)
 }
 
 /// Create a CliClient from a model string, CLI arguments, and configuration
 /// This is the promoted version of the create_client_from_model closure from lib.rs
 pub fn from_model(model_str: &str, cli: &Cli, cli_config: &CliConfig) -> Result<CliClient> {
+    // Endpoints from the config file take precedence over aliases
+    if let Some((name, model_name)) = model_str.split_once(':') {
+        if let Some(provider) = cli_config.config.providers.get(name) {
+            return create_custom_client(name, provider, cli, model_name)
+                .map_err(anyhow::Error::from);
+        }
+    }
+
     // Use the global alias resolver for CLI functionality
     let resolver = crate::aliases::get_global_alias_resolver(&cli_config.config_base_path);
     let resolver = resolver
@@ -65,5 +107,5 @@ pub fn from_model(model_str: &str, cli: &Cli, cli_config: &CliConfig) -> Result<
         )
     })?;
 
-    create_client(&provider, &api_key, None, cli, &model_name).map_err(anyhow::Error::from)
+    create_client(&provider, &api_key, cli, &model_name).map_err(anyhow::Error::from)
 }
@@ -1,13 +1,17 @@
+use crate::cli_client::OPTION_NAMES;
 use reedline::{Completer, Suggestion};
 
 #[derive(Clone)]
 pub struct SlashCommandCompleter {
     pub commands: Vec<String>,
+    /// Model names and aliases offered after `/model`
+    pub models: Vec<String>,
 }
 
 impl SlashCommandCompleter {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(models: Vec<String>) -> Self {
         Self {
+            models,
             commands: vec![
                 "/system".to_string(),
                 "/clear".to_string(),
@@ -15,6 +19,15 @@ impl SlashCommandCompleter {
                 "/quit".to_string(),
                 "/exit".to_string(),
                 "/edit".to_string(),
+                "/retry".to_string(),
+                "/undo".to_string(),
+                "/branch".to_string(),
+                "/attach".to_string(),
+                "/model".to_string(),
+                "/set".to_string(),
+                "/options".to_string(),
+                "/save".to_string(),
+                "/load".to_string(),
             ],
         }
     }
@@ -25,6 +38,26 @@ impl Completer for SlashCommandCompleter {
         let start = line[..pos].rfind(' ').map_or(0, |i| i + 1);
         let word = &line[start..pos];
 
+        let candidates: Vec<String> = match line[..start].trim_end().to_lowercase().as_str() {
+            "/model" => self.models.clone(),
+            "/set" => OPTION_NAMES.iter().map(|name| name.to_string()).collect(),
+            _ => Vec::new(),
+        };
+        if !candidates.is_empty() {
+            return candidates
+                .into_iter()
+                .filter(|candidate| candidate.starts_with(word))
+                .map(|candidate| Suggestion {
+                    value: candidate,
+                    description: None,
+                    style: None,
+                    extra: None,
+                    span: reedline::Span::new(start, pos),
+                    append_whitespace: false,
+                })
+                .collect();
+        }
+
         if !word.starts_with('/') {
             return Vec::new();
         }
@@ -41,6 +74,15 @@ impl Completer for SlashCommandCompleter {
                     "/quit" => Some("Exit chat".to_string()),
                     "/exit" => Some("Exit chat".to_string()),
                     "/edit" => Some("Edit message in $EDITOR".to_string()),
+                    "/retry" => Some("Regenerate the last answer".to_string()),
+                    "/undo" => Some("Drop the last exchange".to_string()),
+                    "/branch" => Some("List or switch conversation branches".to_string()),
+                    "/attach" => Some("Attach a file to the next message".to_string()),
+                    "/model" => Some("Switch model, keeping the conversation".to_string()),
+                    "/set" => Some("Set a sampling option".to_string()),
+                    "/options" => Some("Show sampling options".to_string()),
+                    "/save" => Some("Save the session".to_string()),
+                    "/load" => Some("Load a saved session".to_string()),
                     _ => None,
                 },
                 style: None,
@@ -51,3 +93,38 @@ impl Completer for SlashCommandCompleter {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completes_command_arguments() {
+        let mut completer = SlashCommandCompleter::new(vec![
+            "openai:gpt-4o".to_string(),
+            "openai:gpt-4o-mini".to_string(),
+            "anthropic:claude-3-haiku".to_string(),
+        ]);
+
+        let values: Vec<String> = completer
+            .complete("/model openai:", 14)
+            .into_iter()
+            .map(|s| s.value)
+            .collect();
+        assert_eq!(values, vec!["openai:gpt-4o", "openai:gpt-4o-mini"]);
+
+        let values: Vec<String> = completer
+            .complete("/mo", 3)
+            .into_iter()
+            .map(|s| s.value)
+            .collect();
+        assert_eq!(values, vec!["/model"]);
+
+        let values: Vec<String> = completer
+            .complete("/set te", 7)
+            .into_iter()
+            .map(|s| s.value)
+            .collect();
+        assert_eq!(values, vec!["temperature"]);
+    }
+}
@@ -1,17 +1,64 @@
-use crate::args::CliConfig;
+use crate::args::{Cli, CliConfig};
+use crate::attachments::Attachments;
 use crate::cli_client::CliClient;
+use crate::conversation::ConversationTree;
+use crate::sessions::{Session, SessionStore};
 use anyhow::Result;
 use owo_colors::OwoColorize;
 use reedline::{EditCommand, Signal};
 use std::time::{Duration, Instant};
 
+/// Appended to answers cut short with Ctrl+C
+const TRUNCATED_MARKER: &str = "[response truncated]";
+
+/// A partial answer as kept in the conversation
+fn truncated(partial: &str) -> String {
+    if partial.is_empty() {
+        return TRUNCATED_MARKER.to_string();
+    }
+    format!("{partial}\n\n{TRUNCATED_MARKER}")
+}
+
+/// Drop the unanswered user message from the current branch after a failed
+/// request, so the next message does not follow another user turn
+fn discard_unanswered(conversation: &mut ConversationTree) {
+    conversation.undo();
+    println!(
+        "{}",
+        "(message not kept, press Up to edit and send it again)".dimmed()
+    );
+}
+
+/// Stops listening for Ctrl+C when dropped
+struct CtrlCGuard(tokio::task::JoinHandle<()>);
+
+impl Drop for CtrlCGuard {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Cancel `token` when Ctrl+C is pressed, until the guard is dropped
+fn cancel_on_ctrl_c(token: &rullm_core::CancellationToken) -> CtrlCGuard {
+    let token = token.clone();
+    CtrlCGuard(tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            token.cancel();
+        }
+    }))
+}
+
 pub async fn run_interactive_chat(
-    client: &CliClient,
+    mut client: CliClient,
     initial_system: Option<&str>,
+    resumed: Option<Session>,
+    cli: &Cli,
     config: &CliConfig,
     streaming: bool,
 ) -> Result<()> {
-    use super::slash_command::{HandleCommandResult, SlashCommand, handle_slash_command};
+    use super::slash_command::{
+        ChatContext, HandleCommandResult, SlashCommand, autosave, handle_slash_command,
+    };
     use super::{ChatPrompt, setup_reedline};
 
     println!(
@@ -33,46 +80,77 @@ pub async fn run_interactive_chat(
     );
     println!();
 
-    let mut conversation: Vec<(String, String)> = Vec::new();
-    let mut line_editor = setup_reedline(config.config.vi_mode, &config.data_base_path)?;
+    let model = format!("{}:{}", client.provider_name(), client.model_name());
+    let store = SessionStore::new(&config.data_base_path);
+    let context = ChatContext {
+        cli,
+        config,
+        store: &store,
+    };
+    let mut conversation = ConversationTree::new();
+    let mut session = match resumed {
+        Some(mut session) => {
+            conversation = session.conversation();
+            println!(
+                "{} {} ({} messages)\n",
+                "Resumed session".green(),
+                session.id.yellow(),
+                session.messages.len()
+            );
+            session.model = model;
+            session
+        }
+        None => Session::new(model),
+    };
+    let mut attachments = Attachments::default();
+    let mut line_editor = setup_reedline(config)?;
     let prompt = ChatPrompt::new();
 
     // Track Ctrl+C presses for double-press exit
     let mut last_ctrl_c: Option<Instant> = None;
     const DOUBLE_CTRL_C_TIMEOUT: Duration = Duration::from_secs(2);
 
-    // Add system prompt if provided
+    // Add system prompt if provided, replacing a resumed session's one
     if let Some(system) = initial_system {
-        conversation.push(("system".to_string(), system.to_string()));
+        conversation.set_system(Some(system.to_string()));
         println!("{} {}\n", "System:".green().bold(), system.dimmed());
     }
 
-    // Helper function to DRY up message sending logic
-    async fn process_user_message(
-        input: &str,
-        conversation: &mut Vec<(String, String)>,
+    // Send the current branch and append the assistant's answer to it
+    async fn generate_response(
+        conversation: &mut ConversationTree,
+        session: &mut Session,
         client: &CliClient,
         streaming: bool,
     ) -> Result<()> {
         use crate::spinner::Spinner;
         use futures::StreamExt;
         use owo_colors::OwoColorize;
+        use rullm_core::{CancellationToken, ChatStreamEvent};
         use std::io::{self, Write};
         use tokio::time;
 
-        conversation.push(("user".to_string(), input.to_string()));
+        // Attachments go with the last user message, also when it is retried
+        let parts = conversation.last_user_parts().to_vec();
         if streaming {
             let spinner = Spinner::new("Assistant:");
             spinner.start().await;
             time::sleep(time::Duration::from_millis(10)).await;
 
-            match client.stream_chat_raw(conversation.clone()).await {
+            // Ctrl+C while the answer streams stops it instead of exiting
+            let cancel = CancellationToken::new();
+            let _interrupt = cancel_on_ctrl_c(&cancel);
+            match client
+                .stream_chat_raw(conversation.messages(), &parts, Some(cancel.clone()))
+                .await
+            {
                 Ok(mut stream) => {
                     let mut full_response = String::new();
                     let mut first_token = true;
                     while let Some(result) = stream.next().await {
                         match result {
-                            Ok(token) => {
+                            Ok(ChatStreamEvent::Usage(usage)) => session.add_usage(&usage),
+                            Ok(ChatStreamEvent::Token(token)) => {
                                 if first_token {
                                     spinner.stop_and_replace(&format!(
                                         "{} ",
@@ -84,18 +162,26 @@ pub async fn run_interactive_chat(
                                 print!("{token}");
                                 io::stdout().flush()?;
                             }
+                            Ok(_) => {}
                             Err(err) => {
                                 spinner.stop_and_replace(&format!(
                                     "{} {}\n",
                                     "Error:".red().bold(),
                                     err
                                 ));
+                                discard_unanswered(conversation);
                                 return Ok(());
                             }
                         }
                     }
+                    if cancel.is_cancelled() {
+                        spinner.stop();
+                        println!("\n{}", "(interrupted)".dimmed());
+                        conversation.push("assistant", truncated(&full_response));
+                        return Ok(());
+                    }
                     println!();
-                    conversation.push(("assistant".to_string(), full_response));
+                    conversation.push("assistant", full_response);
 
                     if first_token {
                         spinner.stop_and_replace(&format!(
@@ -107,6 +193,7 @@ pub async fn run_interactive_chat(
                 }
                 Err(e) => {
                     spinner.stop_and_replace(&format!("{} {}\n", "Error:".red().bold(), e));
+                    discard_unanswered(conversation);
                 }
             }
         } else {
@@ -114,19 +201,32 @@ pub async fn run_interactive_chat(
             spinner.start().await;
             time::sleep(time::Duration::from_millis(10)).await;
 
-            // For non-streaming, we'll just use the last user message
-            // TODO: Implement proper conversation support
-            match client.chat(input).await {
+            let request = client.messages_request(&conversation.messages(), &parts);
+            let cancel = CancellationToken::new();
+            let _interrupt = cancel_on_ctrl_c(&cancel);
+            let response = cancel
+                .run_until_cancelled(client.chat_response(request))
+                .await;
+            let Some(response) = response else {
+                spinner.stop();
+                println!("\n{}", "(interrupted)".dimmed());
+                conversation.push("assistant", truncated(""));
+                return Ok(());
+            };
+            match response {
                 Ok(response) => {
+                    session.add_usage(&response.usage);
+                    let content = response.message.content;
                     spinner.stop_and_replace(&format!(
                         "{} {}\n",
                         "Assistant:".blue().bold(),
-                        response
+                        content
                     ));
-                    conversation.push(("assistant".to_string(), response));
+                    conversation.push("assistant", content);
                 }
                 Err(e) => {
                     spinner.stop_and_replace(&format!("{} {}\n", "Error:".red().bold(), e));
+                    discard_unanswered(conversation);
                 }
             }
         }
@@ -143,7 +243,15 @@ pub async fn run_interactive_chat(
                     continue;
                 }
                 if let Some(command) = SlashCommand::parse(input) {
-                    let result = handle_slash_command(command, &mut conversation, client).await?;
+                    let result = handle_slash_command(
+                        command,
+                        &mut conversation,
+                        &mut attachments,
+                        &mut session,
+                        &mut client,
+                        &context,
+                    )
+                    .await?;
                     match result {
                         HandleCommandResult::Quit => {
                             break;
@@ -152,11 +260,23 @@ pub async fn run_interactive_chat(
                             line_editor
                                 .run_edit_commands(&[EditCommand::InsertString(edited_input)]);
                         }
+                        HandleCommandResult::Retry => {
+                            generate_response(&mut conversation, &mut session, &client, streaming)
+                                .await?;
+                            autosave(&mut session, &conversation, &store);
+                        }
                         HandleCommandResult::NoOp => {}
                     }
                     continue;
                 }
-                process_user_message(input, &mut conversation, client, streaming).await?;
+                conversation.push_with_parts(
+                    "user",
+                    attachments.apply(input),
+                    attachments.parts().to_vec(),
+                );
+                generate_response(&mut conversation, &mut session, &client, streaming).await?;
+                attachments.clear();
+                autosave(&mut session, &conversation, &store);
             }
             Signal::CtrlC => {
                 let now = Instant::now();
@@ -181,3 +301,24 @@ pub async fn run_interactive_chat(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interrupted_and_failed_turns_keep_history_alternating() {
+        let mut conversation = ConversationTree::new();
+        conversation.push("user", "Hi".to_string());
+        conversation.push("assistant", truncated(""));
+        assert_eq!(conversation.history()[1].1, TRUNCATED_MARKER);
+
+        conversation.push("user", "Hello?".to_string());
+        discard_unanswered(&mut conversation);
+        conversation.push("user", "Are you there?".to_string());
+
+        let roles: Vec<&str> = conversation.history().iter().map(|(r, _)| *r).collect();
+        assert_eq!(roles, vec!["user", "assistant", "user"]);
+        assert_eq!(truncated("Par"), format!("Par\n\n{TRUNCATED_MARKER}"));
+    }
+}
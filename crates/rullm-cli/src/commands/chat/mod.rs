@@ -11,14 +11,15 @@ use reedline::{
     KeyModifiers, MenuBuilder, Reedline, ReedlineEvent, ReedlineMenu, Vi,
     default_emacs_keybindings, default_vi_insert_keybindings, default_vi_normal_keybindings,
 };
-use std::path::Path;
 use std::process::Command;
 use tempfile::NamedTempFile;
 
-use crate::args::{Cli, CliConfig, model_completer};
+use crate::args::{Cli, CliConfig, model_completer, session_completer};
 use crate::cli_helpers::resolve_model;
 use crate::client;
+use crate::commands::models::load_models_cache;
 use crate::output::OutputLevel;
+use crate::sessions::SessionStore;
 
 pub use completer::SlashCommandCompleter;
 pub use interactive::run_interactive_chat;
@@ -29,6 +30,10 @@ pub struct ChatArgs {
     /// Model to use in format: provider:model-name (e.g., openai:gpt-4, gemini:gemini-pro, anthropic:claude-3-sonnet)
     #[arg(short, long, add = ArgValueCompleter::new(model_completer))]
     pub model: Option<String>,
+
+    /// Resume a saved session (the most recent one if no id is given)
+    #[arg(long, value_name = "ID", add = ArgValueCompleter::new(session_completer))]
+    pub resume: Option<Option<String>>,
 }
 
 impl ChatArgs {
@@ -38,9 +43,34 @@ impl ChatArgs {
         cli_config: &CliConfig,
         cli: &Cli,
     ) -> Result<()> {
-        let model_str = resolve_model(&cli.model, &self.model, &cli_config.config.default_model)?;
+        let store = SessionStore::new(&cli_config.data_base_path);
+        let session = match &self.resume {
+            None => None,
+            Some(Some(id)) => Some(store.load(id)?),
+            Some(None) => Some(
+                store
+                    .latest()?
+                    .ok_or_else(|| anyhow::anyhow!("No saved sessions to resume"))?,
+            ),
+        };
+
+        // A resumed session keeps its model unless one is given explicitly
+        let cmd_model = self
+            .model
+            .clone()
+            .or_else(|| session.as_ref().map(|s| s.model.clone()));
+        let model_str = resolve_model(&cli.model, &cmd_model, &cli_config.config.default_model)?;
         let client = client::from_model(&model_str, cli, cli_config)?;
-        run_interactive_chat(&client, None, cli_config, !cli.no_streaming).await?;
+        // An explicit --system replaces the prompt stored in a resumed session
+        run_interactive_chat(
+            client,
+            cli.system.as_deref(),
+            session,
+            cli,
+            cli_config,
+            !cli.no_streaming,
+        )
+        .await?;
         Ok(())
     }
 }
@@ -61,8 +91,25 @@ fn add_common_keybindings(keybindings: &mut reedline::Keybindings) {
     );
 }
 
-fn setup_reedline(vim_mode: bool, data_path: &Path) -> Result<Reedline> {
-    let completer = Box::new(SlashCommandCompleter::new());
+/// Cached model names and user aliases, for `/model` completion
+fn model_suggestions(cli_config: &CliConfig) -> Vec<String> {
+    let mut models = load_models_cache(cli_config)
+        .ok()
+        .flatten()
+        .map(|cache| cache.models)
+        .unwrap_or_default();
+    if let Ok(resolver) =
+        crate::aliases::get_global_alias_resolver(&cli_config.config_base_path).read()
+    {
+        models.extend(resolver.list_aliases().into_iter().map(|(alias, _)| alias));
+    }
+    models
+}
+
+fn setup_reedline(cli_config: &CliConfig) -> Result<Reedline> {
+    let vim_mode = cli_config.config.vi_mode;
+    let data_path = &cli_config.data_base_path;
+    let completer = Box::new(SlashCommandCompleter::new(model_suggestions(cli_config)));
     let completion_menu = Box::new(ColumnarMenu::default().with_name("completion_menu"));
     let edit_mode: Box<dyn reedline::EditMode> = if vim_mode {
         let mut vi_insert_keybindings = default_vi_insert_keybindings();
@@ -1,4 +1,8 @@
-use crate::cli_client::CliClient;
+use crate::args::Cli;
+use crate::attachments::Attachments;
+use crate::cli_client::{CliClient, CliConfig, OPTION_NAMES};
+use crate::conversation::ConversationTree;
+use crate::sessions::{Session, SessionStore};
 use anyhow::Result;
 use owo_colors::OwoColorize;
 
@@ -9,6 +13,16 @@ pub enum SlashCommand {
     Help,
     Quit,
     Edit,
+    EditMessage(String),
+    Retry,
+    Undo,
+    Branch(String),
+    Attach(String),
+    Model(String),
+    Set(String),
+    Options,
+    Save(String),
+    Load(String),
     Unknown(String),
 }
 
@@ -44,7 +58,19 @@ impl SlashCommand {
             "clear" => SlashCommand::Clear,
             "help" => SlashCommand::Help,
             "quit" | "exit" => SlashCommand::Quit,
-            "edit" => SlashCommand::Edit,
+            "edit" => match parts.get(1).map(|arg| arg.trim()) {
+                Some(arg) if !arg.is_empty() => SlashCommand::EditMessage(arg.to_string()),
+                _ => SlashCommand::Edit,
+            },
+            "retry" => SlashCommand::Retry,
+            "undo" => SlashCommand::Undo,
+            "branch" => SlashCommand::Branch(parts.get(1).unwrap_or(&"").trim().to_string()),
+            "attach" => SlashCommand::Attach(parts.get(1).unwrap_or(&"").trim().to_string()),
+            "model" => SlashCommand::Model(parts.get(1).unwrap_or(&"").trim().to_string()),
+            "set" => SlashCommand::Set(parts.get(1).unwrap_or(&"").trim().to_string()),
+            "options" => SlashCommand::Options,
+            "save" => SlashCommand::Save(parts.get(1).unwrap_or(&"").trim().to_string()),
+            "load" => SlashCommand::Load(parts.get(1).unwrap_or(&"").trim().to_string()),
             _ => SlashCommand::Unknown(command),
         })
     }
@@ -54,21 +80,96 @@ pub enum HandleCommandResult {
     NoOp,
     Quit,
     Edit(String),
+    /// Regenerate the answer to the last user message
+    Retry,
+}
+
+/// Save the session, reporting failures without leaving the chat
+pub(crate) fn autosave(
+    session: &mut Session,
+    conversation: &ConversationTree,
+    store: &SessionStore,
+) {
+    session.set_conversation(conversation);
+    if let Err(e) = store.save(session) {
+        println!("{} {e}", "Failed to save session:".red());
+    }
+}
+
+/// Shorten a message to its first line for listings
+fn preview(message: &str) -> String {
+    let first = message.lines().next().unwrap_or("").trim();
+    if first.chars().count() > 60 {
+        format!("{}...", first.chars().take(57).collect::<String>())
+    } else {
+        first.to_string()
+    }
+}
+
+/// Open $EDITOR on `initial` and return the trimmed result, if any
+fn edit_in_editor(initial: &str) -> Result<Option<String>> {
+    use std::io::Read;
+    use std::process::Command;
+    use tempfile::NamedTempFile;
+
+    let tmp = NamedTempFile::new()?;
+    std::fs::write(tmp.path(), initial)?;
+
+    let editor = super::get_preferred_editor();
+    let status = Command::new(&editor).arg(tmp.path()).status();
+
+    match status {
+        Ok(status) if status.success() => {
+            let mut file = tmp.reopen()?;
+            let mut contents = String::new();
+            file.read_to_string(&mut contents)?;
+            let contents = contents.trim().to_string();
+            if contents.is_empty() {
+                println!("{}", "No input provided in editor.".yellow());
+                Ok(None)
+            } else {
+                Ok(Some(contents))
+            }
+        }
+        Ok(_) => {
+            println!("{}", "Editor exited with error".red());
+            Ok(None)
+        }
+        Err(e) => {
+            println!("{} {}", "Failed to launch editor:".red(), e);
+            println!(
+                "{} {}",
+                "Try setting $EDITOR environment variable or installing neovim".red(),
+                e
+            );
+            Ok(None)
+        }
+    }
+}
+
+/// Settings and storage shared by every command in a chat
+pub struct ChatContext<'a> {
+    pub cli: &'a Cli,
+    pub config: &'a crate::args::CliConfig,
+    pub store: &'a SessionStore,
 }
 
 pub async fn handle_slash_command(
     command: SlashCommand,
-    conversation: &mut Vec<(String, String)>,
-    _client: &CliClient,
+    conversation: &mut ConversationTree,
+    attachments: &mut Attachments,
+    session: &mut Session,
+    client: &mut CliClient,
+    context: &ChatContext<'_>,
 ) -> Result<HandleCommandResult> {
+    let store = context.store;
     match command {
         SlashCommand::System(msg) => {
             if msg.is_empty() {
                 println!("{}", "Usage: /system <message>".yellow());
                 return Ok(HandleCommandResult::NoOp);
             }
-            conversation.retain(|(role, _)| role != "system");
-            conversation.insert(0, ("system".to_string(), msg.clone()));
+            conversation.set_system(Some(msg.clone()));
             println!(
                 "{} {} {} {}",
                 "System".green().bold(),
@@ -78,8 +179,167 @@ pub async fn handle_slash_command(
             );
             Ok(HandleCommandResult::NoOp)
         }
+        SlashCommand::Attach(path) => {
+            if path.is_empty() {
+                println!("{}", "Usage: /attach <path>".yellow());
+                if !attachments.is_empty() {
+                    println!(
+                        "{} {}",
+                        "Pending:".green(),
+                        attachments.names().join(", ").dimmed()
+                    );
+                }
+                return Ok(HandleCommandResult::NoOp);
+            }
+            match attachments.add(std::path::Path::new(&path)) {
+                Ok(mime) => println!(
+                    "{} {} {}",
+                    "Attached".green(),
+                    path,
+                    format!("({mime}), sent with your next message").dimmed()
+                ),
+                Err(e) => println!("{} {e}", "Error:".red()),
+            }
+            Ok(HandleCommandResult::NoOp)
+        }
+        SlashCommand::Model(model) => {
+            if model.is_empty() {
+                println!(
+                    "{} {}:{}",
+                    "Current model:".green(),
+                    client.provider_name(),
+                    client.model_name()
+                );
+                println!("{}", "Usage: /model <alias or provider:model>".yellow());
+                return Ok(HandleCommandResult::NoOp);
+            }
+            match crate::client::from_model(&model, context.cli, context.config) {
+                Ok(mut new_client) => {
+                    // Keep options changed with /set, minus those the new
+                    // provider does not accept
+                    let mut options = client.config().clone();
+                    for name in options.unsupported(new_client.options_provider()) {
+                        options.set(name, None)?;
+                        println!(
+                            "{}",
+                            format!(
+                                "Dropped {name}: not supported by {}",
+                                new_client.provider_name()
+                            )
+                            .dimmed()
+                        );
+                    }
+                    *new_client.config_mut() = options;
+                    *client = new_client;
+                    session.model = format!("{}:{}", client.provider_name(), client.model_name());
+                    println!("{} {}", "Switched to".green(), session.model.blue().bold());
+                }
+                Err(e) => println!("{} {e:#}", "Error:".red()),
+            }
+            Ok(HandleCommandResult::NoOp)
+        }
+        SlashCommand::Set(args) => {
+            let mut args = args.splitn(2, ' ');
+            let key = args.next().unwrap_or("");
+            if key.is_empty() {
+                println!("{}", "Usage: /set <option> [value]".yellow());
+                println!(
+                    "{} {}",
+                    "Options:".green(),
+                    OPTION_NAMES.join(", ").dimmed()
+                );
+                return Ok(HandleCommandResult::NoOp);
+            }
+            let value = args.next().map(str::trim).filter(|v| !v.is_empty());
+            let provider = client.provider_name();
+            let mut options = client.config().clone();
+            match options.set(key, value) {
+                Ok(())
+                    if value.is_some() && !CliConfig::supports(client.options_provider(), key) =>
+                {
+                    println!("{} {provider} does not support {key}", "Error:".red());
+                }
+                Ok(()) => {
+                    *client.config_mut() = options;
+                    match value {
+                        Some(value) => println!("{} {key} = {value}", "Set".green()),
+                        None => println!("{} {key} {}", "Reset".green(), "to the default".dimmed()),
+                    }
+                }
+                Err(e) => println!("{} {e}", "Error:".red()),
+            }
+            Ok(HandleCommandResult::NoOp)
+        }
+        SlashCommand::Options => {
+            println!("{}", "Options:".green().bold());
+            let provider = client.provider_name();
+            for (name, value) in client.config().entries() {
+                if !CliConfig::supports(client.options_provider(), name) {
+                    println!(
+                        "  {} {}",
+                        name.dimmed(),
+                        format!("(not supported by {provider})").dimmed()
+                    );
+                    continue;
+                }
+                match value {
+                    Some(value) => println!("  {} = {value}", name.yellow()),
+                    None => println!("  {} = {}", name.yellow(), "(default)".dimmed()),
+                }
+            }
+            Ok(HandleCommandResult::NoOp)
+        }
+        SlashCommand::Save(id) => {
+            if !id.is_empty() && id != session.id {
+                if store.contains(&id) {
+                    println!("{}", format!("Overwriting saved session {id}").dimmed());
+                }
+                session.id = id;
+            }
+            session.set_conversation(conversation);
+            match store.save(session) {
+                Ok(()) => println!("{} {}", "Saved session".green(), session.id.yellow()),
+                Err(e) => println!("{} {e}", "Error:".red()),
+            }
+            Ok(HandleCommandResult::NoOp)
+        }
+        SlashCommand::Load(id) => {
+            if id.is_empty() {
+                println!("{}", "Usage: /load <session id>".yellow());
+                return Ok(HandleCommandResult::NoOp);
+            }
+            match store.load(&id) {
+                Ok(loaded) => {
+                    *conversation = loaded.conversation();
+                    println!(
+                        "{} {} ({} messages)",
+                        "Loaded session".green(),
+                        loaded.id.yellow(),
+                        loaded.messages.len()
+                    );
+                    if loaded.model != session.model {
+                        println!(
+                            "{}",
+                            format!(
+                                "Recorded with {}; continuing with {}",
+                                loaded.model, session.model
+                            )
+                            .dimmed()
+                        );
+                    }
+                    *session = Session {
+                        model: session.model.clone(),
+                        ..loaded
+                    };
+                }
+                Err(e) => println!("{} {e}", "Error:".red()),
+            }
+            Ok(HandleCommandResult::NoOp)
+        }
         SlashCommand::Clear => {
             conversation.clear();
+            // Later messages go to a new session instead of overwriting this one
+            *session = Session::new(session.model.clone());
             println!("{}", "Conversation cleared.".green());
             Ok(HandleCommandResult::NoOp)
         }
@@ -95,54 +355,123 @@ pub async fn handle_slash_command(
                 "  {} - Clear conversation history",
                 "/clear (clear)".yellow()
             );
+            println!(
+                "  {} - Attach an image, PDF or text file to the next message",
+                "/attach <path>".yellow()
+            );
+            println!(
+                "  {} - Save the session, optionally under a new id",
+                "/save [id]".yellow()
+            );
+            println!("  {} - Load a saved session", "/load <id>".yellow());
+            println!(
+                "  {} - Switch model, keeping the conversation",
+                "/model <alias or provider:model>".yellow()
+            );
+            println!(
+                "  {} - Set a sampling option, or reset it without a value",
+                "/set <option> [value]".yellow()
+            );
+            println!("  {} - Show sampling options", "/options".yellow());
             println!("  {} - Show this help", "/help (help)".yellow());
             println!(
                 "  {} - Edit next message in $EDITOR",
                 "/edit (edit)".yellow()
             );
+            println!(
+                "  {} - Edit your Nth message and continue from there",
+                "/edit <N>".yellow()
+            );
+            println!("  {} - Regenerate the last answer", "/retry".yellow());
+            println!("  {} - Drop the last exchange", "/undo".yellow());
+            println!(
+                "  {} - List branches, or switch to one",
+                "/branch [N]".yellow()
+            );
             println!("  {} - Exit chat", "/quit or /exit (quit or exit)".yellow());
             Ok(HandleCommandResult::NoOp)
         }
         SlashCommand::Quit => Ok(HandleCommandResult::Quit),
-        SlashCommand::Edit => {
-            use std::io::Read;
-            use std::process::Command;
-            use tempfile::NamedTempFile;
-
-            let tmp = NamedTempFile::new()?;
-            // Optionally, pre-fill with last user message or blank
-            // std::fs::write(tmp.path(), "")?;
-
-            let editor = super::get_preferred_editor();
-            let status = Command::new(&editor).arg(tmp.path()).status();
-
-            match status {
-                Ok(status) if status.success() => {
-                    let mut file = tmp.reopen()?;
-                    let mut contents = String::new();
-                    file.read_to_string(&mut contents)?;
-                    let contents = contents.trim().to_string();
-                    if contents.is_empty() {
-                        println!("{}", "No input provided in editor.".yellow());
-                        Ok(HandleCommandResult::NoOp)
-                    } else {
-                        Ok(HandleCommandResult::Edit(contents))
-                    }
+        SlashCommand::Edit => Ok(match edit_in_editor("")? {
+            Some(contents) => HandleCommandResult::Edit(contents),
+            None => HandleCommandResult::NoOp,
+        }),
+        SlashCommand::EditMessage(arg) => {
+            let user_messages = conversation.user_messages();
+            let original = arg
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| Some((n, *user_messages.get(n.checked_sub(1)?)?)));
+            let Some((n, original)) = original else {
+                println!("{}", "Usage: /edit <message number>".yellow());
+                for (i, message) in user_messages.iter().enumerate() {
+                    println!("  {} {}", format!("{}.", i + 1).yellow(), preview(message));
                 }
-                Ok(_) => {
-                    println!("{}", "Editor exited with error".red());
-                    Ok(HandleCommandResult::NoOp)
+                return Ok(HandleCommandResult::NoOp);
+            };
+            let Some(contents) = edit_in_editor(original)? else {
+                return Ok(HandleCommandResult::NoOp);
+            };
+            conversation.edit_user_message(n);
+            println!(
+                "{}",
+                format!("Rewound to before message {n}; send it to start a new branch").dimmed()
+            );
+            Ok(HandleCommandResult::Edit(contents))
+        }
+        SlashCommand::Retry => {
+            if conversation.retry().is_none() {
+                println!("{}", "Nothing to retry.".yellow());
+                return Ok(HandleCommandResult::NoOp);
+            }
+            Ok(HandleCommandResult::Retry)
+        }
+        SlashCommand::Undo => {
+            if conversation.undo() {
+                println!(
+                    "{} {}",
+                    "Removed the last exchange.".green(),
+                    "It is kept as a branch, see /branch".dimmed()
+                );
+                autosave(session, conversation, store);
+            } else {
+                println!("{}", "Nothing to undo.".yellow());
+            }
+            Ok(HandleCommandResult::NoOp)
+        }
+        SlashCommand::Branch(arg) => {
+            if arg.is_empty() {
+                let branches = conversation.branches();
+                if branches.is_empty() {
+                    println!("{}", "No branches yet.".yellow());
+                    return Ok(HandleCommandResult::NoOp);
                 }
-                Err(e) => {
-                    println!("{} {}", "Failed to launch editor:".red(), e);
+                println!("{}", "Branches:".green().bold());
+                for (i, (current, len, last_user)) in branches.into_iter().enumerate() {
+                    let marker = if current { "*" } else { " " };
                     println!(
-                        "{} {}",
-                        "Try setting $EDITOR environment variable or installing neovim".red(),
-                        e
+                        "{marker} {} {} {}",
+                        format!("{}.", i + 1).yellow(),
+                        preview(last_user),
+                        format!("({len} messages)").dimmed()
                     );
-                    Ok(HandleCommandResult::NoOp)
                 }
+                println!("{}", "Switch with /branch <number>".dimmed());
+                return Ok(HandleCommandResult::NoOp);
+            }
+            match arg.parse::<usize>() {
+                Ok(n) if conversation.switch_branch(n) => {
+                    println!(
+                        "{} {} {}",
+                        "Switched to branch".green(),
+                        n.to_string().yellow(),
+                        format!("({} messages)", conversation.history().len()).dimmed()
+                    );
+                    autosave(session, conversation, store);
+                }
+                _ => println!("{} {arg}", "No such branch:".red()),
             }
+            Ok(HandleCommandResult::NoOp)
         }
         SlashCommand::Unknown(cmd) => {
             println!(
@@ -3,7 +3,8 @@ use clap::Subcommand;
 use crate::cli_client::CliClient;
 use anyhow::Result;
 use futures::StreamExt;
-use rullm_core::LlmError;
+use rullm_core::structured::StructuredOutput;
+use rullm_core::{ChatResponse, ChatStreamEvent, ContentPart, LlmError};
 use std::io::{self, Write};
 
 use crate::spinner::Spinner;
@@ -15,6 +16,7 @@ pub mod alias;
 pub mod chat;
 pub mod completions;
 pub mod info;
+pub mod sessions;
 pub mod templates;
 
 pub mod keys;
@@ -27,13 +29,22 @@ pub use completions::CompletionsArgs;
 pub use info::InfoArgs;
 pub use keys::KeysArgs;
 pub use models::ModelsArgs;
+pub use sessions::SessionsArgs;
 
 // Example strings for after_long_help
 const CHAT_EXAMPLES: &str = r#"EXAMPLES:
   rullm chat                               # Start chat with default model
   rullm chat -m openai/gpt-4              # Chat with GPT-4
   rullm chat -m claude                     # Chat using claude alias
-  rullm chat -m gemini/gemini-pro          # Chat with Gemini Pro"#;
+  rullm chat -m gemini/gemini-pro          # Chat with Gemini Pro
+  rullm chat --resume                      # Continue the most recent session
+  rullm chat --resume 20250101-120000      # Continue a specific session"#;
+
+const SESSIONS_EXAMPLES: &str = r#"EXAMPLES:
+  rullm sessions list                      # List saved chat sessions
+  rullm sessions show 20250101-120000      # Show a session's conversation
+  rullm sessions export 20250101-120000 --format json -o chat.json
+  rullm sessions delete 20250101-120000    # Delete a session"#;
 
 const MODELS_EXAMPLES: &str = r#"EXAMPLES:
   rullm models list                        # List cached models
@@ -86,6 +97,9 @@ pub enum Commands {
         after_long_help = "EXAMPLES:\n  rullm templates list\n  rullm templates show code-review\n  rullm templates remove old-template"
     )]
     Templates(templates::TemplatesArgs),
+    /// Manage saved chat sessions
+    #[command(after_long_help = SESSIONS_EXAMPLES)]
+    Sessions(SessionsArgs),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -116,72 +130,57 @@ pub async fn run_single_query(
     client: &CliClient,
     query: &str,
     system_prompt: Option<&str>,
+    attachments: &[ContentPart],
     streaming: bool,
 ) -> Result<(), LlmError> {
     if streaming {
-        // Use token-by-token streaming for real-time output
-        if let Some(_system) = system_prompt {
-            // TODO: Need to implement system prompt handling in streaming
-            // For now, fall back to non-streaming
-            let spinner = Spinner::new("Generating response");
-            spinner.start().await;
-
-            // Small delay to ensure spinner starts
-            tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
-
-            match client.chat(query).await {
-                Ok(response) => {
-                    spinner.stop_and_replace(&format!("{response}\n"));
-                }
-                Err(e) => {
-                    spinner.stop_and_replace(&format!("Error: {e}\n"));
-                    return Err(e);
-                }
-            }
-        } else {
-            // Show spinner while waiting for first token
-            let spinner = Spinner::new("Generating response");
-            spinner.start().await;
-
-            // Small delay to ensure spinner starts
-            tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
-
-            // Simple query streaming
-            let messages = vec![("user".to_string(), query.to_string())];
-
-            match client.stream_chat_raw(messages).await {
-                Ok(mut stream) => {
-                    let mut first_token = true;
-                    while let Some(result) = stream.next().await {
-                        match result {
-                            Ok(token) => {
-                                if first_token {
-                                    spinner.stop();
-                                    first_token = false;
-                                }
-                                print!("{token}");
-                                io::stdout()
-                                    .flush()
-                                    .map_err(|e| LlmError::unknown(e.to_string()))?;
-                            }
-                            Err(err) => {
-                                spinner.stop_and_replace(&format!("Error: {err}\n"));
-                                return Err(err);
+        // Show spinner while waiting for first token
+        let spinner = Spinner::new("Generating response");
+        spinner.start().await;
+
+        // Small delay to ensure spinner starts
+        tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+
+        // Simple query streaming
+        let messages = system_prompt
+            .map(|system| ("system".to_string(), system.to_string()))
+            .into_iter()
+            .chain(std::iter::once(("user".to_string(), query.to_string())))
+            .collect();
+
+        match client.stream_chat_raw(messages, attachments, None).await {
+            Ok(mut stream) => {
+                let mut first_token = true;
+                while let Some(result) = stream.next().await {
+                    match result {
+                        Ok(ChatStreamEvent::Token(token)) => {
+                            if first_token {
+                                spinner.stop();
+                                first_token = false;
                             }
+                            print!("{token}");
+                            io::stdout()
+                                .flush()
+                                .map_err(|e| LlmError::unknown(e.to_string()))?;
+                        }
+                        Ok(_) => {}
+                        Err(err) => {
+                            spinner.stop_and_replace(&format!("Error: {err}\n"));
+                            return Err(err);
                         }
-                    }
-                    println!(); // Final newline
-
-                    // Ensure spinner is stopped if no tokens were received
-                    if first_token {
-                        spinner.stop_and_replace("(No response received)\n");
                     }
                 }
-                Err(e) => {
-                    spinner.stop_and_replace(&format!("Error: {e}\n"));
-                    return Err(e);
+                println!(); // Final newline
+
+                // Ensure spinner is stopped if no tokens were received
+                if first_token {
+                    spinner.stop_and_replace("(No response received)\n");
                 }
             }
+            Err(e) => {
+                spinner.stop_and_replace(&format!("Error: {e}\n"));
+                return Err(e);
+            }
         }
     } else {
         // Non-streaming path with spinner
@@ -191,8 +190,7 @@ pub async fn run_single_query(
         // Small delay to ensure spinner starts
         tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
 
-        // TODO: Implement system prompt support properly
-        let result = client.chat(query).await;
+        let result = client.chat(query, system_prompt, attachments).await;
 
         match result {
             Ok(response) => {
@@ -208,6 +206,66 @@ pub async fn run_single_query(
     Ok(())
 }
 
+/// Run a query and print the response envelope as JSON
+pub async fn run_json_query(
+    client: &CliClient,
+    query: &str,
+    system_prompt: Option<&str>,
+    attachments: &[ContentPart],
+) -> Result<(), LlmError> {
+    let spinner = Spinner::new("Generating response");
+    spinner.start().await;
+
+    let request = client.query_request(query, system_prompt, attachments);
+    let result = client.chat_response(request).await;
+    spinner.stop();
+
+    let envelope = response_envelope(client.provider_name(), &result?);
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&envelope).unwrap_or_default()
+    );
+    Ok(())
+}
+
+/// Run a query that must answer with JSON matching `schema` and print it
+///
+/// Fails with a validation error when the model's output still does not
+/// match the schema after re-prompting.
+pub async fn run_structured_query(
+    client: &CliClient,
+    query: &str,
+    system_prompt: Option<&str>,
+    attachments: &[ContentPart],
+    schema: serde_json::Value,
+) -> Result<(), LlmError> {
+    let spinner = Spinner::new("Generating response");
+    spinner.start().await;
+
+    let request = client.query_request(query, system_prompt, attachments);
+    let result = StructuredOutput::new(client.as_chat_completion(), client.model_name())
+        .generate_structured::<serde_json::Value>(request, schema)
+        .await;
+    spinner.stop();
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&result?).unwrap_or_default()
+    );
+    Ok(())
+}
+
+/// JSON envelope printed by `--format json`
+fn response_envelope(provider: &str, response: &ChatResponse) -> serde_json::Value {
+    serde_json::json!({
+        "text": response.message.content,
+        "provider": provider,
+        "model": response.model,
+        "usage": response.usage,
+        "finish_reason": response.finish_reason,
+    })
+}
+
 fn format_duration(duration: chrono::Duration) -> String {
     let days = duration.num_days();
     let hours = duration.num_hours() % 24;
@@ -220,3 +278,35 @@ fn format_duration(duration: chrono::Duration) -> String {
         (d, h) => format!("{d} days {h}h"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rullm_core::{ChatMessage, TokenUsage};
+
+    #[test]
+    fn test_response_envelope() {
+        let response = ChatResponse {
+            message: ChatMessage::assistant("Hello"),
+            model: "gpt-4o-mini".to_string(),
+            usage: TokenUsage {
+                prompt_tokens: 3,
+                completion_tokens: 1,
+                total_tokens: 4,
+            },
+            finish_reason: Some("stop".to_string()),
+            provider_metadata: None,
+        };
+
+        assert_eq!(
+            response_envelope("openai", &response),
+            serde_json::json!({
+                "text": "Hello",
+                "provider": "openai",
+                "model": "gpt-4o-mini",
+                "usage": {"prompt_tokens": 3, "completion_tokens": 1, "total_tokens": 4},
+                "finish_reason": "stop"
+            })
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use crate::args::session_completer;
+use crate::args::{Cli, CliConfig};
+use crate::output::{self, OutputLevel};
+use crate::sessions::SessionStore;
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand, ValueEnum};
+use clap_complete::engine::ArgValueCompleter;
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct SessionsArgs {
+    #[command(subcommand)]
+    pub action: SessionAction,
+}
+
+#[derive(Subcommand)]
+pub enum SessionAction {
+    /// List saved chat sessions, most recent first
+    List,
+    /// Show a session's conversation
+    Show {
+        /// Session id
+        #[arg(value_name = "ID", add = ArgValueCompleter::new(session_completer))]
+        id: String,
+    },
+    /// Delete a saved session
+    Delete {
+        /// Session id
+        #[arg(value_name = "ID", add = ArgValueCompleter::new(session_completer))]
+        id: String,
+    },
+    /// Export a session as Markdown or JSON
+    Export {
+        /// Session id
+        #[arg(value_name = "ID", add = ArgValueCompleter::new(session_completer))]
+        id: String,
+        /// Export format
+        #[arg(long, value_enum, default_value_t = ExportFormat::Markdown)]
+        format: ExportFormat,
+        /// Write to this file instead of stdout
+        #[arg(long, short = 'o', value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Markdown,
+    Json,
+}
+
+impl SessionsArgs {
+    pub async fn run(
+        &self,
+        output_level: OutputLevel,
+        cli_config: &CliConfig,
+        _cli: &Cli,
+    ) -> Result<()> {
+        let store = SessionStore::new(&cli_config.data_base_path);
+
+        match &self.action {
+            SessionAction::List => {
+                let sessions = store.list()?;
+                if sessions.is_empty() {
+                    output::note("No saved sessions.", output_level);
+                } else {
+                    output::heading("Saved sessions:", output_level);
+                    for session in sessions {
+                        println!(
+                            "  {}  {}  {} messages  {}",
+                            session.id,
+                            session.model,
+                            session.messages.len(),
+                            session.title()
+                        );
+                    }
+                }
+            }
+            SessionAction::Show { id } => {
+                let session = store.load(id)?;
+                println!("{}", session.to_markdown());
+            }
+            SessionAction::Delete { id } => match store.delete(id) {
+                Ok(true) => output::success(&format!("Deleted session '{id}'."), output_level),
+                Ok(false) => output::warning(&format!("Session '{id}' not found."), output_level),
+                Err(e) => output::error(
+                    &format!("Failed to delete session '{id}': {e}"),
+                    output_level,
+                ),
+            },
+            SessionAction::Export { id, format, output } => {
+                let session = store.load(id)?;
+                let content = match format {
+                    ExportFormat::Markdown => session.to_markdown(),
+                    ExportFormat::Json => serde_json::to_string_pretty(&session)?,
+                };
+                match output {
+                    Some(path) => {
+                        std::fs::write(path, content)
+                            .with_context(|| format!("Failed to write {}", path.display()))?;
+                        output::success(
+                            &format!("Exported session '{id}' to {}.", path.display()),
+                            output_level,
+                        );
+                    }
+                    None => println!("{content}"),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
@@ -12,6 +13,9 @@ pub struct Config {
     pub default_model: Option<String>,
     /// Enable vim mode in interactive chat
     pub vi_mode: bool,
+    /// User-defined endpoints from `[providers.<name>]`, used as `<name>:<model>`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub providers: BTreeMap<String, CustomProvider>,
 }
 
 impl Default for Config {
@@ -19,10 +23,36 @@ impl Default for Config {
         Self {
             default_model: Some("openai/gpt-4o-mini".to_string()),
             vi_mode: false,
+            providers: BTreeMap::new(),
         }
     }
 }
 
+/// API dialect spoken by a custom endpoint
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WireFormat {
+    /// `/chat/completions`, as served by vLLM, LM Studio, llama.cpp and most gateways
+    #[default]
+    OpenAI,
+    /// Anthropic's `/v1/messages`
+    Anthropic,
+}
+
+/// An endpoint configured in a `[providers.<name>]` section
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CustomProvider {
+    /// Base URL, e.g. `http://localhost:8000/v1`
+    pub base_url: String,
+    /// Environment variable holding the API key; local servers often need none
+    pub api_key_env: Option<String>,
+    /// Extra headers sent with every request
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub format: WireFormat,
+}
+
 impl Config {
     pub fn load(base_path: &Path) -> Result<Config> {
         let config_path = base_path.join(CONFIG_FILE_NAME);
@@ -53,3 +83,45 @@ impl Config {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_custom_providers() {
+        let config: Config = toml::from_str(
+            r#"
+            default_model = "mylab:qwen2"
+
+            [providers.mylab]
+            base_url = "http://localhost:8000/v1"
+            api_key_env = "MYLAB_API_KEY"
+            headers = { "X-Team" = "research" }
+
+            [providers.gateway]
+            base_url = "https://llm.internal.example.com"
+            format = "anthropic"
+            "#,
+        )
+        .unwrap();
+
+        let mylab = &config.providers["mylab"];
+        assert_eq!(mylab.base_url, "http://localhost:8000/v1");
+        assert_eq!(mylab.api_key_env.as_deref(), Some("MYLAB_API_KEY"));
+        assert_eq!(mylab.headers["X-Team"], "research");
+        assert_eq!(mylab.format, WireFormat::OpenAI);
+
+        let gateway = &config.providers["gateway"];
+        assert_eq!(gateway.api_key_env, None);
+        assert_eq!(gateway.format, WireFormat::Anthropic);
+    }
+
+    #[test]
+    fn test_default_config_omits_providers() {
+        let content = toml::to_string_pretty(&Config::default()).unwrap();
+        assert!(!content.contains("providers"));
+        let config: Config = toml::from_str(&content).unwrap();
+        assert!(config.providers.is_empty());
+    }
+}
@@ -3,4 +3,5 @@ pub const MODEL_FILE_NAME: &str = "models.json";
 pub const ALIASES_CONFIG_FILE: &str = "aliases.toml";
 pub const KEYS_CONFIG_FILE: &str = "keys.toml";
 pub const TEMPLATES_DIR_NAME: &str = "templates";
+pub const SESSIONS_DIR_NAME: &str = "sessions";
 pub const BINARY_NAME: &str = env!("CARGO_BIN_NAME");
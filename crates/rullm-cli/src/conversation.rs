@@ -0,0 +1,314 @@
+use rullm_core::ContentPart;
+use serde::{Deserialize, Serialize};
+
+/// A message in the conversation tree
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Node {
+    /// Index of the previous message, `None` for the first one
+    parent: Option<usize>,
+    role: String,
+    content: String,
+    /// Images and documents attached to a user message
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    parts: Vec<ContentPart>,
+}
+
+/// Chat history stored as a tree so that retried, undone and edited
+/// exchanges stay reachable as branches
+///
+/// The current branch is the path from the root to `head`. New messages are
+/// appended below `head`; moving `head` back and appending again starts a new
+/// branch next to the old one.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConversationTree {
+    system: Option<String>,
+    nodes: Vec<Node>,
+    head: Option<usize>,
+}
+
+impl ConversationTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a single-branch tree from `(role, content)` pairs
+    pub fn from_messages<I>(system: Option<String>, messages: I) -> Self
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let mut tree = Self {
+            system,
+            ..Self::default()
+        };
+        for (role, content) in messages {
+            tree.push(&role, content);
+        }
+        tree
+    }
+
+    pub fn system(&self) -> Option<&str> {
+        self.system.as_deref()
+    }
+
+    pub fn set_system(&mut self, system: Option<String>) {
+        self.system = system;
+    }
+
+    /// True when any message was ever added, on any branch
+    pub fn has_nodes(&self) -> bool {
+        !self.nodes.is_empty()
+    }
+
+    /// Remove every message and the system prompt
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Append a message to the current branch
+    pub fn push(&mut self, role: &str, content: String) {
+        self.push_with_parts(role, content, Vec::new());
+    }
+
+    /// Append a message with attached images or documents
+    pub fn push_with_parts(&mut self, role: &str, content: String, parts: Vec<ContentPart>) {
+        self.nodes.push(Node {
+            parent: self.head,
+            role: role.to_string(),
+            content,
+            parts,
+        });
+        self.head = Some(self.nodes.len() - 1);
+    }
+
+    /// Node indices from the root to `leaf`
+    fn path_to(&self, leaf: Option<usize>) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut current = leaf;
+        while let Some(index) = current {
+            path.push(index);
+            current = self.nodes[index].parent;
+        }
+        path.reverse();
+        path
+    }
+
+    /// Messages on the current branch, without the system prompt
+    pub fn history(&self) -> Vec<(&str, &str)> {
+        self.path_to(self.head)
+            .into_iter()
+            .map(|i| (self.nodes[i].role.as_str(), self.nodes[i].content.as_str()))
+            .collect()
+    }
+
+    /// The current branch as `(role, content)` pairs, system prompt first
+    pub fn messages(&self) -> Vec<(String, String)> {
+        self.system
+            .iter()
+            .map(|system| ("system".to_string(), system.clone()))
+            .chain(
+                self.history()
+                    .into_iter()
+                    .map(|(role, content)| (role.to_string(), content.to_string())),
+            )
+            .collect()
+    }
+
+    /// Indices of the user messages on the current branch
+    fn user_nodes(&self) -> Vec<usize> {
+        self.path_to(self.head)
+            .into_iter()
+            .filter(|&i| self.nodes[i].role == "user")
+            .collect()
+    }
+
+    /// Drop the last exchange from the current branch
+    ///
+    /// Returns false when there is no user message to undo.
+    pub fn undo(&mut self) -> bool {
+        match self.user_nodes().last() {
+            Some(&last) => {
+                self.head = self.nodes[last].parent;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Rewind to the last user message so its answer can be regenerated
+    ///
+    /// Returns the user message, or `None` if there is nothing to retry.
+    pub fn retry(&mut self) -> Option<&str> {
+        let last = *self.user_nodes().last()?;
+        self.head = Some(last);
+        Some(&self.nodes[last].content)
+    }
+
+    /// Rewind to just before the `n`th (1-based) user message on the current
+    /// branch, returning its text so it can be edited and sent again
+    pub fn edit_user_message(&mut self, n: usize) -> Option<String> {
+        let index = *self.user_nodes().get(n.checked_sub(1)?)?;
+        self.head = self.nodes[index].parent;
+        Some(self.nodes[index].content.clone())
+    }
+
+    /// Attachments of the last user message on the current branch, which are
+    /// sent again when its answer is regenerated
+    pub fn last_user_parts(&self) -> &[ContentPart] {
+        self.user_nodes()
+            .last()
+            .map_or(&[], |&i| self.nodes[i].parts.as_slice())
+    }
+
+    /// The user messages on the current branch, in order
+    pub fn user_messages(&self) -> Vec<&str> {
+        self.user_nodes()
+            .into_iter()
+            .map(|i| self.nodes[i].content.as_str())
+            .collect()
+    }
+
+    /// Tips of every branch, in the order they were created
+    fn leaves(&self) -> Vec<usize> {
+        let mut has_children = vec![false; self.nodes.len()];
+        for node in &self.nodes {
+            if let Some(parent) = node.parent {
+                has_children[parent] = true;
+            }
+        }
+        (0..self.nodes.len())
+            .filter(|&i| !has_children[i])
+            .collect()
+    }
+
+    /// One entry per branch: whether it is the current one, its message count
+    /// and its last user message
+    pub fn branches(&self) -> Vec<(bool, usize, &str)> {
+        self.leaves()
+            .into_iter()
+            .map(|leaf| {
+                let path = self.path_to(Some(leaf));
+                let last_user = path
+                    .iter()
+                    .rev()
+                    .find(|&&i| self.nodes[i].role == "user")
+                    .map(|&i| self.nodes[i].content.as_str())
+                    .unwrap_or("");
+                (Some(leaf) == self.head, path.len(), last_user)
+            })
+            .collect()
+    }
+
+    /// Make the `n`th (1-based) branch from [`branches`](Self::branches) current
+    pub fn switch_branch(&mut self, n: usize) -> bool {
+        match self.leaves().get(n.wrapping_sub(1)) {
+            Some(&leaf) => {
+                self.head = Some(leaf);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exchange(tree: &mut ConversationTree, user: &str, assistant: &str) {
+        tree.push("user", user.to_string());
+        tree.push("assistant", assistant.to_string());
+    }
+
+    #[test]
+    fn test_messages_follow_current_branch() {
+        let mut tree = ConversationTree::new();
+        tree.set_system(Some("Be brief".to_string()));
+        exchange(&mut tree, "Hi", "Hello");
+
+        assert_eq!(
+            tree.messages(),
+            vec![
+                ("system".to_string(), "Be brief".to_string()),
+                ("user".to_string(), "Hi".to_string()),
+                ("assistant".to_string(), "Hello".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_undo_keeps_old_exchange_as_branch() {
+        let mut tree = ConversationTree::new();
+        exchange(&mut tree, "Hi", "Hello");
+        exchange(&mut tree, "Tell a joke", "No.");
+
+        assert!(tree.undo());
+        assert_eq!(tree.history(), vec![("user", "Hi"), ("assistant", "Hello")]);
+
+        exchange(&mut tree, "Tell a story", "Once...");
+        let branches = tree.branches();
+        assert_eq!(branches.len(), 2);
+        assert_eq!(branches[0], (false, 4, "Tell a joke"));
+        assert_eq!(branches[1], (true, 4, "Tell a story"));
+
+        assert!(tree.switch_branch(1));
+        assert_eq!(tree.history()[3], ("assistant", "No."));
+        assert!(!tree.switch_branch(3));
+        assert!(!tree.switch_branch(0));
+    }
+
+    #[test]
+    fn test_retry_rewinds_to_last_user_message() {
+        let mut tree = ConversationTree::new();
+        exchange(&mut tree, "Hi", "Hello");
+
+        assert_eq!(tree.retry(), Some("Hi"));
+        assert_eq!(tree.history(), vec![("user", "Hi")]);
+
+        tree.push("assistant", "Hey!".to_string());
+        assert_eq!(tree.branches().len(), 2);
+        assert!(ConversationTree::new().retry().is_none());
+    }
+
+    #[test]
+    fn test_retry_keeps_attachments() {
+        let image = ContentPart::image_base64("image/png", "AAAA");
+        let mut tree = ConversationTree::new();
+        tree.push_with_parts("user", "What is this?".to_string(), vec![image.clone()]);
+        tree.push("assistant", "A cat".to_string());
+
+        assert_eq!(tree.retry(), Some("What is this?"));
+        assert_eq!(tree.last_user_parts(), std::slice::from_ref(&image));
+
+        let json = serde_json::to_string(&tree).unwrap();
+        let restored: ConversationTree = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.last_user_parts(), &[image]);
+    }
+
+    #[test]
+    fn test_edit_user_message_branches_before_it() {
+        let mut tree = ConversationTree::new();
+        exchange(&mut tree, "First", "One");
+        exchange(&mut tree, "Second", "Two");
+
+        assert_eq!(tree.edit_user_message(2), Some("Second".to_string()));
+        assert_eq!(tree.user_messages(), vec!["First"]);
+        assert_eq!(tree.edit_user_message(5), None);
+        assert_eq!(tree.edit_user_message(0), None);
+
+        assert_eq!(tree.edit_user_message(1), Some("First".to_string()));
+        assert!(tree.history().is_empty());
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let mut tree =
+            ConversationTree::from_messages(None, vec![("user".to_string(), "Hi".to_string())]);
+        tree.undo();
+        tree.push("user", "Hey".to_string());
+
+        let json = serde_json::to_string(&tree).unwrap();
+        let restored: ConversationTree = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, tree);
+        assert_eq!(restored.branches().len(), 2);
+    }
+}
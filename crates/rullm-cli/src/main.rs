@@ -3,19 +3,24 @@
 mod aliases;
 mod api_keys;
 mod args;
+mod attachments;
 mod cli_client;
 mod cli_helpers;
 mod client;
 mod commands;
 mod config;
 mod constants;
+mod conversation;
 mod output;
 mod provider;
+mod sessions;
 mod spinner;
 mod templates;
 
+use anyhow::Context;
 use anyhow::Result;
-use args::{Cli, CliConfig};
+use args::{Cli, CliConfig, OutputFormat};
+use attachments::Attachments;
 use clap::{CommandFactory, Parser};
 use cli_helpers::resolve_direct_query_model;
 use commands::Commands;
@@ -53,7 +58,8 @@ pub async fn run() -> Result<()> {
             Some(Commands::Info(_))
             | Some(Commands::Keys(_))
             | Some(Commands::Alias(_))
-            | Some(Commands::Completions(_)) => {
+            | Some(Commands::Completions(_))
+            | Some(Commands::Sessions(_)) => {
                 use clap::error::ErrorKind;
 
                 let mut cmd = Cli::command();
@@ -79,6 +85,29 @@ pub async fn run() -> Result<()> {
         .exit();
     }
 
+    // Validate that attach and output flags are only used for quick-query mode
+    if cli.command.is_some() {
+        use clap::error::ErrorKind;
+
+        let unexpected = if !cli.attach.is_empty() {
+            Some("'--attach' (use /attach in chat)")
+        } else if cli.json_schema.is_some() {
+            Some("'--json-schema'")
+        } else if cli.format != OutputFormat::Text {
+            Some("'--format'")
+        } else {
+            None
+        };
+        if let Some(flag) = unexpected {
+            let mut cmd = Cli::command();
+            cmd.error(
+                ErrorKind::UnknownArgument,
+                format!("unexpected argument {flag} found when using subcommands"),
+            )
+            .exit();
+        }
+    }
+
     // Handle commands
     match &cli.command {
         Some(Commands::Chat(args)) => args.run(output_level, &cli_config, &cli).await?,
@@ -88,11 +117,13 @@ pub async fn run() -> Result<()> {
         Some(Commands::Alias(args)) => args.run(output_level, &cli_config, &cli).await?,
         Some(Commands::Completions(args)) => args.run(output_level, &cli_config, &cli).await?,
         Some(Commands::Templates(args)) => args.run(output_level, &cli_config, &cli).await?,
+        Some(Commands::Sessions(args)) => args.run(output_level, &cli_config, &cli).await?,
         None => {
             if let Some(query) = &cli.query {
                 let model_str =
                     resolve_direct_query_model(&cli.model, &cli_config.config.default_model)?;
                 let client = client::from_model(&model_str, &cli, &cli_config)?;
+                let attachments = Attachments::load(&cli.attach)?;
 
                 // Handle template if provided
                 let (system_prompt, final_query) = if let Some(template_name) = &cli.template {
@@ -101,14 +132,40 @@ pub async fn run() -> Result<()> {
                     (cli.system, query.clone())
                 };
 
-                commands::run_single_query(
-                    &client,
-                    &final_query,
-                    system_prompt.as_deref(),
-                    !cli.no_streaming,
-                )
-                .await
-                .map_err(anyhow::Error::from)?;
+                let final_query = attachments.apply(&final_query);
+
+                if let Some(path) = &cli.json_schema {
+                    let schema = std::fs::read_to_string(path)
+                        .with_context(|| format!("Cannot read schema {}", path.display()))?;
+                    let schema: serde_json::Value = serde_json::from_str(&schema)
+                        .with_context(|| format!("Invalid JSON in schema {}", path.display()))?;
+                    commands::run_structured_query(
+                        &client,
+                        &final_query,
+                        system_prompt.as_deref(),
+                        attachments.parts(),
+                        schema,
+                    )
+                    .await?;
+                } else if cli.format == OutputFormat::Json {
+                    commands::run_json_query(
+                        &client,
+                        &final_query,
+                        system_prompt.as_deref(),
+                        attachments.parts(),
+                    )
+                    .await?;
+                } else {
+                    commands::run_single_query(
+                        &client,
+                        &final_query,
+                        system_prompt.as_deref(),
+                        attachments.parts(),
+                        !cli.no_streaming,
+                    )
+                    .await
+                    .map_err(anyhow::Error::from)?;
+                }
             } else {
                 eprintln!("Error: No query provided. Use --help for usage information.");
                 std::process::exit(1);
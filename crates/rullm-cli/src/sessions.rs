@@ -0,0 +1,362 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rullm_core::TokenUsage;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::constants::SESSIONS_DIR_NAME;
+use crate::conversation::ConversationTree;
+
+/// A single message in a saved chat session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionMessage {
+    /// "user" or "assistant"
+    pub role: String,
+    pub content: String,
+}
+
+/// A saved interactive chat conversation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    /// Identifier, also used as the file name
+    pub id: String,
+    /// Model in `provider:model` format
+    pub model: String,
+    pub system_prompt: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Current branch of the conversation, without the system prompt
+    #[serde(default)]
+    pub messages: Vec<SessionMessage>,
+    /// Full conversation including branches left by retry, undo and edit
+    #[serde(default, skip_serializing_if = "has_no_nodes")]
+    pub tree: ConversationTree,
+    /// Token usage summed over the responses that reported it
+    #[serde(default)]
+    pub usage: TokenUsage,
+}
+
+/// Nothing to save: no message was added on any branch
+fn has_no_nodes(tree: &ConversationTree) -> bool {
+    !tree.has_nodes()
+}
+
+impl Session {
+    /// Create an empty session with a timestamp-based id
+    ///
+    /// The id has microsecond precision so that sessions started in the same
+    /// second do not overwrite each other.
+    pub fn new(model: impl Into<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: now.format("%Y%m%d-%H%M%S-%6f").to_string(),
+            model: model.into(),
+            system_prompt: None,
+            created_at: now,
+            updated_at: now,
+            messages: Vec::new(),
+            tree: ConversationTree::new(),
+            usage: TokenUsage::default(),
+        }
+    }
+
+    /// Replace the stored conversation
+    pub fn set_conversation(&mut self, tree: &ConversationTree) {
+        self.system_prompt = tree.system().map(str::to_string);
+        self.messages = tree
+            .history()
+            .into_iter()
+            .map(|(role, content)| SessionMessage {
+                role: role.to_string(),
+                content: content.to_string(),
+            })
+            .collect();
+        self.tree = tree.clone();
+    }
+
+    /// The stored conversation, rebuilt from `messages` for sessions saved
+    /// without a tree
+    pub fn conversation(&self) -> ConversationTree {
+        if self.tree.has_nodes() || self.messages.is_empty() {
+            let mut tree = self.tree.clone();
+            tree.set_system(self.system_prompt.clone());
+            return tree;
+        }
+        ConversationTree::from_messages(
+            self.system_prompt.clone(),
+            self.messages
+                .iter()
+                .map(|m| (m.role.clone(), m.content.clone())),
+        )
+    }
+
+    /// Add the token usage of one response
+    pub fn add_usage(&mut self, usage: &TokenUsage) {
+        self.usage.prompt_tokens += usage.prompt_tokens;
+        self.usage.completion_tokens += usage.completion_tokens;
+        self.usage.total_tokens += usage.total_tokens;
+    }
+
+    /// First user message, shortened for listings
+    pub fn title(&self) -> String {
+        let first = self
+            .messages
+            .iter()
+            .find(|m| m.role == "user")
+            .map(|m| m.content.lines().next().unwrap_or("").trim())
+            .unwrap_or("");
+        if first.chars().count() > 50 {
+            format!("{}...", first.chars().take(47).collect::<String>())
+        } else {
+            first.to_string()
+        }
+    }
+
+    /// Render the session as Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# Session {}\n\n- Model: {}\n- Created: {}\n- Updated: {}\n- Tokens: {}\n",
+            self.id,
+            self.model,
+            self.created_at.to_rfc3339(),
+            self.updated_at.to_rfc3339(),
+            self.usage.total_tokens
+        );
+        if let Some(system) = &self.system_prompt {
+            out.push_str(&format!("\n## System\n\n{system}\n"));
+        }
+        for message in &self.messages {
+            let heading = match message.role.as_str() {
+                "user" => "User",
+                "assistant" => "Assistant",
+                other => other,
+            };
+            out.push_str(&format!("\n## {heading}\n\n{}\n", message.content));
+        }
+        out
+    }
+}
+
+/// Store for chat sessions, one JSON file per session
+pub struct SessionStore {
+    sessions_dir: PathBuf,
+}
+
+impl SessionStore {
+    /// Create a new SessionStore under the given data directory
+    pub fn new(base_path: &Path) -> Self {
+        Self {
+            sessions_dir: base_path.join(SESSIONS_DIR_NAME),
+        }
+    }
+
+    fn path(&self, id: &str) -> Result<PathBuf> {
+        if id.is_empty()
+            || !id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            anyhow::bail!("Invalid session id '{id}': use letters, digits, '-' and '_' only");
+        }
+        Ok(self.sessions_dir.join(format!("{id}.json")))
+    }
+
+    /// Check if a session exists
+    pub fn contains(&self, id: &str) -> bool {
+        self.path(id).is_ok_and(|path| path.exists())
+    }
+
+    /// Save a session, bumping its `updated_at`
+    pub fn save(&self, session: &mut Session) -> Result<()> {
+        let file_path = self.path(&session.id)?;
+        fs::create_dir_all(&self.sessions_dir).context("Failed to create sessions directory")?;
+
+        session.updated_at = Utc::now();
+        let content =
+            serde_json::to_string_pretty(session).context("Failed to serialize session")?;
+
+        // Atomic write: write to temp file then rename
+        let temp_path = file_path.with_extension("json.tmp");
+        fs::write(&temp_path, content)
+            .with_context(|| format!("Failed to write temporary session file: {temp_path:?}"))?;
+        fs::rename(&temp_path, &file_path)
+            .with_context(|| format!("Failed to rename session file: {file_path:?}"))?;
+        Ok(())
+    }
+
+    /// Load a session by id
+    pub fn load(&self, id: &str) -> Result<Session> {
+        let file_path = self.path(id)?;
+        if !file_path.exists() {
+            anyhow::bail!("Session '{id}' not found");
+        }
+        let content = fs::read_to_string(&file_path)
+            .with_context(|| format!("Failed to read session file: {file_path:?}"))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse session file: {file_path:?}"))
+    }
+
+    /// All sessions, most recently updated first
+    pub fn list(&self) -> Result<Vec<Session>> {
+        if !self.sessions_dir.exists() {
+            return Ok(Vec::new());
+        }
+        let entries =
+            fs::read_dir(&self.sessions_dir).context("Failed to read sessions directory")?;
+
+        let mut sessions = Vec::new();
+        for entry in entries {
+            let path = entry.context("Failed to read directory entry")?.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            let parsed = fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|content| Ok(serde_json::from_str::<Session>(&content)?));
+            match parsed {
+                Ok(session) => sessions.push(session),
+                Err(e) => eprintln!("Warning: Failed to load session {path:?}: {e}"),
+            }
+        }
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.updated_at));
+        Ok(sessions)
+    }
+
+    /// The most recently updated session, if any
+    pub fn latest(&self) -> Result<Option<Session>> {
+        Ok(self.list()?.into_iter().next())
+    }
+
+    /// Delete a session file. Returns true if deleted.
+    pub fn delete(&self, id: &str) -> Result<bool> {
+        let file_path = self.path(id)?;
+        if !file_path.exists() {
+            return Ok(false);
+        }
+        fs::remove_file(&file_path)
+            .with_context(|| format!("Failed to delete session file: {file_path:?}"))?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn conversation() -> ConversationTree {
+        ConversationTree::from_messages(
+            Some("Be brief".to_string()),
+            vec![
+                ("user".to_string(), "Hi".to_string()),
+                ("assistant".to_string(), "Hello!".to_string()),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_conversation_round_trip() {
+        let mut session = Session::new("openai:gpt-4o");
+        session.set_conversation(&conversation());
+
+        assert_eq!(session.system_prompt.as_deref(), Some("Be brief"));
+        assert_eq!(session.messages.len(), 2);
+        assert_eq!(session.conversation(), conversation());
+        assert_eq!(session.title(), "Hi");
+    }
+
+    #[test]
+    fn test_sessions_without_tree_are_rebuilt_from_messages() {
+        let mut session = Session::new("openai:gpt-4o");
+        session.set_conversation(&conversation());
+        session.tree = ConversationTree::new();
+
+        let json = serde_json::to_string(&session).unwrap();
+        assert!(!json.contains("\"tree\""));
+        let loaded: Session = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.conversation(), conversation());
+    }
+
+    #[test]
+    fn test_new_sessions_get_distinct_ids() {
+        let first = Session::new("openai:gpt-4o");
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        let second = Session::new("openai:gpt-4o");
+        assert_ne!(first.id, second.id);
+        assert!(SessionStore::new(Path::new(".")).path(&first.id).is_ok());
+    }
+
+    #[test]
+    fn test_branches_survive_undo_to_root() {
+        let dir = TempDir::new().unwrap();
+        let store = SessionStore::new(dir.path());
+        let mut tree = conversation();
+        assert!(tree.undo());
+        assert!(tree.history().is_empty());
+
+        let mut session = Session::new("openai:gpt-4o");
+        session.set_conversation(&tree);
+        store.save(&mut session).unwrap();
+
+        let mut loaded = store.load(&session.id).unwrap().conversation();
+        assert_eq!(loaded, tree);
+        assert!(loaded.switch_branch(1));
+        assert_eq!(
+            loaded.history(),
+            vec![("user", "Hi"), ("assistant", "Hello!")]
+        );
+    }
+
+    #[test]
+    fn test_store_save_load_list_delete() {
+        let dir = TempDir::new().unwrap();
+        let store = SessionStore::new(dir.path());
+
+        let mut older = Session::new("openai:gpt-4o");
+        older.id = "older".to_string();
+        store.save(&mut older).unwrap();
+
+        let mut newer = Session::new("anthropic:claude-3-haiku");
+        newer.id = "newer".to_string();
+        newer.set_conversation(&conversation());
+        newer.add_usage(&TokenUsage {
+            prompt_tokens: 5,
+            completion_tokens: 2,
+            total_tokens: 7,
+        });
+        store.save(&mut newer).unwrap();
+
+        let loaded = store.load("newer").unwrap();
+        assert_eq!(loaded.model, "anthropic:claude-3-haiku");
+        assert_eq!(loaded.conversation(), conversation());
+        assert_eq!(loaded.usage.total_tokens, 7);
+
+        let ids: Vec<String> = store.list().unwrap().into_iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec!["newer", "older"]);
+        assert_eq!(store.latest().unwrap().unwrap().id, "newer");
+
+        assert!(store.delete("older").unwrap());
+        assert!(!store.delete("older").unwrap());
+        assert!(store.load("older").is_err());
+    }
+
+    #[test]
+    fn test_rejects_path_like_ids() {
+        let dir = TempDir::new().unwrap();
+        let store = SessionStore::new(dir.path());
+
+        assert!(store.load("../keys").is_err());
+        assert!(!store.contains("a/b"));
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let mut session = Session::new("openai:gpt-4o");
+        session.set_conversation(&conversation());
+
+        let markdown = session.to_markdown();
+        assert!(markdown.contains("## System\n\nBe brief"));
+        assert!(markdown.contains("## User\n\nHi\n\n## Assistant\n\nHello!"));
+    }
+}
@@ -0,0 +1,463 @@
+//! Agent loop that runs registered tools until the model gives a final answer
+//!
+//! [`Agent`] sends the conversation to a [`ChatCompletion`] provider, executes
+//! any tool calls in the response with the registered handlers (concurrently
+//! when the model requests several), appends the results and repeats until the
+//! model answers without calling tools or a limit is reached.
+//!
+//! ```rust,no_run
+//! use rullm_core::agent::Agent;
+//! use rullm_core::{ChatRequestBuilder, OpenAIConfig, OpenAIProvider};
+//! use serde_json::json;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let provider = OpenAIProvider::new(OpenAIConfig::new("your-api-key"))?;
+//! let agent = Agent::new(provider, "gpt-4o-mini")
+//!     .tool(
+//!         "get_weather",
+//!         "Look up the current weather for a city",
+//!         json!({
+//!             "type": "object",
+//!             "properties": {"city": {"type": "string"}},
+//!             "required": ["city"]
+//!         }),
+//!         |args| async move { Ok(json!({"city": args["city"], "forecast": "sunny"})) },
+//!     )
+//!     .with_max_iterations(5);
+//!
+//! let request = ChatRequestBuilder::new()
+//!     .user("What's the weather in Paris?")
+//!     .build();
+//! let run = agent.run(request).await?;
+//! println!("{}", run.response.message.content);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::compat_types::{
+    ChatMessage, ChatRequest, ChatResponse, TokenUsage, ToolCall, ToolDefinition,
+};
+use crate::error::LlmError;
+use crate::types::ChatCompletion;
+use futures::future::{BoxFuture, join_all};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+/// Default number of model round trips before the agent stops
+pub const DEFAULT_MAX_ITERATIONS: usize = 10;
+
+/// Tool result for calls left pending when a run stops at a limit
+const NOT_EXECUTED: &str = "Error: not executed, the agent reached its limit";
+
+type ToolHandler = Arc<
+    dyn Fn(serde_json::Value) -> BoxFuture<'static, Result<serde_json::Value, LlmError>>
+        + Send
+        + Sync,
+>;
+
+struct RegisteredTool {
+    definition: ToolDefinition,
+    handler: ToolHandler,
+}
+
+/// Why an agent run stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgentStopReason {
+    /// The model answered without calling tools
+    Completed,
+    /// The model was still calling tools after `max_iterations` round trips
+    MaxIterations,
+    /// The token budget was used up while the model was still calling tools
+    TokenBudget,
+}
+
+/// Outcome of [`Agent::run`]
+#[derive(Debug, Clone)]
+pub struct AgentRun {
+    /// The last response from the model
+    pub response: ChatResponse,
+    /// The full conversation, including tool calls and tool results
+    ///
+    /// Every tool call is answered, so the messages can be sent again to
+    /// continue the conversation. Calls still pending when the run stops at a
+    /// limit are not executed and get an error result instead.
+    pub messages: Vec<ChatMessage>,
+    /// Token usage summed over all model calls
+    pub usage: TokenUsage,
+    /// Number of model calls made
+    pub iterations: usize,
+    pub stop_reason: AgentStopReason,
+}
+
+/// Runs a tool-calling loop against a provider
+pub struct Agent {
+    provider: Arc<dyn ChatCompletion>,
+    model: String,
+    tools: Vec<RegisteredTool>,
+    max_iterations: usize,
+    token_budget: Option<u32>,
+}
+
+impl Agent {
+    /// Create an agent sending requests to `model` on `provider`
+    pub fn new<P>(provider: P, model: impl Into<String>) -> Self
+    where
+        P: ChatCompletion + 'static,
+    {
+        Self {
+            provider: Arc::new(provider),
+            model: model.into(),
+            tools: Vec::new(),
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            token_budget: None,
+        }
+    }
+
+    /// Register a tool the model may call
+    ///
+    /// `parameters` is the JSON schema of the arguments passed to `handler`.
+    /// A registered tool replaces any earlier tool with the same name.
+    pub fn tool<F, Fut>(
+        mut self,
+        name: impl Into<String>,
+        description: impl Into<String>,
+        parameters: serde_json::Value,
+        handler: F,
+    ) -> Self
+    where
+        F: Fn(serde_json::Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<serde_json::Value, LlmError>> + Send + 'static,
+    {
+        let definition = ToolDefinition::new(name, description, parameters);
+        self.tools
+            .retain(|tool| tool.definition.name != definition.name);
+        self.tools.push(RegisteredTool {
+            definition,
+            handler: Arc::new(move |args| Box::pin(handler(args))),
+        });
+        self
+    }
+
+    /// Set the maximum number of model calls per run
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Stop once the total tokens used across model calls reach `budget`
+    pub fn with_token_budget(mut self, budget: u32) -> Self {
+        self.token_budget = Some(budget);
+        self
+    }
+
+    /// Definitions of the registered tools, as sent to the model
+    pub fn tool_definitions(&self) -> Vec<ToolDefinition> {
+        self.tools
+            .iter()
+            .map(|tool| tool.definition.clone())
+            .collect()
+    }
+
+    /// Run the conversation in `request` until the model gives a final answer
+    ///
+    /// The registered tools are added to the request's tools, replacing any
+    /// request tool with the same name. Tool handler
+    /// errors and calls to unknown tools are reported back to the model as
+    /// tool results so it can recover; provider errors end the run.
+    pub async fn run(&self, mut request: ChatRequest) -> Result<AgentRun, LlmError> {
+        if self.max_iterations == 0 {
+            return Err(LlmError::validation("max_iterations must be at least 1"));
+        }
+        if !self.tools.is_empty() {
+            let tools = request.tools.get_or_insert_with(Vec::new);
+            tools.retain(|tool| self.tools.iter().all(|t| t.definition.name != tool.name));
+            tools.extend(self.tool_definitions());
+        }
+
+        let handlers: HashMap<&str, &ToolHandler> = self
+            .tools
+            .iter()
+            .map(|tool| (tool.definition.name.as_str(), &tool.handler))
+            .collect();
+        let mut usage = TokenUsage {
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            total_tokens: 0,
+        };
+        let mut iterations = 0;
+
+        loop {
+            let response = self
+                .provider
+                .chat_completion(request.clone(), &self.model)
+                .await?;
+            iterations += 1;
+            usage.prompt_tokens += response.usage.prompt_tokens;
+            usage.completion_tokens += response.usage.completion_tokens;
+            usage.total_tokens += response.usage.total_tokens;
+            request.messages.push(response.message.clone());
+
+            let stop_reason = if response.message.tool_calls.is_empty() {
+                Some(AgentStopReason::Completed)
+            } else if self
+                .token_budget
+                .is_some_and(|budget| usage.total_tokens >= budget)
+            {
+                Some(AgentStopReason::TokenBudget)
+            } else if iterations >= self.max_iterations {
+                Some(AgentStopReason::MaxIterations)
+            } else {
+                None
+            };
+
+            if let Some(stop_reason) = stop_reason {
+                // Answer pending calls without running them so the history stays valid
+                request.messages.extend(
+                    response
+                        .message
+                        .tool_calls
+                        .iter()
+                        .map(|call| ChatMessage::tool_result(call.id.clone(), NOT_EXECUTED)),
+                );
+                return Ok(AgentRun {
+                    response,
+                    messages: request.messages,
+                    usage,
+                    iterations,
+                    stop_reason,
+                });
+            }
+
+            let results = join_all(
+                response
+                    .message
+                    .tool_calls
+                    .iter()
+                    .map(|call| dispatch(&handlers, call)),
+            )
+            .await;
+            request.messages.extend(results);
+        }
+    }
+}
+
+/// Run one tool call and turn its outcome into a tool result message
+async fn dispatch(handlers: &HashMap<&str, &ToolHandler>, call: &ToolCall) -> ChatMessage {
+    let content = match handlers.get(call.name.as_str()) {
+        Some(handler) => match handler(call.arguments.clone()).await {
+            Ok(serde_json::Value::String(text)) => text,
+            Ok(value) => value.to_string(),
+            Err(e) => format!("Error: {e}"),
+        },
+        None => format!("Error: unknown tool '{}'", call.name),
+    };
+    ChatMessage::tool_result(call.id.clone(), content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compat_types::{ChatRequestBuilder, ChatRole};
+    use crate::utils::test_helpers::ScriptedProvider;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn call(id: &str, name: &str, arguments: serde_json::Value) -> ToolCall {
+        ToolCall {
+            id: id.to_string(),
+            name: name.to_string(),
+            arguments,
+        }
+    }
+
+    fn add_tool(agent: Agent) -> Agent {
+        agent.tool(
+            "add",
+            "Add two numbers",
+            serde_json::json!({"type": "object"}),
+            |args| async move {
+                let sum = args["a"].as_i64().unwrap_or(0) + args["b"].as_i64().unwrap_or(0);
+                Ok(serde_json::json!(sum))
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_run_executes_tools_until_final_answer() {
+        let provider = ScriptedProvider::new(vec![
+            ChatMessage::assistant_tool_calls(
+                "",
+                vec![call("call_1", "add", serde_json::json!({"a": 2, "b": 3}))],
+            ),
+            ChatMessage::assistant("2 + 3 = 5"),
+        ]);
+        let agent = add_tool(Agent::new(provider.clone(), "test-model"));
+
+        let run = agent
+            .run(ChatRequestBuilder::new().user("What is 2 + 3?").build())
+            .await
+            .unwrap();
+
+        assert_eq!(run.stop_reason, AgentStopReason::Completed);
+        assert_eq!(run.response.message.content, "2 + 3 = 5");
+        assert_eq!(run.iterations, 2);
+        assert_eq!(run.usage.total_tokens, 30);
+        assert_eq!(run.messages.len(), 4);
+        assert_eq!(run.messages[2].role, ChatRole::Tool);
+        assert_eq!(run.messages[2].tool_call_id.as_deref(), Some("call_1"));
+        assert_eq!(run.messages[2].content, "5");
+
+        let requests = provider.requests();
+        assert_eq!(requests[0].tools.as_ref().unwrap()[0].name, "add");
+        assert_eq!(requests[1].messages.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_run_does_not_duplicate_request_tools() {
+        let provider = ScriptedProvider::text(&["done"]);
+        let agent = add_tool(Agent::new(provider.clone(), "test-model"));
+        let request = ChatRequestBuilder::new()
+            .user("Go")
+            .tool(ToolDefinition::new(
+                "add",
+                "Stale description",
+                serde_json::json!({}),
+            ))
+            .build();
+
+        agent.run(request).await.unwrap();
+
+        let tools = provider.requests()[0].tools.clone().unwrap();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].description, "Add two numbers");
+    }
+
+    #[tokio::test]
+    async fn test_run_dispatches_parallel_calls_concurrently() {
+        let provider = ScriptedProvider::new(vec![
+            ChatMessage::assistant_tool_calls(
+                "",
+                vec![
+                    call("call_1", "wait", serde_json::json!({"id": "first"})),
+                    call("call_2", "wait", serde_json::json!({"id": "second"})),
+                ],
+            ),
+            ChatMessage::assistant("done"),
+        ]);
+        let agent = Agent::new(provider, "test-model").tool(
+            "wait",
+            "Sleep briefly",
+            serde_json::json!({"type": "object"}),
+            |args| async move {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                Ok(args["id"].clone())
+            },
+        );
+
+        let started = std::time::Instant::now();
+        let run = agent
+            .run(ChatRequestBuilder::new().user("Go").build())
+            .await
+            .unwrap();
+
+        assert!(started.elapsed() < Duration::from_millis(390));
+        assert_eq!(run.messages[2].content, "first");
+        assert_eq!(run.messages[3].content, "second");
+    }
+
+    #[tokio::test]
+    async fn test_run_reports_tool_errors_to_model() {
+        let provider = ScriptedProvider::new(vec![
+            ChatMessage::assistant_tool_calls(
+                "",
+                vec![
+                    call("call_1", "missing", serde_json::json!({})),
+                    call("call_2", "fail", serde_json::json!({})),
+                ],
+            ),
+            ChatMessage::assistant("sorry"),
+        ]);
+        let agent = Agent::new(provider, "test-model").tool(
+            "fail",
+            "Always fails",
+            serde_json::json!({"type": "object"}),
+            |_| async { Err(LlmError::validation("bad input")) },
+        );
+
+        let run = agent
+            .run(ChatRequestBuilder::new().user("Go").build())
+            .await
+            .unwrap();
+
+        assert_eq!(run.messages[2].content, "Error: unknown tool 'missing'");
+        assert!(run.messages[3].content.contains("bad input"));
+    }
+
+    #[tokio::test]
+    async fn test_run_stops_at_limits() {
+        let looping = || {
+            ChatMessage::assistant_tool_calls(
+                "",
+                vec![call("call_1", "add", serde_json::json!({"a": 1, "b": 1}))],
+            )
+        };
+
+        let agent = add_tool(Agent::new(
+            ScriptedProvider::new(vec![looping(), looping(), looping()]),
+            "test-model",
+        ))
+        .with_max_iterations(2);
+        let run = agent
+            .run(ChatRequestBuilder::new().user("Go").build())
+            .await
+            .unwrap();
+        assert_eq!(run.stop_reason, AgentStopReason::MaxIterations);
+        assert_eq!(run.iterations, 2);
+        assert_eq!(run.messages.len(), 5);
+        assert_eq!(run.messages[4].role, ChatRole::Tool);
+        assert_eq!(run.messages[2].content, "2");
+        assert_eq!(run.messages[4].content, NOT_EXECUTED);
+
+        // The stopped history is complete enough to resume from
+        let provider = ScriptedProvider::new(vec![ChatMessage::assistant("1 + 1 = 2")]);
+        let request = run
+            .messages
+            .into_iter()
+            .fold(ChatRequestBuilder::new(), |builder, message| {
+                builder.message(message)
+            })
+            .build();
+        let resumed = add_tool(Agent::new(provider.clone(), "test-model"))
+            .run(request)
+            .await
+            .unwrap();
+        assert_eq!(resumed.stop_reason, AgentStopReason::Completed);
+        assert_eq!(provider.requests()[0].messages.len(), 5);
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let agent = Agent::new(
+            ScriptedProvider::new(vec![looping(), looping(), looping()]),
+            "test-model",
+        )
+        .tool(
+            "add",
+            "Add two numbers",
+            serde_json::json!({"type": "object"}),
+            move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async { Ok(serde_json::json!(2)) }
+            },
+        )
+        .with_token_budget(20);
+        let run = agent
+            .run(ChatRequestBuilder::new().user("Go").build())
+            .await
+            .unwrap();
+        assert_eq!(run.stop_reason, AgentStopReason::TokenBudget);
+        assert_eq!(run.usage.total_tokens, 30);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(run.messages.last().unwrap().content, NOT_EXECUTED);
+    }
+}
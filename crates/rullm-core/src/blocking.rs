@@ -0,0 +1,255 @@
+//! Synchronous wrappers around the async clients
+//!
+//! Enabled with the `blocking` cargo feature. Each [`Client`] owns a small
+//! single-threaded Tokio runtime and blocks the calling thread on it, in the
+//! spirit of `reqwest::blocking`. Streaming responses are returned as a
+//! [`ChatStream`] iterator.
+//!
+//! These wrappers must not be used from inside an async runtime: blocking on
+//! a runtime from within another one panics.
+//!
+//! ```rust,no_run
+//! use rullm_core::blocking::OpenAIClient;
+//! use rullm_core::{ChatRequestBuilder, ChatStreamEvent, OpenAIConfig};
+//!
+//! # fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = OpenAIClient::new(OpenAIConfig::new("your-api-key"))?;
+//!
+//! let request = ChatRequestBuilder::new().user("Hello").build();
+//! let response = client.chat_completion(request, "gpt-4o-mini")?;
+//! println!("{}", response.message.content);
+//!
+//! let request = ChatRequestBuilder::new().user("Tell me a story").build();
+//! for event in client.chat_completion_stream(request, "gpt-4o-mini") {
+//!     if let ChatStreamEvent::Token(token) = event? {
+//!         print!("{token}");
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::compat_types::{ChatRequest, ChatResponse, ChatStreamEvent};
+use crate::config::{
+    AnthropicConfig, ConfigBuilder, GoogleAiConfig, OpenAICompatibleConfig, OpenAIConfig,
+};
+use crate::error::LlmError;
+use crate::providers::ProviderIdentity;
+use crate::types::{ChatCompletion, StreamResult};
+use futures::StreamExt;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+/// Blocking [`OpenAIClient`](crate::OpenAIClient)
+pub type OpenAIClient = Client<crate::OpenAIClient>;
+/// Blocking [`AnthropicClient`](crate::AnthropicClient)
+pub type AnthropicClient = Client<crate::AnthropicClient>;
+/// Blocking [`GoogleClient`](crate::GoogleClient)
+pub type GoogleClient = Client<crate::GoogleClient>;
+/// Blocking [`OpenAICompatibleProvider`](crate::OpenAICompatibleProvider)
+pub type OpenAICompatibleProvider = Client<crate::OpenAICompatibleProvider>;
+
+/// A provider client whose calls block until they complete
+pub struct Client<C> {
+    inner: C,
+    runtime: Arc<Runtime>,
+}
+
+impl<C: ChatCompletion> Client<C> {
+    /// Wrap an async client
+    pub fn from_async(inner: C) -> Result<Self, LlmError> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| LlmError::configuration(format!("Failed to start runtime: {e}")))?;
+        Ok(Self {
+            inner,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// The wrapped async client
+    pub fn get_ref(&self) -> &C {
+        &self.inner
+    }
+
+    /// Send a chat request and wait for the full response
+    pub fn chat_completion(
+        &self,
+        request: ChatRequest,
+        model: &str,
+    ) -> Result<ChatResponse, LlmError> {
+        self.runtime
+            .block_on(self.inner.chat_completion(request, model))
+    }
+
+    /// Send a chat request and iterate over the streamed events
+    ///
+    /// Request errors are yielded by the iterator.
+    pub fn chat_completion_stream(&self, mut request: ChatRequest, model: &str) -> ChatStream {
+        request.stream = Some(true);
+        let stream = self
+            .runtime
+            .block_on(self.inner.chat_completion_stream(request, model, None));
+        ChatStream {
+            stream,
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// List the models available to the configured account
+    pub fn available_models(&self) -> Result<Vec<String>, LlmError> {
+        self.runtime.block_on(self.inner.available_models())
+    }
+
+    /// Check that the provider is reachable with the configured credentials
+    pub fn health_check(&self) -> Result<(), LlmError> {
+        self.runtime.block_on(self.inner.health_check())
+    }
+}
+
+impl Client<crate::OpenAIClient> {
+    pub fn new(config: OpenAIConfig) -> Result<Self, LlmError> {
+        Self::from_async(crate::OpenAIClient::new(config)?)
+    }
+
+    /// Create a client from `OPENAI_API_KEY` and related variables
+    pub fn from_env() -> Result<Self, LlmError> {
+        Self::new(ConfigBuilder::openai_from_env()?)
+    }
+}
+
+impl Client<crate::AnthropicClient> {
+    pub fn new(config: AnthropicConfig) -> Result<Self, LlmError> {
+        Self::from_async(crate::AnthropicClient::new(config)?)
+    }
+
+    /// Create a client from `ANTHROPIC_API_KEY` and related variables
+    pub fn from_env() -> Result<Self, LlmError> {
+        Self::new(ConfigBuilder::anthropic_from_env()?)
+    }
+}
+
+impl Client<crate::GoogleClient> {
+    pub fn new(config: GoogleAiConfig) -> Result<Self, LlmError> {
+        Self::from_async(crate::GoogleClient::new(config)?)
+    }
+
+    /// Create a client from `GOOGLE_AI_API_KEY` and related variables
+    pub fn from_env() -> Result<Self, LlmError> {
+        Self::new(ConfigBuilder::google_ai_from_env()?)
+    }
+}
+
+impl Client<crate::OpenAICompatibleProvider> {
+    /// Create an OpenAI-compatible provider with custom identity
+    pub fn new(
+        config: OpenAICompatibleConfig,
+        identity: ProviderIdentity,
+    ) -> Result<Self, LlmError> {
+        Self::from_async(crate::OpenAICompatibleProvider::new(config, identity)?)
+    }
+
+    /// Create a Groq provider
+    pub fn groq(config: OpenAICompatibleConfig) -> Result<Self, LlmError> {
+        Self::from_async(crate::OpenAICompatibleProvider::groq(config)?)
+    }
+
+    /// Create an OpenRouter provider
+    pub fn openrouter(config: OpenAICompatibleConfig) -> Result<Self, LlmError> {
+        Self::from_async(crate::OpenAICompatibleProvider::openrouter(config)?)
+    }
+}
+
+/// Iterator over the events of a streamed response
+///
+/// Each call to `next` blocks until the next event arrives. Iteration ends
+/// after [`ChatStreamEvent::Done`] or when the connection closes.
+pub struct ChatStream {
+    stream: StreamResult<ChatStreamEvent>,
+    runtime: Arc<Runtime>,
+}
+
+impl Iterator for ChatStream {
+    type Item = Result<ChatStreamEvent, LlmError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime.block_on(self.stream.next())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compat_types::{ChatRequestBuilder, TokenUsage};
+    use crate::utils::test_helpers::{MockHttpServer, MockResponse};
+
+    /// Start a mock server on a runtime of its own, as a synchronous caller
+    /// has none
+    fn serve(responses: Vec<MockResponse>) -> (Runtime, MockHttpServer) {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap();
+        let server = runtime.block_on(MockHttpServer::start(responses));
+        (runtime, server)
+    }
+
+    #[test]
+    fn test_chat_completion_blocks_for_response() {
+        let (_runtime, server) = serve(vec![MockResponse::json(serde_json::json!({
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-3-haiku-20240307",
+            "content": [{"type": "text", "text": "Hi"}],
+            "stop_reason": "end_turn",
+            "usage": {"input_tokens": 3, "output_tokens": 1}
+        }))]);
+
+        let client =
+            AnthropicClient::new(AnthropicConfig::new("test-key").with_base_url(server.base_url()))
+                .unwrap();
+        let request = ChatRequestBuilder::new().user("Hello").build();
+        let response = client
+            .chat_completion(request, "claude-3-haiku-20240307")
+            .unwrap();
+
+        assert_eq!(response.message.content, "Hi");
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn test_stream_iterates_events() {
+        let (_runtime, server) = serve(vec![MockResponse::sse(&[
+            r#"{"choices":[{"index":0,"delta":{"role":"assistant","content":"Hi"}}]}"#,
+            r#"{"choices":[{"index":0,"delta":{},"finish_reason":"stop"}]}"#,
+            r#"{"choices":[],"usage":{"prompt_tokens":3,"completion_tokens":1,"total_tokens":4}}"#,
+            "[DONE]",
+        ])]);
+
+        let config = OpenAICompatibleConfig::groq("test-key").with_base_url(server.base_url());
+        let client = OpenAICompatibleProvider::groq(config).unwrap();
+        let request = ChatRequestBuilder::new().user("Hello").build();
+        let events: Vec<ChatStreamEvent> = client
+            .chat_completion_stream(request, "llama3-8b-8192")
+            .map(|event| event.unwrap())
+            .collect();
+
+        assert_eq!(
+            events,
+            vec![
+                ChatStreamEvent::Token("Hi".to_string()),
+                ChatStreamEvent::Finish("stop".to_string()),
+                ChatStreamEvent::Usage(TokenUsage {
+                    prompt_tokens: 3,
+                    completion_tokens: 1,
+                    total_tokens: 4,
+                }),
+                ChatStreamEvent::Done,
+            ]
+        );
+        assert_eq!(server.requests()[0].json()["stream"], true);
+    }
+}
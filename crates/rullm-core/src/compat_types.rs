@@ -1,9 +1,11 @@
 //! Compatibility types for OpenAI-compatible provider
 //!
-//! These are minimal types to support Groq/OpenRouter through the OpenAICompatibleProvider.
-//! For full-featured usage, use the concrete provider clients directly.
+//! These are minimal provider-neutral types used by the [`ChatCompletion`](crate::types::ChatCompletion)
+//! trait. For full-featured usage, use the concrete provider clients directly.
 
+use crate::error::LlmError;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -18,6 +20,199 @@ pub enum ChatRole {
 pub struct ChatMessage {
     pub role: ChatRole,
     pub content: String,
+    /// Images and documents sent after `content`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parts: Vec<ContentPart>,
+    /// Tools the assistant asked to call (assistant messages only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// Id of the tool call this message answers (tool messages only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+impl ChatMessage {
+    pub fn new(role: ChatRole, content: impl Into<String>) -> Self {
+        Self {
+            role,
+            content: content.into(),
+            parts: Vec::new(),
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+        }
+    }
+
+    pub fn system(content: impl Into<String>) -> Self {
+        Self::new(ChatRole::System, content)
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self::new(ChatRole::User, content)
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self::new(ChatRole::Assistant, content)
+    }
+
+    /// Attach an image or document to this message
+    pub fn with_part(mut self, part: ContentPart) -> Self {
+        self.parts.push(part);
+        self
+    }
+
+    /// Whether the message carries anything besides text
+    pub fn has_media(&self) -> bool {
+        self.parts
+            .iter()
+            .any(|part| !matches!(part, ContentPart::Text { .. }))
+    }
+
+    /// An assistant message requesting tool calls, with optional accompanying text
+    pub fn assistant_tool_calls(content: impl Into<String>, tool_calls: Vec<ToolCall>) -> Self {
+        Self {
+            tool_calls,
+            ..Self::assistant(content)
+        }
+    }
+
+    /// The result of running the tool call `tool_call_id`
+    pub fn tool_result(tool_call_id: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            tool_call_id: Some(tool_call_id.into()),
+            ..Self::new(ChatRole::Tool, content)
+        }
+    }
+}
+
+/// A piece of multimodal message content
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentPart {
+    Text {
+        text: String,
+    },
+    /// An image the provider fetches from a URL
+    ImageUrl {
+        url: String,
+    },
+    /// An inline image, e.g. `image/png`
+    ImageBase64 {
+        media_type: String,
+        data: String,
+    },
+    /// An inline document such as a PDF
+    Document {
+        media_type: String,
+        data: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+    },
+}
+
+impl ContentPart {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text { text: text.into() }
+    }
+
+    pub fn image_url(url: impl Into<String>) -> Self {
+        Self::ImageUrl { url: url.into() }
+    }
+
+    pub fn image_base64(media_type: impl Into<String>, data: impl Into<String>) -> Self {
+        Self::ImageBase64 {
+            media_type: media_type.into(),
+            data: data.into(),
+        }
+    }
+
+    pub fn pdf(data: impl Into<String>) -> Self {
+        Self::Document {
+            media_type: "application/pdf".to_string(),
+            data: data.into(),
+            name: None,
+        }
+    }
+
+    /// Short description used in error messages
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Text { .. } => "text",
+            Self::ImageUrl { .. } => "image URL",
+            Self::ImageBase64 { .. } => "inline image",
+            Self::Document { .. } => "document",
+        }
+    }
+}
+
+/// A tool the model may call, with a JSON schema describing its arguments
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    pub parameters: serde_json::Value,
+}
+
+impl ToolDefinition {
+    pub fn new(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        parameters: serde_json::Value,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            parameters,
+        }
+    }
+}
+
+/// How the model may use the tools in a request
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolChoice {
+    /// The model decides whether to call tools
+    Auto,
+    /// The model must not call tools
+    None,
+    /// The model must call at least one tool
+    Required,
+    /// The model must call the named tool
+    Tool(String),
+}
+
+/// Output format the model should produce
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    /// Free-form text (the default)
+    Text,
+    /// Any valid JSON object
+    Json,
+    /// JSON matching `schema`
+    JsonSchema {
+        /// Schema name, used where providers require one
+        name: String,
+        schema: serde_json::Value,
+    },
+}
+
+impl ResponseFormat {
+    pub fn json_schema(name: impl Into<String>, schema: serde_json::Value) -> Self {
+        Self::JsonSchema {
+            name: name.into(),
+            schema,
+        }
+    }
+}
+
+/// A tool invocation requested by the model
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ToolCall {
+    /// Id used to match the tool result to this call
+    pub id: String,
+    pub name: String,
+    /// Parsed JSON arguments
+    pub arguments: serde_json::Value,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,7 +221,66 @@ pub struct ChatRequest {
     pub temperature: Option<f32>,
     pub max_tokens: Option<u32>,
     pub top_p: Option<f32>,
+    /// Sample only from the `top_k` most likely tokens (Anthropic, Google)
+    #[serde(default)]
+    pub top_k: Option<u32>,
+    /// Sequences that end the response
+    #[serde(default)]
+    pub stop: Option<Vec<String>>,
+    /// Seed for best-effort deterministic sampling (OpenAI)
+    #[serde(default)]
+    pub seed: Option<i64>,
+    /// Penalize tokens by how often they already appeared (OpenAI)
+    #[serde(default)]
+    pub frequency_penalty: Option<f32>,
+    /// Penalize tokens that already appeared at all (OpenAI)
+    #[serde(default)]
+    pub presence_penalty: Option<f32>,
+    /// Bias per token id, from -100 to 100 (OpenAI)
+    #[serde(default)]
+    pub logit_bias: Option<HashMap<String, i32>>,
     pub stream: Option<bool>,
+    /// Extra provider-specific parameters merged into the request body by
+    /// OpenAI-compatible providers; the native clients reject them
+    pub extra_params: Option<HashMap<String, serde_json::Value>>,
+    /// Tools the model may call
+    #[serde(default)]
+    pub tools: Option<Vec<ToolDefinition>>,
+    /// How the model may use `tools`
+    #[serde(default)]
+    pub tool_choice: Option<ToolChoice>,
+    /// Ask for JSON output instead of free-form text
+    #[serde(default)]
+    pub response_format: Option<ResponseFormat>,
+}
+
+impl ChatRequest {
+    /// Fail if any of the named options is set, for providers that do not
+    /// accept them
+    pub(crate) fn reject_sampling(&self, provider: &str, options: &[&str]) -> Result<(), LlmError> {
+        let set: Vec<&str> = options
+            .iter()
+            .copied()
+            .filter(|option| match *option {
+                "top_k" => self.top_k.is_some(),
+                "stop" => self.stop.is_some(),
+                "seed" => self.seed.is_some(),
+                "frequency_penalty" => self.frequency_penalty.is_some(),
+                "presence_penalty" => self.presence_penalty.is_some(),
+                "logit_bias" => self.logit_bias.is_some(),
+                "extra_params" => self.extra_params.is_some(),
+                _ => false,
+            })
+            .collect();
+        if set.is_empty() {
+            Ok(())
+        } else {
+            Err(LlmError::validation(format!(
+                "{provider} does not support {}",
+                set.join(", ")
+            )))
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,18 +289,46 @@ pub struct ChatResponse {
     pub model: String,
     pub usage: TokenUsage,
     pub finish_reason: Option<String>,
+    /// Provider-specific response data (e.g. response id)
+    pub provider_metadata: Option<HashMap<String, serde_json::Value>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub struct TokenUsage {
     pub prompt_tokens: u32,
     pub completion_tokens: u32,
     pub total_tokens: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// An event in a streamed chat completion
+///
+/// Tool call events share an `index` identifying the call within the stream.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ChatStreamEvent {
+    /// A fragment of the assistant's answer
     Token(String),
+    /// A fragment of the model's reasoning, for models that expose it
+    ReasoningDelta(String),
+    /// The model started a tool call
+    ToolCallStart {
+        index: usize,
+        id: String,
+        name: String,
+    },
+    /// A fragment of a tool call's JSON arguments
+    ToolCallDelta {
+        index: usize,
+        arguments: String,
+    },
+    /// A tool call finished streaming, with its arguments parsed
+    ToolCallComplete {
+        index: usize,
+        call: ToolCall,
+    },
+    /// Token usage for the response
+    Usage(TokenUsage),
+    /// The provider's reason for ending the response
+    Finish(String),
     Done,
     Error(String),
 }
@@ -56,6 +338,17 @@ pub struct ChatRequestBuilder {
     temperature: Option<f32>,
     max_tokens: Option<u32>,
     top_p: Option<f32>,
+    top_k: Option<u32>,
+    stop: Option<Vec<String>>,
+    seed: Option<i64>,
+    frequency_penalty: Option<f32>,
+    presence_penalty: Option<f32>,
+    logit_bias: Option<HashMap<String, i32>>,
+    stream: bool,
+    extra_params: Option<HashMap<String, serde_json::Value>>,
+    tools: Option<Vec<ToolDefinition>>,
+    tool_choice: Option<ToolChoice>,
+    response_format: Option<ResponseFormat>,
 }
 
 impl ChatRequestBuilder {
@@ -65,14 +358,70 @@ impl ChatRequestBuilder {
             temperature: None,
             max_tokens: None,
             top_p: None,
+            top_k: None,
+            stop: None,
+            seed: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            logit_bias: None,
+            stream: false,
+            extra_params: None,
+            tools: None,
+            tool_choice: None,
+            response_format: None,
         }
     }
 
-    pub fn add_message(mut self, role: ChatRole, content: impl Into<String>) -> Self {
-        self.messages.push(ChatMessage {
-            role,
-            content: content.into(),
-        });
+    pub fn add_message(self, role: ChatRole, content: impl Into<String>) -> Self {
+        self.message(ChatMessage::new(role, content))
+    }
+
+    pub fn message(mut self, message: ChatMessage) -> Self {
+        self.messages.push(message);
+        self
+    }
+
+    pub fn system(self, content: impl Into<String>) -> Self {
+        self.add_message(ChatRole::System, content)
+    }
+
+    pub fn user(self, content: impl Into<String>) -> Self {
+        self.add_message(ChatRole::User, content)
+    }
+
+    /// Add a user message with images or documents after its text
+    pub fn user_with_parts(self, content: impl Into<String>, parts: Vec<ContentPart>) -> Self {
+        self.message(ChatMessage {
+            parts,
+            ..ChatMessage::user(content)
+        })
+    }
+
+    pub fn assistant(self, content: impl Into<String>) -> Self {
+        self.add_message(ChatRole::Assistant, content)
+    }
+
+    pub fn tool_result(self, tool_call_id: impl Into<String>, content: impl Into<String>) -> Self {
+        self.message(ChatMessage::tool_result(tool_call_id, content))
+    }
+
+    pub fn tool(mut self, tool: ToolDefinition) -> Self {
+        self.tools.get_or_insert_with(Vec::new).push(tool);
+        self
+    }
+
+    pub fn tools(mut self, tools: Vec<ToolDefinition>) -> Self {
+        self.tools.get_or_insert_with(Vec::new).extend(tools);
+        self
+    }
+
+    pub fn tool_choice(mut self, tool_choice: ToolChoice) -> Self {
+        self.tool_choice = Some(tool_choice);
+        self
+    }
+
+    pub fn response_format(mut self, response_format: ResponseFormat) -> Self {
+        self.response_format = Some(response_format);
         self
     }
 
@@ -91,13 +440,65 @@ impl ChatRequestBuilder {
         self
     }
 
+    pub fn top_k(mut self, top_k: u32) -> Self {
+        self.top_k = Some(top_k);
+        self
+    }
+
+    pub fn stop(mut self, stop: Vec<String>) -> Self {
+        self.stop = Some(stop);
+        self
+    }
+
+    pub fn seed(mut self, seed: i64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn frequency_penalty(mut self, penalty: f32) -> Self {
+        self.frequency_penalty = Some(penalty);
+        self
+    }
+
+    pub fn presence_penalty(mut self, penalty: f32) -> Self {
+        self.presence_penalty = Some(penalty);
+        self
+    }
+
+    pub fn logit_bias(mut self, logit_bias: HashMap<String, i32>) -> Self {
+        self.logit_bias = Some(logit_bias);
+        self
+    }
+
+    pub fn stream(mut self, stream: bool) -> Self {
+        self.stream = stream;
+        self
+    }
+
+    pub fn extra_param(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
+        self.extra_params
+            .get_or_insert_with(HashMap::new)
+            .insert(key.into(), value);
+        self
+    }
+
     pub fn build(self) -> ChatRequest {
         ChatRequest {
             messages: self.messages,
             temperature: self.temperature,
             max_tokens: self.max_tokens,
             top_p: self.top_p,
-            stream: Some(false),
+            top_k: self.top_k,
+            stop: self.stop,
+            seed: self.seed,
+            frequency_penalty: self.frequency_penalty,
+            presence_penalty: self.presence_penalty,
+            logit_bias: self.logit_bias,
+            stream: Some(self.stream),
+            extra_params: self.extra_params,
+            tools: self.tools,
+            tool_choice: self.tool_choice,
+            response_format: self.response_format,
         }
     }
 }
@@ -2,6 +2,20 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::Duration;
 
+/// Default connect timeout for provider clients
+pub const DEFAULT_CONNECT_TIMEOUT_SECONDS: u64 = 10;
+
+/// Default stream idle timeout for provider clients
+pub const DEFAULT_STREAM_IDLE_TIMEOUT_SECONDS: u64 = 60;
+
+fn default_connect_timeout_seconds() -> u64 {
+    DEFAULT_CONNECT_TIMEOUT_SECONDS
+}
+
+fn default_stream_idle_timeout_seconds() -> u64 {
+    DEFAULT_STREAM_IDLE_TIMEOUT_SECONDS
+}
+
 /// Configuration trait for LLM providers
 pub trait ProviderConfig: Send + Sync {
     /// Get the API key for this provider
@@ -13,6 +27,16 @@ pub trait ProviderConfig: Send + Sync {
     /// Get default request timeout
     fn timeout(&self) -> Duration;
 
+    /// Get the timeout for establishing a connection
+    fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECONDS)
+    }
+
+    /// Get how long a stream may go without receiving data before it is aborted
+    fn stream_idle_timeout(&self) -> Duration {
+        Duration::from_secs(DEFAULT_STREAM_IDLE_TIMEOUT_SECONDS)
+    }
+
     /// Get any additional headers required by the provider
     fn headers(&self) -> HashMap<String, String>;
 
@@ -88,14 +112,97 @@ impl ProviderConfig for HttpProviderConfig {
     }
 }
 
+/// Retry policy for transient failures (429, 5xx, 529 and connection errors)
+///
+/// Delays grow exponentially from `base_delay`, capped at `max_delay`, with
+/// optional random jitter. A `Retry-After` header from the server takes
+/// precedence over the computed delay.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Number of retries after the initial attempt (0 disables retrying)
+    pub max_retries: u32,
+    /// Delay before the first retry
+    pub base_delay: Duration,
+    /// Upper bound for any single delay, including server-requested ones
+    pub max_delay: Duration,
+    /// Randomize delays to avoid synchronized retries across clients
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    pub fn with_base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Delay before retry number `retry` (starting at 0)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exp = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+
+        if self.jitter {
+            use rand::Rng;
+            exp.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+        } else {
+            exp
+        }
+    }
+}
+
 /// OpenAI-compatible configuration (supports OpenAI, Groq, OpenRouter, etc.)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpenAICompatibleConfig {
-    pub api_key: String,
+    /// Sent as a bearer token; `None` for servers without authentication
+    pub api_key: Option<String>,
     pub organization: Option<String>,
     pub project: Option<String>,
     pub base_url: Option<String>,
     pub timeout_seconds: u64,
+    #[serde(default = "default_connect_timeout_seconds")]
+    pub connect_timeout_seconds: u64,
+    #[serde(default = "default_stream_idle_timeout_seconds")]
+    pub stream_idle_timeout_seconds: u64,
+    #[serde(default)]
+    pub retry_policy: RetryPolicy,
+    /// Extra headers sent with every request, overriding the defaults
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
 }
 
 /// Type alias for backwards compatibility
@@ -104,34 +211,56 @@ pub type OpenAIConfig = OpenAICompatibleConfig;
 impl OpenAICompatibleConfig {
     pub fn new(api_key: impl Into<String>) -> Self {
         Self {
-            api_key: api_key.into(),
+            api_key: Some(api_key.into()),
             organization: None,
             project: None,
             base_url: None,
             timeout_seconds: 30,
+            connect_timeout_seconds: DEFAULT_CONNECT_TIMEOUT_SECONDS,
+            stream_idle_timeout_seconds: DEFAULT_STREAM_IDLE_TIMEOUT_SECONDS,
+            retry_policy: RetryPolicy::default(),
+            headers: HashMap::new(),
         }
     }
 
     pub fn groq(api_key: impl Into<String>) -> Self {
         Self {
-            api_key: api_key.into(),
+            api_key: Some(api_key.into()),
             organization: None,
             project: None,
             base_url: Some("https://api.groq.com/openai/v1".to_string()),
             timeout_seconds: 30,
+            connect_timeout_seconds: DEFAULT_CONNECT_TIMEOUT_SECONDS,
+            stream_idle_timeout_seconds: DEFAULT_STREAM_IDLE_TIMEOUT_SECONDS,
+            retry_policy: RetryPolicy::default(),
+            headers: HashMap::new(),
         }
     }
 
     pub fn openrouter(api_key: impl Into<String>) -> Self {
         Self {
-            api_key: api_key.into(),
+            api_key: Some(api_key.into()),
             organization: None,
             project: None,
             base_url: Some("https://openrouter.ai/api/v1".to_string()),
             timeout_seconds: 30,
+            connect_timeout_seconds: DEFAULT_CONNECT_TIMEOUT_SECONDS,
+            stream_idle_timeout_seconds: DEFAULT_STREAM_IDLE_TIMEOUT_SECONDS,
+            retry_policy: RetryPolicy::default(),
+            headers: HashMap::new(),
         }
     }
 
+    /// Configuration for a server that needs no API key, such as a local
+    /// vLLM or llama.cpp server
+    pub fn without_api_key(base_url: impl Into<String>) -> Self {
+        Self {
+            api_key: None,
+            ..Self::new("")
+        }
+        .with_base_url(base_url)
+    }
+
     pub fn with_organization(mut self, org: impl Into<String>) -> Self {
         self.organization = Some(org.into());
         self
@@ -146,11 +275,37 @@ impl OpenAICompatibleConfig {
         self.base_url = Some(base_url.into());
         self
     }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout_seconds: u64) -> Self {
+        self.timeout_seconds = timeout_seconds;
+        self
+    }
+
+    pub fn with_connect_timeout(mut self, connect_timeout_seconds: u64) -> Self {
+        self.connect_timeout_seconds = connect_timeout_seconds;
+        self
+    }
+
+    pub fn with_stream_idle_timeout(mut self, stream_idle_timeout_seconds: u64) -> Self {
+        self.stream_idle_timeout_seconds = stream_idle_timeout_seconds;
+        self
+    }
+
+    /// Send an extra header with every request
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
 }
 
 impl ProviderConfig for OpenAICompatibleConfig {
     fn api_key(&self) -> &str {
-        &self.api_key
+        self.api_key.as_deref().unwrap_or_default()
     }
 
     fn base_url(&self) -> &str {
@@ -163,12 +318,19 @@ impl ProviderConfig for OpenAICompatibleConfig {
         Duration::from_secs(self.timeout_seconds)
     }
 
+    fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout_seconds)
+    }
+
+    fn stream_idle_timeout(&self) -> Duration {
+        Duration::from_secs(self.stream_idle_timeout_seconds)
+    }
+
     fn headers(&self) -> HashMap<String, String> {
         let mut headers = HashMap::new();
-        headers.insert(
-            "Authorization".to_string(),
-            format!("Bearer {}", self.api_key),
-        );
+        if let Some(api_key) = &self.api_key {
+            headers.insert("Authorization".to_string(), format!("Bearer {api_key}"));
+        }
         headers.insert("Content-Type".to_string(), "application/json".to_string());
 
         if let Some(org) = &self.organization {
@@ -179,11 +341,12 @@ impl ProviderConfig for OpenAICompatibleConfig {
             headers.insert("OpenAI-Project".to_string(), project.clone());
         }
 
+        headers.extend(self.headers.clone());
         headers
     }
 
     fn validate(&self) -> Result<(), crate::error::LlmError> {
-        if self.api_key.is_empty() {
+        if self.api_key.as_deref() == Some("") {
             return Err(crate::error::LlmError::configuration("API key is required"));
         }
 
@@ -200,6 +363,15 @@ pub struct AnthropicConfig {
     pub api_key: String,
     pub base_url: Option<String>,
     pub timeout_seconds: u64,
+    #[serde(default = "default_connect_timeout_seconds")]
+    pub connect_timeout_seconds: u64,
+    #[serde(default = "default_stream_idle_timeout_seconds")]
+    pub stream_idle_timeout_seconds: u64,
+    #[serde(default)]
+    pub retry_policy: RetryPolicy,
+    /// Extra headers sent with every request, overriding the defaults
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
 }
 
 impl AnthropicConfig {
@@ -208,6 +380,10 @@ impl AnthropicConfig {
             api_key: api_key.into(),
             base_url: None,
             timeout_seconds: 30,
+            connect_timeout_seconds: DEFAULT_CONNECT_TIMEOUT_SECONDS,
+            stream_idle_timeout_seconds: DEFAULT_STREAM_IDLE_TIMEOUT_SECONDS,
+            retry_policy: RetryPolicy::default(),
+            headers: HashMap::new(),
         }
     }
 
@@ -215,6 +391,32 @@ impl AnthropicConfig {
         self.base_url = Some(base_url.into());
         self
     }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout_seconds: u64) -> Self {
+        self.timeout_seconds = timeout_seconds;
+        self
+    }
+
+    pub fn with_connect_timeout(mut self, connect_timeout_seconds: u64) -> Self {
+        self.connect_timeout_seconds = connect_timeout_seconds;
+        self
+    }
+
+    pub fn with_stream_idle_timeout(mut self, stream_idle_timeout_seconds: u64) -> Self {
+        self.stream_idle_timeout_seconds = stream_idle_timeout_seconds;
+        self
+    }
+
+    /// Send an extra header with every request
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
 }
 
 impl ProviderConfig for AnthropicConfig {
@@ -232,11 +434,20 @@ impl ProviderConfig for AnthropicConfig {
         Duration::from_secs(self.timeout_seconds)
     }
 
+    fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout_seconds)
+    }
+
+    fn stream_idle_timeout(&self) -> Duration {
+        Duration::from_secs(self.stream_idle_timeout_seconds)
+    }
+
     fn headers(&self) -> HashMap<String, String> {
         let mut headers = HashMap::new();
         headers.insert("x-api-key".to_string(), self.api_key.clone());
         headers.insert("Content-Type".to_string(), "application/json".to_string());
         headers.insert("anthropic-version".to_string(), "2023-06-01".to_string());
+        headers.extend(self.headers.clone());
         headers
     }
 
@@ -257,6 +468,12 @@ pub struct GoogleAiConfig {
     pub api_key: String,
     pub base_url: Option<String>,
     pub timeout_seconds: u64,
+    #[serde(default = "default_connect_timeout_seconds")]
+    pub connect_timeout_seconds: u64,
+    #[serde(default = "default_stream_idle_timeout_seconds")]
+    pub stream_idle_timeout_seconds: u64,
+    #[serde(default)]
+    pub retry_policy: RetryPolicy,
 }
 
 impl GoogleAiConfig {
@@ -265,6 +482,9 @@ impl GoogleAiConfig {
             api_key: api_key.into(),
             base_url: None,
             timeout_seconds: 30,
+            connect_timeout_seconds: DEFAULT_CONNECT_TIMEOUT_SECONDS,
+            stream_idle_timeout_seconds: DEFAULT_STREAM_IDLE_TIMEOUT_SECONDS,
+            retry_policy: RetryPolicy::default(),
         }
     }
 
@@ -272,6 +492,26 @@ impl GoogleAiConfig {
         self.base_url = Some(base_url.into());
         self
     }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout_seconds: u64) -> Self {
+        self.timeout_seconds = timeout_seconds;
+        self
+    }
+
+    pub fn with_connect_timeout(mut self, connect_timeout_seconds: u64) -> Self {
+        self.connect_timeout_seconds = connect_timeout_seconds;
+        self
+    }
+
+    pub fn with_stream_idle_timeout(mut self, stream_idle_timeout_seconds: u64) -> Self {
+        self.stream_idle_timeout_seconds = stream_idle_timeout_seconds;
+        self
+    }
 }
 
 impl ProviderConfig for GoogleAiConfig {
@@ -289,6 +529,14 @@ impl ProviderConfig for GoogleAiConfig {
         Duration::from_secs(self.timeout_seconds)
     }
 
+    fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout_seconds)
+    }
+
+    fn stream_idle_timeout(&self) -> Duration {
+        Duration::from_secs(self.stream_idle_timeout_seconds)
+    }
+
     fn headers(&self) -> HashMap<String, String> {
         let mut headers = HashMap::new();
         headers.insert("Content-Type".to_string(), "application/json".to_string());
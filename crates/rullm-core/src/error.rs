@@ -60,9 +60,17 @@ pub enum LlmError {
     #[error("Resource error: {message}")]
     Resource { message: String },
 
-    /// Provider service unavailable
-    #[error("Service unavailable: {provider} is currently unavailable")]
-    ServiceUnavailable { provider: String },
+    /// Provider service unavailable or overloaded (HTTP 503 and 529)
+    #[error(
+        "Service unavailable: {provider} is currently unavailable{}",
+        .message.as_deref().map(|m| format!(": {m}")).unwrap_or_default()
+    )]
+    ServiceUnavailable {
+        provider: String,
+        /// The provider's explanation, when it sent one
+        message: Option<String>,
+        code: Option<String>,
+    },
 
     /// Generic errors for cases not covered above
     #[error("Unexpected error: {message}")]
@@ -174,6 +182,21 @@ impl LlmError {
     pub fn service_unavailable(provider: impl Into<String>) -> Self {
         Self::ServiceUnavailable {
             provider: provider.into(),
+            message: None,
+            code: None,
+        }
+    }
+
+    /// Create a service unavailable error carrying the provider's message
+    pub fn service_unavailable_with_message(
+        provider: impl Into<String>,
+        message: impl Into<String>,
+        code: Option<String>,
+    ) -> Self {
+        Self::ServiceUnavailable {
+            provider: provider.into(),
+            message: Some(message.into()),
+            code,
         }
     }
 
@@ -10,6 +10,7 @@
 //! - Rate limiting, timeouts, and error handling
 //! - Dual APIs: Simple string-based API and advanced API with full control
 //! - Streaming support for token-by-token responses
+//! - Optional blocking API for synchronous code (`blocking` feature)
 //! - Test suite with examples
 //! - Metrics, logging, and error handling
 //!
@@ -32,12 +33,13 @@
 //! ### Advanced API (Full Control)
 //!
 //! ```rust,no_run
-//! use rullm_core::{OpenAIConfig, OpenAIProvider, ChatCompletion, ChatRequestBuilder, ChatRole};
+//! use rullm_core::{OpenAIConfig, OpenAIProvider, ChatCompletion, ChatRequestBuilder};
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
 //!     let config = OpenAIConfig::new("your-api-key");
-//!     let provider = OpenAIProvider::new(config)?;
+//!     // Any client can be used here: AnthropicClient, GoogleClient, OpenAICompatibleProvider...
+//!     let provider: Box<dyn ChatCompletion> = Box::new(OpenAIProvider::new(config)?);
 //!
 //!     let request = ChatRequestBuilder::new()
 //!         .user("Hello, world!")
@@ -58,9 +60,10 @@
 //!
 //! ### Core Streaming Types
 //!
-//! - [`ChatStreamEvent`] - Events emitted during streaming (Token, Done, Error)
+//! - [`ChatStreamEvent`] - Events emitted during streaming: tokens, reasoning, tool calls,
+//!   usage, finish reason, and the final `Done`
 //! - [`StreamResult`] - Type alias for `Pin<Box<dyn Stream<Item = Result<ChatStreamEvent, LlmError>>>>`
-//! - [`ChatProvider::chat_completion_stream`] - Main streaming method for all providers
+//! - [`ChatCompletion::chat_completion_stream`] - Main streaming method for all providers
 //!
 //! ### Basic Streaming Usage
 //!
@@ -71,7 +74,7 @@
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
 //!     let config = OpenAIConfig::new("your-api-key");
-//!     let provider = OpenAIProvider::new(config)?;
+//!     let provider: Box<dyn ChatCompletion> = Box::new(OpenAIProvider::new(config)?);
 //!
 //!     let request = ChatRequestBuilder::new()
 //!         .user("Tell me a story")
@@ -88,6 +91,16 @@
 //!                 print!("{}", token);
 //!                 std::io::Write::flush(&mut std::io::stdout())?;
 //!             }
+//!             ChatStreamEvent::ReasoningDelta(_) => {}
+//!             ChatStreamEvent::ToolCallStart { name, .. } => println!("\n🔧 Calling {}", name),
+//!             ChatStreamEvent::ToolCallDelta { .. } => {}
+//!             ChatStreamEvent::ToolCallComplete { call, .. } => {
+//!                 println!("🔧 {}({})", call.name, call.arguments);
+//!             }
+//!             ChatStreamEvent::Usage(usage) => {
+//!                 println!("\n📊 {} tokens", usage.total_tokens);
+//!             }
+//!             ChatStreamEvent::Finish(reason) => println!("\n🏁 Finished: {}", reason),
 //!             ChatStreamEvent::Done => {
 //!                 println!("\n✅ Stream completed");
 //!                 break;
@@ -134,7 +147,7 @@
 //!
 //! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
 //! # let config = OpenAIConfig::new("your-api-key");
-//! # let provider = OpenAIProvider::new(config)?;
+//! # let provider: Box<dyn ChatCompletion> = Box::new(OpenAIProvider::new(config)?);
 //! # let request = ChatRequestBuilder::new().user("test").build();
 //! match provider.chat_completion(request, "gpt-4").await {
 //!     Ok(response) => println!("Success: {}", response.message.content),
@@ -148,29 +161,46 @@
 //! # }
 //! ```
 
+pub mod agent;
+#[cfg(feature = "blocking")]
+pub mod blocking;
 pub mod compat_types;
 pub mod config;
 pub mod error;
+pub mod middleware;
 pub mod providers;
+pub mod simple;
+pub mod stream;
+pub mod structured;
+pub mod translate;
+pub mod types;
 pub mod utils;
 
 #[cfg(test)]
 mod tests;
 
 // Concrete client exports
-pub use providers::{AnthropicClient, GoogleClient, OpenAIClient, OpenAICompatibleProvider};
+pub use providers::{
+    AnthropicClient, AnthropicProvider, GoogleClient, GoogleProvider, OpenAIClient,
+    OpenAICompatibleProvider, OpenAIProvider,
+};
 
+pub use agent::{Agent, AgentRun, AgentStopReason};
 pub use config::{
     AnthropicConfig, ConfigBuilder, GoogleAiConfig, OpenAICompatibleConfig, OpenAIConfig,
-    ProviderConfig,
+    ProviderConfig, RetryPolicy,
 };
 pub use error::LlmError;
-pub use utils::sse::sse_lines;
+pub use middleware::{LlmServiceBuilder, MiddlewareConfig, MiddlewareStack, RateLimit};
+pub use structured::StructuredOutput;
+pub use tokio_util::sync::CancellationToken;
+pub use types::{ChatCompletion, LlmProvider, StreamConfig, StreamResult};
+pub use utils::sse::{SseEvent, sse_events, sse_lines};
 
 // Compatibility types for OpenAI-compatible providers
 pub use compat_types::{
     ChatMessage, ChatRequest, ChatRequestBuilder, ChatResponse, ChatRole, ChatStreamEvent,
-    TokenUsage,
+    ContentPart, ResponseFormat, TokenUsage, ToolCall, ToolChoice, ToolDefinition,
 };
 
 // Re-export test utilities for integration tests and examples
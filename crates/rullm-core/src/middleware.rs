@@ -0,0 +1,428 @@
+//! Tower middleware for chat providers
+//!
+//! [`LlmServiceBuilder`] wraps any [`ChatCompletion`] provider in a
+//! [`tower::Service`] stack with optional timeout, rate limiting, logging and
+//! metrics layers.
+//!
+//! ```rust,no_run
+//! use rullm_core::{ChatRequestBuilder, LlmServiceBuilder, OpenAIConfig, OpenAIProvider};
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let provider = OpenAIProvider::new(OpenAIConfig::new("your-api-key"))?;
+//! let mut service = LlmServiceBuilder::new()
+//!     .timeout(Duration::from_secs(60))
+//!     .rate_limit(10, Duration::from_secs(1))
+//!     .logging()
+//!     .metrics()
+//!     .build(provider, "gpt-4o-mini".to_string());
+//!
+//! let request = ChatRequestBuilder::new().user("Hello!").build();
+//! let response = service.call(request).await?;
+//! println!("{}", response.message.content);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::compat_types::{ChatRequest, ChatResponse};
+use crate::error::LlmError;
+use crate::types::ChatCompletion;
+use futures::future::BoxFuture;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tower::util::BoxService;
+use tower::{BoxError, Layer, Service, ServiceBuilder, ServiceExt};
+
+type LlmBoxService = BoxService<ChatRequest, ChatResponse, LlmError>;
+
+/// Rate limit applied to requests passing through the stack
+#[derive(Debug, Clone)]
+pub struct RateLimit {
+    /// Maximum number of requests allowed per period
+    pub requests_per_period: u64,
+    /// Length of the rate limit window
+    pub period: Duration,
+}
+
+/// Configuration for the middleware stack
+#[derive(Debug, Clone)]
+pub struct MiddlewareConfig {
+    /// Per-request timeout (`None` disables the timeout layer)
+    pub timeout: Option<Duration>,
+    /// Optional request rate limit
+    pub rate_limit: Option<RateLimit>,
+    /// Log requests and their outcome
+    pub enable_logging: bool,
+    /// Record request counts, latencies and token usage via the `metrics` crate
+    pub enable_metrics: bool,
+}
+
+impl Default for MiddlewareConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Some(Duration::from_secs(30)),
+            rate_limit: None,
+            enable_logging: true,
+            enable_metrics: false,
+        }
+    }
+}
+
+/// Builder for a provider wrapped in middleware layers
+#[derive(Debug, Clone, Default)]
+pub struct LlmServiceBuilder {
+    config: MiddlewareConfig,
+}
+
+impl LlmServiceBuilder {
+    /// Create a builder with the default configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a builder from an existing configuration
+    pub fn with_config(config: MiddlewareConfig) -> Self {
+        Self { config }
+    }
+
+    /// Set the per-request timeout
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = Some(timeout);
+        self
+    }
+
+    /// Allow at most `requests_per_period` requests every `period`
+    pub fn rate_limit(mut self, requests_per_period: u64, period: Duration) -> Self {
+        self.config.rate_limit = Some(RateLimit {
+            requests_per_period,
+            period,
+        });
+        self
+    }
+
+    /// Enable request logging
+    pub fn logging(mut self) -> Self {
+        self.config.enable_logging = true;
+        self
+    }
+
+    /// Enable metrics collection
+    pub fn metrics(mut self) -> Self {
+        self.config.enable_metrics = true;
+        self
+    }
+
+    /// Wrap `provider` in the configured middleware, sending requests to `model`
+    pub fn build<P>(self, provider: P, model: String) -> MiddlewareStack
+    where
+        P: ChatCompletion + 'static,
+    {
+        MiddlewareStack {
+            provider: Arc::new(provider),
+            model,
+            config: self.config,
+            service: None,
+        }
+    }
+}
+
+/// A provider wrapped in timeout, rate limit, logging and metrics layers
+///
+/// The layers are assembled on first use, since tower's rate limiter needs a
+/// running Tokio runtime.
+pub struct MiddlewareStack {
+    provider: Arc<dyn ChatCompletion>,
+    model: String,
+    config: MiddlewareConfig,
+    service: Option<LlmBoxService>,
+}
+
+impl MiddlewareStack {
+    /// The configuration this stack was built with
+    pub fn config(&self) -> &MiddlewareConfig {
+        &self.config
+    }
+
+    /// The model requests are sent to
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Wait for capacity and send a request through the stack
+    pub async fn call(&mut self, request: ChatRequest) -> Result<ChatResponse, LlmError> {
+        let service = self.service();
+        service.ready().await?.call(request).await
+    }
+
+    fn service(&mut self) -> &mut LlmBoxService {
+        let (provider, model, config) = (&self.provider, &self.model, &self.config);
+        self.service
+            .get_or_insert_with(|| build_service(provider.clone(), model.clone(), config))
+    }
+}
+
+impl Service<ChatRequest> for MiddlewareStack {
+    type Response = ChatResponse;
+    type Error = LlmError;
+    type Future = BoxFuture<'static, Result<ChatResponse, LlmError>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service().poll_ready(cx)
+    }
+
+    fn call(&mut self, request: ChatRequest) -> Self::Future {
+        Service::call(self.service(), request)
+    }
+}
+
+/// Assemble the layers around the provider, innermost first
+fn build_service(
+    provider: Arc<dyn ChatCompletion>,
+    model: String,
+    config: &MiddlewareConfig,
+) -> LlmBoxService {
+    let provider_name = provider.name();
+    let mut service = BoxService::new(ProviderService { provider, model });
+
+    if let Some(timeout) = config.timeout {
+        service = BoxService::new(
+            ServiceBuilder::new()
+                .map_err(move |e| timeout_error(e, timeout))
+                .timeout(timeout)
+                .service(service),
+        );
+    }
+
+    if let Some(rate_limit) = &config.rate_limit {
+        service = BoxService::new(
+            ServiceBuilder::new()
+                .rate_limit(rate_limit.requests_per_period, rate_limit.period)
+                .service(service),
+        );
+    }
+
+    if config.enable_metrics {
+        service = BoxService::new(MetricsLayer::new(provider_name).layer(service));
+    }
+
+    if config.enable_logging {
+        service = BoxService::new(LoggingLayer::new(provider_name).layer(service));
+    }
+
+    service
+}
+
+/// Map errors from tower's timeout layer back into [`LlmError`]
+fn timeout_error(error: BoxError, timeout: Duration) -> LlmError {
+    if error.is::<tower::timeout::error::Elapsed>() {
+        return LlmError::timeout(timeout);
+    }
+    match error.downcast::<LlmError>() {
+        Ok(error) => *error,
+        Err(error) => LlmError::unknown_with_source("Middleware error", error),
+    }
+}
+
+/// Innermost service that forwards requests to the provider
+#[derive(Clone)]
+struct ProviderService {
+    provider: Arc<dyn ChatCompletion>,
+    model: String,
+}
+
+impl Service<ChatRequest> for ProviderService {
+    type Response = ChatResponse;
+    type Error = LlmError;
+    type Future = BoxFuture<'static, Result<ChatResponse, LlmError>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: ChatRequest) -> Self::Future {
+        let provider = self.provider.clone();
+        let model = self.model.clone();
+        Box::pin(async move { provider.chat_completion(request, &model).await })
+    }
+}
+
+/// Layer that logs each request and its outcome
+#[derive(Debug, Clone)]
+pub struct LoggingLayer {
+    provider: &'static str,
+}
+
+impl LoggingLayer {
+    /// Create a logging layer labelled with the provider name
+    pub fn new(provider: &'static str) -> Self {
+        Self { provider }
+    }
+}
+
+impl<S> Layer<S> for LoggingLayer {
+    type Service = LoggingService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        LoggingService {
+            inner,
+            provider: self.provider,
+        }
+    }
+}
+
+/// Service produced by [`LoggingLayer`]
+#[derive(Debug, Clone)]
+pub struct LoggingService<S> {
+    inner: S,
+    provider: &'static str,
+}
+
+impl<S> Service<ChatRequest> for LoggingService<S>
+where
+    S: Service<ChatRequest, Response = ChatResponse, Error = LlmError>,
+    S::Future: Send + 'static,
+{
+    type Response = ChatResponse;
+    type Error = LlmError;
+    type Future = BoxFuture<'static, Result<ChatResponse, LlmError>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: ChatRequest) -> Self::Future {
+        let provider = self.provider;
+        log::debug!(
+            "{provider}: sending chat request with {} message(s)",
+            request.messages.len()
+        );
+
+        let start = Instant::now();
+        let future = self.inner.call(request);
+        Box::pin(async move {
+            let result = future.await;
+            let elapsed = start.elapsed();
+            match &result {
+                Ok(response) => log::info!(
+                    "{provider}: {} completed in {elapsed:?} ({} tokens)",
+                    response.model,
+                    response.usage.total_tokens
+                ),
+                Err(e) => log::warn!("{provider}: request failed after {elapsed:?}: {e}"),
+            }
+            result
+        })
+    }
+}
+
+/// Layer that records request metrics
+///
+/// Emits `llm_requests_total` (labels `provider`, `status`),
+/// `llm_request_duration_seconds` and `llm_tokens_total` (label `kind`).
+#[derive(Debug, Clone)]
+pub struct MetricsLayer {
+    provider: &'static str,
+}
+
+impl MetricsLayer {
+    /// Create a metrics layer labelled with the provider name
+    pub fn new(provider: &'static str) -> Self {
+        Self { provider }
+    }
+}
+
+impl<S> Layer<S> for MetricsLayer {
+    type Service = MetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MetricsService {
+            inner,
+            provider: self.provider,
+        }
+    }
+}
+
+/// Service produced by [`MetricsLayer`]
+#[derive(Debug, Clone)]
+pub struct MetricsService<S> {
+    inner: S,
+    provider: &'static str,
+}
+
+impl<S> Service<ChatRequest> for MetricsService<S>
+where
+    S: Service<ChatRequest, Response = ChatResponse, Error = LlmError>,
+    S::Future: Send + 'static,
+{
+    type Response = ChatResponse;
+    type Error = LlmError;
+    type Future = BoxFuture<'static, Result<ChatResponse, LlmError>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: ChatRequest) -> Self::Future {
+        let provider = self.provider;
+        let start = Instant::now();
+        let future = self.inner.call(request);
+        Box::pin(async move {
+            let result = future.await;
+            let status = if result.is_ok() { "success" } else { "error" };
+
+            metrics::counter!("llm_requests_total", "provider" => provider, "status" => status)
+                .increment(1);
+            metrics::histogram!("llm_request_duration_seconds", "provider" => provider)
+                .record(start.elapsed().as_secs_f64());
+            if let Ok(response) = &result {
+                metrics::counter!("llm_tokens_total", "provider" => provider, "kind" => "prompt")
+                    .increment(response.usage.prompt_tokens.into());
+                metrics::counter!("llm_tokens_total", "provider" => provider, "kind" => "completion")
+                    .increment(response.usage.completion_tokens.into());
+            }
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compat_types::ChatRequestBuilder;
+    use crate::utils::test_helpers::ScriptedProvider;
+
+    #[tokio::test]
+    async fn test_timeout_maps_to_llm_error() {
+        let provider = ScriptedProvider::text(&["done"]).with_delay(Duration::from_millis(200));
+        let mut stack = LlmServiceBuilder::new()
+            .timeout(Duration::from_millis(10))
+            .build(provider, "m".to_string());
+
+        let result = stack
+            .call(ChatRequestBuilder::new().user("hi").build())
+            .await;
+
+        assert!(
+            matches!(result, Err(LlmError::Timeout { duration }) if duration == Duration::from_millis(10))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_delays_excess_requests() {
+        let provider = ScriptedProvider::text(&["done", "done"]);
+        let mut stack = LlmServiceBuilder::new()
+            .rate_limit(1, Duration::from_millis(100))
+            .build(provider, "m".to_string());
+
+        let start = Instant::now();
+        for _ in 0..2 {
+            stack
+                .call(ChatRequestBuilder::new().user("hi").build())
+                .await
+                .unwrap();
+        }
+
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+}
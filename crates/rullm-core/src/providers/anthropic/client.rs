@@ -1,6 +1,7 @@
 use super::types::*;
-use crate::config::{AnthropicConfig, ProviderConfig};
+use crate::config::{AnthropicConfig, ProviderConfig, RetryPolicy};
 use crate::error::LlmError;
+use crate::utils::http::{client_for, send_with_retry, with_idle_timeout};
 use crate::utils::sse::sse_lines;
 use futures::Stream;
 use futures::StreamExt;
@@ -18,6 +19,12 @@ pub struct AnthropicClient {
 impl AnthropicClient {
     /// Create a new Anthropic client
     pub fn new(config: AnthropicConfig) -> Result<Self, LlmError> {
+        let client = client_for(&config)?;
+        Self::with_client(config, client)
+    }
+
+    /// Create a new Anthropic client using an existing HTTP client
+    pub fn with_client(config: AnthropicConfig, client: Client) -> Result<Self, LlmError> {
         config.validate()?;
         let base_url = config
             .base_url
@@ -26,7 +33,7 @@ impl AnthropicClient {
 
         Ok(Self {
             config,
-            client: Client::new(),
+            client,
             base_url,
         })
     }
@@ -41,29 +48,20 @@ impl AnthropicClient {
     pub async fn messages(&self, request: MessagesRequest) -> Result<MessagesResponse, LlmError> {
         let url = format!("{}/v1/messages", self.base_url);
 
-        let mut req = self.client.post(&url);
+        let mut req = self.client.post(&url).timeout(self.config.timeout());
 
         // Add headers from config
         for (key, value) in self.config.headers() {
             req = req.header(key, value);
         }
 
-        let response = req.json(&request).send().await?;
-
-        if !response.status().is_success() {
-            let status = response.status().to_string();
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-
-            return Err(LlmError::api(
-                "anthropic",
-                format!("API Error: {status} - {error_text}"),
-                Some(status),
-                None,
-            ));
-        }
+        let response = send_with_retry(
+            &self.config.retry_policy,
+            "anthropic",
+            req.json(&request),
+            self.config.timeout(),
+        )
+        .await?;
 
         let response_data: MessagesResponse = response.json().await.map_err(|e| {
             LlmError::serialization("Failed to parse MessagesResponse", Box::new(e))
@@ -93,31 +91,17 @@ impl AnthropicClient {
             }
         }
 
-        let response = self
-            .client
-            .post(&url)
-            .headers(header_map)
-            .json(&request)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let status = response.status().to_string();
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-
-            return Err(LlmError::api(
-                "anthropic",
-                format!("API Error: {status} - {error_text}"),
-                Some(status),
-                None,
-            ));
-        }
+        let response = send_with_retry(
+            &self.config.retry_policy,
+            "anthropic",
+            self.client.post(&url).headers(header_map).json(&request),
+            self.config.timeout(),
+        )
+        .await?;
 
         let byte_stream = response.bytes_stream();
-        let sse_stream = sse_lines(byte_stream);
+        let sse_stream =
+            with_idle_timeout(sse_lines(byte_stream), self.config.stream_idle_timeout());
 
         Ok(Box::pin(sse_stream.map(|event_result| {
             event_result.and_then(|data| {
@@ -146,27 +130,18 @@ impl AnthropicClient {
             "system": system,
         });
 
-        let mut req = self.client.post(&url);
+        let mut req = self.client.post(&url).timeout(self.config.timeout());
         for (key, value) in self.config.headers() {
             req = req.header(key, value);
         }
 
-        let response = req.json(&body).send().await?;
-
-        if !response.status().is_success() {
-            let status = response.status().to_string();
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-
-            return Err(LlmError::api(
-                "anthropic",
-                format!("API Error: {status} - {error_text}"),
-                Some(status),
-                None,
-            ));
-        }
+        let response = send_with_retry(
+            &self.config.retry_policy,
+            "anthropic",
+            req.json(&body),
+            self.config.timeout(),
+        )
+        .await?;
 
         let json: serde_json::Value = response.json().await.map_err(|e| {
             LlmError::serialization("Failed to parse count_tokens response", Box::new(e))
@@ -185,6 +160,50 @@ impl AnthropicClient {
         Ok(tokens)
     }
 
+    /// List available models
+    pub async fn list_models(&self) -> Result<Vec<String>, LlmError> {
+        let url = format!("{}/v1/models", self.base_url);
+
+        let mut req = self.client.get(&url).timeout(self.config.timeout());
+        for (key, value) in self.config.headers() {
+            req = req.header(key, value);
+        }
+
+        let response = send_with_retry(
+            &self.config.retry_policy,
+            "anthropic",
+            req,
+            self.config.timeout(),
+        )
+        .await?;
+
+        let json: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| LlmError::serialization("Failed to parse models response", Box::new(e)))?;
+
+        let models_array = json.get("data").and_then(|d| d.as_array()).ok_or_else(|| {
+            LlmError::serialization(
+                "Invalid models response format",
+                Box::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "Missing data array",
+                )),
+            )
+        })?;
+
+        let models: Vec<String> = models_array
+            .iter()
+            .filter_map(|m| {
+                m.get("id")
+                    .and_then(|id| id.as_str())
+                    .map(|s| s.to_string())
+            })
+            .collect();
+
+        Ok(models)
+    }
+
     /// Health check
     pub async fn health_check(&self) -> Result<(), LlmError> {
         // Anthropic doesn't have a dedicated health endpoint
@@ -194,22 +213,18 @@ impl AnthropicClient {
         let minimal_request =
             MessagesRequest::new("claude-3-haiku-20240307", vec![Message::user("hi")], 1);
 
-        let mut req = self.client.post(&url);
+        let mut req = self.client.post(&url).timeout(self.config.timeout());
         for (key, value) in self.config.headers() {
             req = req.header(key, value);
         }
 
-        let response = req.json(&minimal_request).send().await?;
-
-        if response.status().is_success() {
-            Ok(())
-        } else {
-            Err(LlmError::api(
-                "anthropic",
-                "Health check failed",
-                Some(response.status().to_string()),
-                None,
-            ))
-        }
+        send_with_retry(
+            &RetryPolicy::none(),
+            "anthropic",
+            req.json(&minimal_request),
+            self.config.timeout(),
+        )
+        .await?;
+        Ok(())
     }
 }
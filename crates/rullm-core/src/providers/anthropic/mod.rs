@@ -24,6 +24,7 @@
 //! ```
 
 pub mod client;
+pub(crate) mod provider;
 pub mod types;
 
 pub use client::AnthropicClient;
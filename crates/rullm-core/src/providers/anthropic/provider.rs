@@ -0,0 +1,669 @@
+//! [`ChatCompletion`] implementation for [`AnthropicClient`]
+
+use super::client::AnthropicClient;
+use super::types::{
+    ContentBlock, DocumentSource, ImageSource, Message, MessageContent, MessagesRequest,
+    MessagesResponse, Role, SystemPrompt, Tool, ToolChoice,
+};
+use crate::compat_types::{
+    self, ChatMessage, ChatRequest, ChatResponse, ChatRole, ChatStreamEvent, TokenUsage, ToolCall,
+};
+use crate::error::LlmError;
+use crate::stream::{anthropic_events, cancel_with, chat_stream_events, unless_cancelled};
+use crate::types::{ChatCompletion, LlmProvider, StreamConfig, StreamResult};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use futures::StreamExt;
+
+/// Anthropic requires `max_tokens`; used when the request does not set one
+const DEFAULT_MAX_TOKENS: u32 = 1024;
+
+/// Tool used to force JSON output when no schema name is given
+const JSON_TOOL_NAME: &str = "json_response";
+
+/// Image formats accepted by the Messages API
+const IMAGE_MEDIA_TYPES: &[&str] = &["image/jpeg", "image/png", "image/gif", "image/webp"];
+
+/// Convert a provider-neutral content part into an Anthropic content block
+fn to_anthropic_block(part: compat_types::ContentPart) -> Result<ContentBlock, LlmError> {
+    Ok(match part {
+        compat_types::ContentPart::Text { text } => ContentBlock::Text { text },
+        compat_types::ContentPart::ImageUrl { url } => ContentBlock::Image {
+            source: ImageSource::Url { url },
+        },
+        compat_types::ContentPart::ImageBase64 { media_type, data } => {
+            if !IMAGE_MEDIA_TYPES.contains(&media_type.as_str()) {
+                return Err(LlmError::validation(format!(
+                    "Anthropic does not accept {media_type} images, only {}",
+                    IMAGE_MEDIA_TYPES.join(", ")
+                )));
+            }
+            ContentBlock::Image {
+                source: ImageSource::Base64 { media_type, data },
+            }
+        }
+        compat_types::ContentPart::Document {
+            media_type, data, ..
+        } => {
+            let source = match media_type.as_str() {
+                "application/pdf" => DocumentSource::Base64 { media_type, data },
+                // Base64 sources are PDF only; text goes as a decoded string
+                "text/plain" => DocumentSource::Text {
+                    media_type,
+                    data: decode_text(&data)?,
+                },
+                _ => {
+                    return Err(LlmError::validation(format!(
+                        "Anthropic does not accept {media_type} documents, only application/pdf and text/plain"
+                    )));
+                }
+            };
+            ContentBlock::Document { source }
+        }
+    })
+}
+
+/// Decode a base64 `text/plain` document into its UTF-8 text
+fn decode_text(data: &str) -> Result<String, LlmError> {
+    STANDARD
+        .decode(data)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| LlmError::validation("text/plain document is not base64-encoded UTF-8"))
+}
+
+/// Text followed by the message's content parts
+fn content_blocks(msg: ChatMessage) -> Result<Vec<ContentBlock>, LlmError> {
+    let text = (!msg.content.is_empty()).then(|| ContentBlock::text(msg.content));
+    text.into_iter()
+        .map(Ok)
+        .chain(msg.parts.into_iter().map(to_anthropic_block))
+        .collect()
+}
+
+/// Tool that JSON output is forced through, since Anthropic has no JSON mode
+fn structured_output_tool(format: &compat_types::ResponseFormat) -> Option<Tool> {
+    let (name, schema) = match format {
+        compat_types::ResponseFormat::Text => return None,
+        compat_types::ResponseFormat::Json => (
+            JSON_TOOL_NAME.to_string(),
+            serde_json::json!({"type": "object"}),
+        ),
+        compat_types::ResponseFormat::JsonSchema { name, schema } => (name.clone(), schema.clone()),
+    };
+    Some(Tool {
+        name,
+        description: "Respond with JSON by calling this tool with the answer as its input"
+            .to_string(),
+        input_schema: schema,
+    })
+}
+
+/// Move the forced tool call's input into the message text
+fn unwrap_structured_output(response: &mut ChatResponse, tool_name: &str) {
+    let message = &mut response.message;
+    if let Some(index) = message.tool_calls.iter().position(|c| c.name == tool_name) {
+        let call = message.tool_calls.remove(index);
+        message.content = call.arguments.to_string();
+    }
+}
+
+/// Streaming counterpart of [`unwrap_structured_output`]: the forced tool
+/// call's argument fragments are yielded as answer tokens
+fn unwrap_structured_stream(
+    mut events: StreamResult<ChatStreamEvent>,
+    tool_name: String,
+) -> StreamResult<ChatStreamEvent> {
+    Box::pin(async_stream::stream! {
+        let mut json_index = None;
+        while let Some(event) = events.next().await {
+            match event {
+                Ok(ChatStreamEvent::ToolCallStart { index, ref name, .. })
+                    if json_index.is_none() && *name == tool_name =>
+                {
+                    json_index = Some(index);
+                }
+                Ok(ChatStreamEvent::ToolCallDelta { index, arguments })
+                    if json_index == Some(index) =>
+                {
+                    yield Ok(ChatStreamEvent::Token(arguments));
+                }
+                Ok(ChatStreamEvent::ToolCallComplete { index, .. })
+                    if json_index == Some(index) => {}
+                event => yield event,
+            }
+        }
+    })
+}
+
+/// Convert a provider-neutral request into an Anthropic messages request.
+///
+/// System messages are joined into the top-level `system` prompt. Tool calls
+/// become `tool_use` blocks, and consecutive tool results are sent together
+/// as `tool_result` blocks in a single user message. Images and documents
+/// are only accepted in user messages. A JSON response format is emulated by
+/// forcing a call to a tool whose input schema is the requested schema.
+pub fn to_anthropic_request(
+    request: ChatRequest,
+    model: &str,
+) -> Result<MessagesRequest, LlmError> {
+    request.reject_sampling(
+        "Anthropic",
+        &[
+            "seed",
+            "frequency_penalty",
+            "presence_penalty",
+            "logit_bias",
+            "extra_params",
+        ],
+    )?;
+    let mut system_parts = Vec::new();
+    let mut messages: Vec<Message> = Vec::new();
+
+    for msg in request.messages {
+        if msg.has_media() && msg.role != ChatRole::User {
+            return Err(LlmError::validation(
+                "Anthropic only accepts images and documents in user messages",
+            ));
+        }
+        if msg.role == ChatRole::User && !msg.parts.is_empty() {
+            messages.push(Message::user_with_blocks(content_blocks(msg)?));
+            continue;
+        }
+        match (msg.role, msg.tool_call_id) {
+            (ChatRole::System, _) => system_parts.push(msg.content),
+            (ChatRole::Assistant, _) if !msg.tool_calls.is_empty() => {
+                let text = (!msg.content.is_empty()).then(|| ContentBlock::text(msg.content));
+                let calls = msg
+                    .tool_calls
+                    .into_iter()
+                    .map(|call| ContentBlock::ToolUse {
+                        id: call.id,
+                        name: call.name,
+                        input: call.arguments,
+                    });
+                messages.push(Message::assistant_with_blocks(
+                    text.into_iter().chain(calls).collect(),
+                ));
+            }
+            (ChatRole::Assistant, _) => messages.push(Message::assistant(msg.content)),
+            (ChatRole::Tool, Some(tool_use_id)) => {
+                let result = ContentBlock::ToolResult {
+                    tool_use_id,
+                    content: Some(msg.content),
+                    is_error: None,
+                };
+                match messages.last_mut() {
+                    Some(Message {
+                        role: Role::User,
+                        content: MessageContent::Blocks(blocks),
+                    }) if blocks
+                        .iter()
+                        .all(|block| matches!(block, ContentBlock::ToolResult { .. })) =>
+                    {
+                        blocks.push(result)
+                    }
+                    _ => messages.push(Message::user_with_blocks(vec![result])),
+                }
+            }
+            (ChatRole::User | ChatRole::Tool, _) => messages.push(Message::user(msg.content)),
+        }
+    }
+
+    let mut anthropic_request = MessagesRequest::new(
+        model,
+        messages,
+        request.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+    );
+    if !system_parts.is_empty() {
+        anthropic_request.system = Some(SystemPrompt::Text(system_parts.join("\n\n")));
+    }
+    anthropic_request.temperature = request.temperature;
+    anthropic_request.top_p = request.top_p;
+    anthropic_request.top_k = request.top_k;
+    anthropic_request.stop_sequences = request.stop;
+    anthropic_request.tools = request.tools.map(|tools| {
+        tools
+            .into_iter()
+            .map(|tool| Tool {
+                name: tool.name,
+                description: tool.description,
+                input_schema: tool.parameters,
+            })
+            .collect()
+    });
+    anthropic_request.tool_choice = request.tool_choice.map(|choice| match choice {
+        compat_types::ToolChoice::Auto => ToolChoice::Auto {
+            choice_type: "auto".to_string(),
+        },
+        compat_types::ToolChoice::None => ToolChoice::None {
+            choice_type: "none".to_string(),
+        },
+        compat_types::ToolChoice::Required => ToolChoice::Any {
+            choice_type: "any".to_string(),
+        },
+        compat_types::ToolChoice::Tool(name) => ToolChoice::Tool {
+            choice_type: "tool".to_string(),
+            name,
+        },
+    });
+    if let Some(tool) = request
+        .response_format
+        .as_ref()
+        .and_then(structured_output_tool)
+    {
+        anthropic_request.tool_choice = Some(ToolChoice::Tool {
+            choice_type: "tool".to_string(),
+            name: tool.name.clone(),
+        });
+        anthropic_request
+            .tools
+            .get_or_insert_with(Vec::new)
+            .push(tool);
+    }
+    Ok(anthropic_request)
+}
+
+/// Convert an Anthropic messages response into the provider-neutral response
+pub fn from_anthropic_response(response: MessagesResponse) -> Result<ChatResponse, LlmError> {
+    if response.content.is_empty() {
+        return Err(LlmError::serialization(
+            "No content in Anthropic response",
+            Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Empty content array",
+            )),
+        ));
+    }
+
+    let content = response
+        .content
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect::<String>();
+
+    let tool_calls = response
+        .content
+        .into_iter()
+        .filter_map(|block| match block {
+            ContentBlock::ToolUse { id, name, input } => Some(ToolCall {
+                id,
+                name,
+                arguments: input,
+            }),
+            _ => None,
+        })
+        .collect();
+
+    let finish_reason = response
+        .stop_reason
+        .and_then(|reason| serde_json::to_value(reason).ok())
+        .and_then(|value| value.as_str().map(|s| s.to_string()));
+
+    Ok(ChatResponse {
+        message: ChatMessage::assistant_tool_calls(content, tool_calls),
+        model: response.model,
+        usage: TokenUsage {
+            prompt_tokens: response.usage.input_tokens,
+            completion_tokens: response.usage.output_tokens,
+            total_tokens: response.usage.input_tokens + response.usage.output_tokens,
+        },
+        finish_reason,
+        provider_metadata: Some(
+            [("id".to_string(), serde_json::Value::String(response.id))]
+                .into_iter()
+                .collect(),
+        ),
+    })
+}
+
+impl AnthropicClient {
+    /// Parse a raw Messages API response body into a [`ChatResponse`]
+    pub fn parse_anthropic_response(
+        &self,
+        response: serde_json::Value,
+    ) -> Result<ChatResponse, LlmError> {
+        let response: MessagesResponse = serde_json::from_value(response).map_err(|e| {
+            LlmError::serialization("Failed to parse MessagesResponse", Box::new(e))
+        })?;
+        from_anthropic_response(response)
+    }
+}
+
+#[async_trait::async_trait]
+impl LlmProvider for AnthropicClient {
+    fn name(&self) -> &'static str {
+        "anthropic"
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        &["anthropic", "claude"]
+    }
+
+    fn default_base_url(&self) -> Option<&'static str> {
+        Some("https://api.anthropic.com")
+    }
+
+    fn env_key(&self) -> &'static str {
+        "ANTHROPIC_API_KEY"
+    }
+
+    async fn available_models(&self) -> Result<Vec<String>, LlmError> {
+        AnthropicClient::list_models(self).await
+    }
+
+    async fn health_check(&self) -> Result<(), LlmError> {
+        AnthropicClient::health_check(self).await
+    }
+}
+
+#[async_trait::async_trait]
+impl ChatCompletion for AnthropicClient {
+    async fn chat_completion(
+        &self,
+        request: ChatRequest,
+        model: &str,
+    ) -> Result<ChatResponse, LlmError> {
+        let json_tool = request
+            .response_format
+            .as_ref()
+            .and_then(structured_output_tool)
+            .map(|tool| tool.name);
+        let response = self.messages(to_anthropic_request(request, model)?).await?;
+        let mut response = from_anthropic_response(response)?;
+        if let Some(tool_name) = json_tool {
+            unwrap_structured_output(&mut response, &tool_name);
+        }
+        Ok(response)
+    }
+
+    async fn chat_completion_stream(
+        &self,
+        request: ChatRequest,
+        model: &str,
+        config: Option<StreamConfig>,
+    ) -> StreamResult<ChatStreamEvent> {
+        let cancel = config.and_then(|config| config.cancel);
+        let json_tool = request
+            .response_format
+            .as_ref()
+            .and_then(structured_output_tool)
+            .map(|tool| tool.name);
+        let request = match to_anthropic_request(request, model) {
+            Ok(request) => request,
+            Err(e) => return Box::pin(futures::stream::once(async move { Err(e) })),
+        };
+        let stream = match unless_cancelled(cancel.as_ref(), self.messages_stream(request)).await {
+            Some(Ok(stream)) => stream,
+            Some(Err(e)) => return Box::pin(futures::stream::once(async move { Err(e) })),
+            None => return Box::pin(futures::stream::empty()),
+        };
+
+        let events = chat_stream_events(anthropic_events(stream));
+        let events = match json_tool {
+            Some(tool_name) => unwrap_structured_stream(events, tool_name),
+            None => events,
+        };
+        cancel_with(events, cancel)
+    }
+
+    /// Claude tokenizes slightly denser than OpenAI, roughly 3.5 characters per token
+    async fn estimate_tokens(&self, text: &str, _model: &str) -> Result<u32, LlmError> {
+        Ok((text.chars().count() as f32 / 3.5).ceil() as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compat_types::ChatRequestBuilder;
+
+    #[test]
+    fn test_to_anthropic_request_routes_system_prompt() {
+        let request = ChatRequestBuilder::new()
+            .system("Be brief")
+            .user("Hi")
+            .assistant("Hello")
+            .user("Bye")
+            .build();
+
+        let anthropic_request = to_anthropic_request(request, "claude-3-haiku-20240307").unwrap();
+
+        assert_eq!(anthropic_request.messages.len(), 3);
+        assert_eq!(anthropic_request.max_tokens, DEFAULT_MAX_TOKENS);
+        assert!(matches!(
+            anthropic_request.system,
+            Some(SystemPrompt::Text(ref s)) if s == "Be brief"
+        ));
+    }
+
+    #[test]
+    fn test_text_document_is_sent_as_text_source() {
+        let text = compat_types::ContentPart::Document {
+            media_type: "text/plain".to_string(),
+            data: STANDARD.encode("hello, world"),
+            name: Some("notes.txt".to_string()),
+        };
+        let pdf = compat_types::ContentPart::pdf("JVBERi0=");
+        let request = ChatRequestBuilder::new()
+            .user_with_parts("Summarize", vec![text, pdf])
+            .build();
+
+        let body =
+            serde_json::to_value(to_anthropic_request(request, "claude-3-haiku-20240307").unwrap())
+                .unwrap();
+
+        let content = &body["messages"][0]["content"];
+        assert_eq!(
+            content[1]["source"],
+            serde_json::json!({"type": "text", "media_type": "text/plain", "data": "hello, world"})
+        );
+        assert_eq!(content[2]["source"]["type"], "base64");
+        assert_eq!(content[2]["source"]["media_type"], "application/pdf");
+    }
+
+    #[test]
+    fn test_to_anthropic_request_translates_tools() {
+        let calls = vec![
+            ToolCall {
+                id: "toolu_1".to_string(),
+                name: "get_weather".to_string(),
+                arguments: serde_json::json!({"city": "Paris"}),
+            },
+            ToolCall {
+                id: "toolu_2".to_string(),
+                name: "get_weather".to_string(),
+                arguments: serde_json::json!({"city": "Rome"}),
+            },
+        ];
+        let request = ChatRequestBuilder::new()
+            .user("Weather in Paris and Rome?")
+            .message(ChatMessage::assistant_tool_calls("Checking", calls))
+            .tool_result("toolu_1", "sunny")
+            .tool_result("toolu_2", "rainy")
+            .tool(compat_types::ToolDefinition::new(
+                "get_weather",
+                "Look up the weather",
+                serde_json::json!({"type": "object"}),
+            ))
+            .tool_choice(compat_types::ToolChoice::Required)
+            .build();
+
+        let body =
+            serde_json::to_value(to_anthropic_request(request, "claude-3-haiku-20240307").unwrap())
+                .unwrap();
+
+        assert_eq!(body["tools"][0]["input_schema"]["type"], "object");
+        assert_eq!(body["tool_choice"]["type"], "any");
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1]["content"][0]["type"], "text");
+        assert_eq!(messages[1]["content"][1]["type"], "tool_use");
+        assert_eq!(messages[1]["content"][2]["input"]["city"], "Rome");
+        assert_eq!(messages[2]["role"], "user");
+        assert_eq!(messages[2]["content"][0]["tool_use_id"], "toolu_1");
+        assert_eq!(messages[2]["content"][1]["tool_use_id"], "toolu_2");
+    }
+
+    #[test]
+    fn test_from_anthropic_response_parses_tool_use() {
+        let response: MessagesResponse = serde_json::from_value(serde_json::json!({
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "content": [
+                {"type": "text", "text": "Let me check."},
+                {"type": "tool_use", "id": "toolu_1", "name": "get_weather", "input": {"city": "Paris"}}
+            ],
+            "model": "claude-3-haiku-20240307",
+            "stop_reason": "tool_use",
+            "stop_sequence": null,
+            "usage": {"input_tokens": 10, "output_tokens": 5}
+        }))
+        .unwrap();
+
+        let response = from_anthropic_response(response).unwrap();
+
+        assert_eq!(response.message.content, "Let me check.");
+        assert_eq!(response.message.tool_calls[0].id, "toolu_1");
+        assert_eq!(response.message.tool_calls[0].arguments["city"], "Paris");
+        assert_eq!(response.finish_reason, Some("tool_use".to_string()));
+    }
+
+    #[test]
+    fn test_to_anthropic_request_translates_content_parts() {
+        let request = ChatRequestBuilder::new()
+            .user_with_parts(
+                "Compare",
+                vec![
+                    compat_types::ContentPart::image_url("https://example.com/cat.png"),
+                    compat_types::ContentPart::image_base64("image/png", "aGVsbG8="),
+                    compat_types::ContentPart::pdf("JVBERi0="),
+                ],
+            )
+            .build();
+
+        let body =
+            serde_json::to_value(to_anthropic_request(request, "claude-3-haiku-20240307").unwrap())
+                .unwrap();
+
+        let content = &body["messages"][0]["content"];
+        assert_eq!(content[0]["text"], "Compare");
+        assert_eq!(content[1]["source"]["type"], "url");
+        assert_eq!(content[2]["source"]["media_type"], "image/png");
+        assert_eq!(content[3]["type"], "document");
+        assert_eq!(content[3]["source"]["media_type"], "application/pdf");
+    }
+
+    #[test]
+    fn test_to_anthropic_request_rejects_unsupported_parts() {
+        let request = ChatRequestBuilder::new()
+            .user_with_parts(
+                "Look",
+                vec![compat_types::ContentPart::image_base64(
+                    "image/tiff",
+                    "AAAA",
+                )],
+            )
+            .build();
+
+        assert!(matches!(
+            to_anthropic_request(request, "claude-3-haiku-20240307"),
+            Err(LlmError::Validation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_response_format_forces_json_tool() {
+        let schema =
+            serde_json::json!({"type": "object", "properties": {"n": {"type": "integer"}}});
+        let request = ChatRequestBuilder::new()
+            .user("Count")
+            .response_format(compat_types::ResponseFormat::json_schema(
+                "count",
+                schema.clone(),
+            ))
+            .build();
+
+        let anthropic_request = to_anthropic_request(request, "claude-3-haiku-20240307").unwrap();
+        let body = serde_json::to_value(&anthropic_request).unwrap();
+
+        assert_eq!(body["tools"][0]["name"], "count");
+        assert_eq!(body["tools"][0]["input_schema"], schema);
+        assert_eq!(
+            body["tool_choice"],
+            serde_json::json!({"type": "tool", "name": "count"})
+        );
+
+        let mut response = ChatResponse {
+            message: ChatMessage::assistant_tool_calls(
+                "",
+                vec![ToolCall {
+                    id: "toolu_1".to_string(),
+                    name: "count".to_string(),
+                    arguments: serde_json::json!({"n": 3}),
+                }],
+            ),
+            model: "claude-3-haiku-20240307".to_string(),
+            usage: TokenUsage {
+                prompt_tokens: 1,
+                completion_tokens: 1,
+                total_tokens: 2,
+            },
+            finish_reason: Some("tool_use".to_string()),
+            provider_metadata: None,
+        };
+        unwrap_structured_output(&mut response, "count");
+
+        assert_eq!(response.message.content, r#"{"n":3}"#);
+        assert!(response.message.tool_calls.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_structured_stream_yields_json_as_tokens() {
+        let events = vec![
+            ChatStreamEvent::ToolCallStart {
+                index: 0,
+                id: "toolu_1".to_string(),
+                name: "count".to_string(),
+            },
+            ChatStreamEvent::ToolCallDelta {
+                index: 0,
+                arguments: r#"{"n":"#.to_string(),
+            },
+            ChatStreamEvent::ToolCallDelta {
+                index: 0,
+                arguments: "3}".to_string(),
+            },
+            ChatStreamEvent::ToolCallComplete {
+                index: 0,
+                call: ToolCall {
+                    id: "toolu_1".to_string(),
+                    name: "count".to_string(),
+                    arguments: serde_json::json!({"n": 3}),
+                },
+            },
+            ChatStreamEvent::Finish("tool_use".to_string()),
+            ChatStreamEvent::Done,
+        ];
+        let stream = Box::pin(futures::stream::iter(events.into_iter().map(Ok)));
+
+        let events: Vec<ChatStreamEvent> = unwrap_structured_stream(stream, "count".to_string())
+            .map(|event| event.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(
+            events,
+            vec![
+                ChatStreamEvent::Token(r#"{"n":"#.to_string()),
+                ChatStreamEvent::Token("3}".to_string()),
+                ChatStreamEvent::Finish("tool_use".to_string()),
+                ChatStreamEvent::Done,
+            ]
+        );
+    }
+}
@@ -92,6 +92,8 @@ pub enum ContentBlock {
     Text { text: String },
     /// Image content
     Image { source: ImageSource },
+    /// Document content (e.g. a PDF)
+    Document { source: DocumentSource },
     /// Tool use (request to call a tool)
     ToolUse {
         id: String,
@@ -114,6 +116,20 @@ pub enum ContentBlock {
 pub enum ImageSource {
     /// Base64-encoded image
     Base64 { media_type: String, data: String },
+    /// Image fetched from a URL
+    Url { url: String },
+}
+
+/// Document source (base64, plain text or URL)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DocumentSource {
+    /// Base64-encoded document, only accepted for PDFs
+    Base64 { media_type: String, data: String },
+    /// Plain text document
+    Text { media_type: String, data: String },
+    /// Document fetched from a URL
+    Url { url: String },
 }
 
 /// System prompt can be a string or array of text blocks
@@ -176,6 +192,11 @@ pub enum ToolChoice {
         #[serde(rename = "type")]
         choice_type: String, // "any"
     },
+    /// Model must not use tools
+    None {
+        #[serde(rename = "type")]
+        choice_type: String, // "none"
+    },
     /// Force a specific tool
     Tool {
         #[serde(rename = "type")]
@@ -223,7 +244,8 @@ pub enum StopReason {
 /// Token usage information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Usage {
-    /// Number of input tokens
+    /// Number of input tokens (absent from streamed `message_delta` usage)
+    #[serde(default)]
     pub input_tokens: u32,
     /// Number of output tokens
     pub output_tokens: u32,
@@ -283,6 +305,8 @@ pub struct MessageStartData {
 pub enum ContentBlockStart {
     Text { text: String },
     ToolUse { id: String, name: String },
+    Thinking { thinking: String },
+    RedactedThinking { data: String },
 }
 
 /// Delta (incremental change)
@@ -293,6 +317,10 @@ pub enum Delta {
     TextDelta { text: String },
     /// Tool input delta
     InputJsonDelta { partial_json: String },
+    /// Extended thinking delta
+    ThinkingDelta { thinking: String },
+    /// Signature verifying a thinking block
+    SignatureDelta { signature: String },
 }
 
 /// Message delta data
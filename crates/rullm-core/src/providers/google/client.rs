@@ -1,6 +1,7 @@
 use super::types::*;
-use crate::config::{GoogleAiConfig, ProviderConfig};
+use crate::config::{GoogleAiConfig, ProviderConfig, RetryPolicy};
 use crate::error::LlmError;
+use crate::utils::http::{client_for, send_with_retry, with_idle_timeout};
 use crate::utils::sse::sse_lines;
 use futures::Stream;
 use futures::StreamExt;
@@ -18,6 +19,12 @@ pub struct GoogleClient {
 impl GoogleClient {
     /// Create a new Google client
     pub fn new(config: GoogleAiConfig) -> Result<Self, LlmError> {
+        let client = client_for(&config)?;
+        Self::with_client(config, client)
+    }
+
+    /// Create a new Google client using an existing HTTP client
+    pub fn with_client(config: GoogleAiConfig, client: Client) -> Result<Self, LlmError> {
         config.validate()?;
         let base_url = config
             .base_url
@@ -26,7 +33,7 @@ impl GoogleClient {
 
         Ok(Self {
             config,
-            client: Client::new(),
+            client,
             base_url,
         })
     }
@@ -50,27 +57,18 @@ impl GoogleClient {
             self.config.api_key()
         );
 
-        let mut req = self.client.post(&url);
+        let mut req = self.client.post(&url).timeout(self.config.timeout());
         for (key, value) in self.config.headers() {
             req = req.header(key, value);
         }
 
-        let response = req.json(&request).send().await?;
-
-        if !response.status().is_success() {
-            let status = response.status().to_string();
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-
-            return Err(LlmError::api(
-                "google",
-                format!("API Error: {status} - {error_text}"),
-                Some(status),
-                None,
-            ));
-        }
+        let response = send_with_retry(
+            &self.config.retry_policy,
+            "google",
+            req.json(&request),
+            self.config.timeout(),
+        )
+        .await?;
 
         let response_data: GenerateContentResponse = response.json().await.map_err(|e| {
             LlmError::serialization("Failed to parse GenerateContentResponse", Box::new(e))
@@ -105,31 +103,17 @@ impl GoogleClient {
             }
         }
 
-        let response = self
-            .client
-            .post(&url)
-            .headers(header_map)
-            .json(&request)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let status = response.status().to_string();
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-
-            return Err(LlmError::api(
-                "google",
-                format!("API Error: {status} - {error_text}"),
-                Some(status),
-                None,
-            ));
-        }
+        let response = send_with_retry(
+            &self.config.retry_policy,
+            "google",
+            self.client.post(&url).headers(header_map).json(&request),
+            self.config.timeout(),
+        )
+        .await?;
 
         let byte_stream = response.bytes_stream();
-        let sse_stream = sse_lines(byte_stream);
+        let sse_stream =
+            with_idle_timeout(sse_lines(byte_stream), self.config.stream_idle_timeout());
 
         Ok(Box::pin(sse_stream.map(|event_result| {
             event_result.and_then(|data| {
@@ -147,21 +131,18 @@ impl GoogleClient {
     pub async fn list_models(&self) -> Result<Vec<String>, LlmError> {
         let url = format!("{}/models?key={}", self.base_url, self.config.api_key());
 
-        let mut req = self.client.get(&url);
+        let mut req = self.client.get(&url).timeout(self.config.timeout());
         for (key, value) in self.config.headers() {
             req = req.header(key, value);
         }
 
-        let response = req.send().await?;
-
-        if !response.status().is_success() {
-            return Err(LlmError::api(
-                "google",
-                "Failed to fetch available models",
-                Some(response.status().to_string()),
-                None,
-            ));
-        }
+        let response = send_with_retry(
+            &self.config.retry_policy,
+            "google",
+            req,
+            self.config.timeout(),
+        )
+        .await?;
 
         let json: serde_json::Value = response
             .json()
@@ -197,22 +178,12 @@ impl GoogleClient {
     pub async fn health_check(&self) -> Result<(), LlmError> {
         let url = format!("{}/models?key={}", self.base_url, self.config.api_key());
 
-        let mut req = self.client.get(&url);
+        let mut req = self.client.get(&url).timeout(self.config.timeout());
         for (key, value) in self.config.headers() {
             req = req.header(key, value);
         }
 
-        let response = req.send().await?;
-
-        if response.status().is_success() {
-            Ok(())
-        } else {
-            Err(LlmError::api(
-                "google",
-                "Health check failed",
-                Some(response.status().to_string()),
-                None,
-            ))
-        }
+        send_with_retry(&RetryPolicy::none(), "google", req, self.config.timeout()).await?;
+        Ok(())
     }
 }
@@ -22,6 +22,7 @@
 //! ```
 
 pub mod client;
+pub(crate) mod provider;
 pub mod types;
 
 pub use client::GoogleClient;
@@ -0,0 +1,543 @@
+//! [`ChatCompletion`] implementation for [`GoogleClient`]
+
+use super::client::GoogleClient;
+use super::types::{
+    Content, FunctionCall, FunctionCallingConfig, FunctionDeclaration, FunctionResponse,
+    GenerateContentRequest, GenerateContentResponse, GenerationConfig, Part, Tool, ToolConfig,
+};
+use crate::compat_types::{
+    ChatMessage, ChatRequest, ChatResponse, ChatRole, ChatStreamEvent, ContentPart, ResponseFormat,
+    TokenUsage, ToolCall, ToolChoice,
+};
+use crate::error::LlmError;
+use crate::stream::{cancel_with, chat_stream_events, google_events, unless_cancelled};
+use crate::types::{ChatCompletion, LlmProvider, StreamConfig, StreamResult};
+use std::collections::HashMap;
+
+/// Convert a provider-neutral content part into a Gemini part
+fn to_google_part(part: ContentPart) -> Result<Part, LlmError> {
+    match part {
+        ContentPart::Text { text } => Ok(Part::text(text)),
+        ContentPart::ImageUrl { url } => Err(LlmError::validation(format!(
+            "Google does not fetch image URLs ({url}); send the image inline as base64"
+        ))),
+        ContentPart::ImageBase64 { media_type, data }
+        | ContentPart::Document {
+            media_type, data, ..
+        } => Ok(Part::image(media_type, data)),
+    }
+}
+
+/// Strip JSON Schema keywords that Gemini's OpenAPI-style schema rejects
+fn google_schema(mut schema: serde_json::Value) -> serde_json::Value {
+    match &mut schema {
+        serde_json::Value::Object(map) => {
+            map.remove("$schema");
+            map.remove("additionalProperties");
+            for value in map.values_mut() {
+                *value = google_schema(value.take());
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for value in items.iter_mut() {
+                *value = google_schema(value.take());
+            }
+        }
+        _ => {}
+    }
+    schema
+}
+
+/// Convert a provider-neutral request into a Gemini generate content request.
+///
+/// System messages are joined into `system_instruction`. Gemini identifies
+/// function responses by function name rather than call id, so tool results
+/// are matched to the name of the call they answer. Images and documents are
+/// only accepted in user messages.
+pub fn to_google_request(request: ChatRequest) -> Result<GenerateContentRequest, LlmError> {
+    request.reject_sampling(
+        "Google",
+        &[
+            "seed",
+            "frequency_penalty",
+            "presence_penalty",
+            "logit_bias",
+            "extra_params",
+        ],
+    )?;
+    let mut system_parts = Vec::new();
+    let mut contents: Vec<Content> = Vec::new();
+    let mut call_names = HashMap::new();
+
+    for msg in request.messages {
+        if msg.has_media() && msg.role != ChatRole::User {
+            return Err(LlmError::validation(
+                "Google only accepts images and documents in user messages",
+            ));
+        }
+        if msg.role == ChatRole::User && !msg.parts.is_empty() {
+            let text = (!msg.content.is_empty()).then(|| Part::text(msg.content));
+            let parts = msg
+                .parts
+                .into_iter()
+                .map(to_google_part)
+                .collect::<Result<Vec<_>, _>>()?;
+            contents.push(Content::user_with_parts(
+                text.into_iter().chain(parts).collect(),
+            ));
+            continue;
+        }
+        match (msg.role, msg.tool_call_id) {
+            (ChatRole::System, _) => system_parts.push(msg.content),
+            (ChatRole::Assistant, _) if !msg.tool_calls.is_empty() => {
+                let text = (!msg.content.is_empty()).then(|| Part::text(msg.content));
+                let calls = msg.tool_calls.into_iter().map(|call| {
+                    call_names.insert(call.id, call.name.clone());
+                    Part::FunctionCall {
+                        function_call: FunctionCall {
+                            name: call.name,
+                            args: call.arguments,
+                        },
+                    }
+                });
+                contents.push(Content::model_with_parts(
+                    text.into_iter().chain(calls).collect(),
+                ));
+            }
+            (ChatRole::Assistant, _) => contents.push(Content::model(msg.content)),
+            (ChatRole::Tool, Some(id)) => {
+                let part = Part::FunctionResponse {
+                    function_response: FunctionResponse {
+                        name: call_names.get(&id).cloned().unwrap_or(id),
+                        response: function_response(msg.content),
+                    },
+                };
+                // Responses to parallel calls belong in the same turn
+                match contents.last_mut() {
+                    Some(content)
+                        if content.role.as_deref() == Some("user")
+                            && content
+                                .parts
+                                .iter()
+                                .all(|part| matches!(part, Part::FunctionResponse { .. })) =>
+                    {
+                        content.parts.push(part)
+                    }
+                    _ => contents.push(Content::user_with_parts(vec![part])),
+                }
+            }
+            (ChatRole::User | ChatRole::Tool, _) => contents.push(Content::user(msg.content)),
+        }
+    }
+
+    let mut google_request = GenerateContentRequest::new(contents);
+    if !system_parts.is_empty() {
+        google_request = google_request.with_system(system_parts.join("\n\n"));
+    }
+    let (response_mime_type, response_schema) = match request.response_format {
+        None | Some(ResponseFormat::Text) => (None, None),
+        Some(ResponseFormat::Json) => (Some("application/json".to_string()), None),
+        Some(ResponseFormat::JsonSchema { schema, .. }) => (
+            Some("application/json".to_string()),
+            Some(google_schema(schema)),
+        ),
+    };
+    if request.temperature.is_some()
+        || request.max_tokens.is_some()
+        || request.top_p.is_some()
+        || request.top_k.is_some()
+        || request.stop.is_some()
+        || response_mime_type.is_some()
+    {
+        google_request = google_request.with_generation_config(GenerationConfig {
+            stop_sequences: request.stop,
+            temperature: request.temperature,
+            max_output_tokens: request.max_tokens,
+            top_p: request.top_p,
+            top_k: request.top_k,
+            response_mime_type,
+            response_schema,
+        });
+    }
+    if let Some(tools) = request.tools {
+        google_request.tools = Some(vec![Tool {
+            function_declarations: Some(
+                tools
+                    .into_iter()
+                    .map(|tool| FunctionDeclaration {
+                        name: tool.name,
+                        description: tool.description,
+                        parameters: Some(tool.parameters),
+                    })
+                    .collect(),
+            ),
+        }]);
+    }
+    google_request.tool_config = request.tool_choice.map(|choice| {
+        let (mode, allowed_function_names) = match choice {
+            ToolChoice::Auto => ("AUTO", None),
+            ToolChoice::None => ("NONE", None),
+            ToolChoice::Required => ("ANY", None),
+            ToolChoice::Tool(name) => ("ANY", Some(vec![name])),
+        };
+        ToolConfig {
+            function_calling_config: FunctionCallingConfig {
+                mode: mode.to_string(),
+                allowed_function_names,
+            },
+        }
+    });
+    Ok(google_request)
+}
+
+/// Gemini expects a JSON object as the function response; other tool output is wrapped
+fn function_response(content: String) -> serde_json::Value {
+    match serde_json::from_str::<serde_json::Value>(&content) {
+        Ok(value @ serde_json::Value::Object(_)) => value,
+        Ok(value) => serde_json::json!({ "result": value }),
+        Err(_) => serde_json::json!({ "result": content }),
+    }
+}
+
+/// Random id for a function call, since Gemini does not assign call ids
+///
+/// Ids must stay unique across the turns of a conversation, so they cannot
+/// be derived from the part position.
+pub(crate) fn new_call_id() -> String {
+    format!("call_{:016x}", rand::random::<u64>())
+}
+
+/// Collect the function calls in a content block.
+fn content_tool_calls(content: &Content) -> Vec<ToolCall> {
+    content
+        .parts
+        .iter()
+        .filter_map(|part| match part {
+            Part::FunctionCall { function_call } => Some(function_call),
+            _ => None,
+        })
+        .map(|call| ToolCall {
+            id: new_call_id(),
+            name: call.name.clone(),
+            arguments: call.args.clone(),
+        })
+        .collect()
+}
+
+/// Concatenate the text parts of a content block
+fn content_text(content: &Content) -> String {
+    content
+        .parts
+        .iter()
+        .filter_map(|part| match part {
+            Part::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Convert a Gemini response into the provider-neutral response
+pub fn from_google_response(
+    response: GenerateContentResponse,
+    model: &str,
+) -> Result<ChatResponse, LlmError> {
+    let candidate = response.candidates.first().ok_or_else(|| {
+        LlmError::serialization(
+            "No candidates in Google response",
+            Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Empty candidates array",
+            )),
+        )
+    })?;
+
+    let finish_reason = candidate
+        .finish_reason
+        .as_ref()
+        .and_then(|reason| serde_json::to_value(reason).ok())
+        .and_then(|value| value.as_str().map(|s| s.to_string()));
+
+    let usage = response
+        .usage_metadata
+        .as_ref()
+        .map(|usage| TokenUsage {
+            prompt_tokens: usage.prompt_token_count,
+            completion_tokens: usage.candidates_token_count,
+            total_tokens: usage.total_token_count,
+        })
+        .unwrap_or(TokenUsage {
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            total_tokens: 0,
+        });
+
+    Ok(ChatResponse {
+        message: ChatMessage::assistant_tool_calls(
+            content_text(&candidate.content),
+            content_tool_calls(&candidate.content),
+        ),
+        model: model.to_string(),
+        usage,
+        finish_reason,
+        provider_metadata: None,
+    })
+}
+
+#[async_trait::async_trait]
+impl LlmProvider for GoogleClient {
+    fn name(&self) -> &'static str {
+        "google"
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        &["google", "gemini"]
+    }
+
+    fn default_base_url(&self) -> Option<&'static str> {
+        Some("https://generativelanguage.googleapis.com/v1beta")
+    }
+
+    fn env_key(&self) -> &'static str {
+        "GOOGLE_AI_API_KEY"
+    }
+
+    async fn available_models(&self) -> Result<Vec<String>, LlmError> {
+        GoogleClient::list_models(self).await
+    }
+
+    async fn health_check(&self) -> Result<(), LlmError> {
+        GoogleClient::health_check(self).await
+    }
+}
+
+#[async_trait::async_trait]
+impl ChatCompletion for GoogleClient {
+    async fn chat_completion(
+        &self,
+        request: ChatRequest,
+        model: &str,
+    ) -> Result<ChatResponse, LlmError> {
+        let response = self
+            .generate_content(model, to_google_request(request)?)
+            .await?;
+        from_google_response(response, model)
+    }
+
+    async fn chat_completion_stream(
+        &self,
+        request: ChatRequest,
+        model: &str,
+        config: Option<StreamConfig>,
+    ) -> StreamResult<ChatStreamEvent> {
+        let cancel = config.and_then(|config| config.cancel);
+        let request = match to_google_request(request) {
+            Ok(request) => request,
+            Err(e) => return Box::pin(futures::stream::once(async move { Err(e) })),
+        };
+        let stream = match unless_cancelled(
+            cancel.as_ref(),
+            self.stream_generate_content(model, request),
+        )
+        .await
+        {
+            Some(Ok(stream)) => stream,
+            Some(Err(e)) => return Box::pin(futures::stream::once(async move { Err(e) })),
+            None => return Box::pin(futures::stream::empty()),
+        };
+
+        cancel_with(chat_stream_events(google_events(stream)), cancel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compat_types::ChatRequestBuilder;
+
+    #[test]
+    fn test_to_google_request_routes_system_instruction() {
+        let request = ChatRequestBuilder::new()
+            .system("Be brief")
+            .user("Hi")
+            .assistant("Hello")
+            .temperature(0.2)
+            .build();
+
+        let google_request = to_google_request(request).unwrap();
+
+        assert_eq!(google_request.contents.len(), 2);
+        assert_eq!(google_request.contents[1].role.as_deref(), Some("model"));
+        let system = google_request.system_instruction.unwrap();
+        assert_eq!(content_text(&system), "Be brief");
+        assert_eq!(
+            google_request.generation_config.unwrap().temperature,
+            Some(0.2)
+        );
+    }
+
+    #[test]
+    fn test_from_google_response() {
+        let response: GenerateContentResponse = serde_json::from_value(serde_json::json!({
+            "candidates": [{
+                "content": {"role": "model", "parts": [{"text": "Hello"}, {"text": " there"}]},
+                "finishReason": "STOP"
+            }],
+            "usageMetadata": {"promptTokenCount": 4, "candidatesTokenCount": 2, "totalTokenCount": 6}
+        }))
+        .unwrap();
+
+        let response = from_google_response(response, "gemini-1.5-flash").unwrap();
+
+        assert_eq!(response.message.content, "Hello there");
+        assert_eq!(response.model, "gemini-1.5-flash");
+        assert_eq!(response.usage.total_tokens, 6);
+        assert_eq!(response.finish_reason, Some("STOP".to_string()));
+    }
+
+    #[test]
+    fn test_to_google_request_translates_tools() {
+        let call = ToolCall {
+            id: "call_0".to_string(),
+            name: "get_weather".to_string(),
+            arguments: serde_json::json!({"city": "Paris"}),
+        };
+        let request = ChatRequestBuilder::new()
+            .user("Weather in Paris?")
+            .message(ChatMessage::assistant_tool_calls("", vec![call]))
+            .tool_result("call_0", "sunny")
+            .tool(crate::compat_types::ToolDefinition::new(
+                "get_weather",
+                "Look up the weather",
+                serde_json::json!({"type": "object"}),
+            ))
+            .tool_choice(ToolChoice::Tool("get_weather".to_string()))
+            .build();
+
+        let body = serde_json::to_value(to_google_request(request).unwrap()).unwrap();
+
+        assert_eq!(
+            body["tools"][0]["functionDeclarations"][0]["name"],
+            "get_weather"
+        );
+        assert_eq!(body["toolConfig"]["functionCallingConfig"]["mode"], "ANY");
+        let contents = body["contents"].as_array().unwrap();
+        assert_eq!(contents[1]["role"], "model");
+        assert_eq!(
+            contents[1]["parts"][0]["function_call"]["args"]["city"],
+            "Paris"
+        );
+        assert_eq!(contents[2]["role"], "user");
+        let response = &contents[2]["parts"][0]["function_response"];
+        assert_eq!(response["name"], "get_weather");
+        assert_eq!(response["response"]["result"], "sunny");
+    }
+
+    #[test]
+    fn test_from_google_response_parses_function_calls() {
+        let response: GenerateContentResponse = serde_json::from_value(serde_json::json!({
+            "candidates": [{
+                "content": {"role": "model", "parts": [
+                    {"functionCall": {"name": "get_weather", "args": {"city": "Paris"}}},
+                    {"functionCall": {"name": "get_weather", "args": {"city": "Rome"}}}
+                ]},
+                "finishReason": "STOP"
+            }]
+        }))
+        .unwrap();
+
+        let first = from_google_response(response.clone(), "gemini-1.5-flash").unwrap();
+        let second = from_google_response(response, "gemini-1.5-flash").unwrap();
+
+        let calls = &first.message.tool_calls;
+        assert_eq!(calls.len(), 2);
+        assert!(calls[0].id.starts_with("call_"));
+        assert_ne!(calls[0].id, calls[1].id);
+        assert_ne!(calls[0].id, second.message.tool_calls[0].id);
+        assert_eq!(calls[1].arguments["city"], "Rome");
+    }
+
+    #[test]
+    fn test_from_google_response_allows_missing_token_counts() {
+        let response: GenerateContentResponse = serde_json::from_value(serde_json::json!({
+            "candidates": [{
+                "content": {"role": "model", "parts": [{"text": ""}]},
+                "finishReason": "SAFETY"
+            }],
+            "usageMetadata": {"promptTokenCount": 7, "totalTokenCount": 7}
+        }))
+        .unwrap();
+
+        let response = from_google_response(response, "gemini-1.5-flash").unwrap();
+
+        assert_eq!(response.usage.prompt_tokens, 7);
+        assert_eq!(response.usage.completion_tokens, 0);
+    }
+
+    #[test]
+    fn test_to_google_request_translates_content_parts() {
+        let request = ChatRequestBuilder::new()
+            .user_with_parts(
+                "Summarize",
+                vec![
+                    ContentPart::image_base64("image/png", "aGVsbG8="),
+                    ContentPart::pdf("JVBERi0="),
+                ],
+            )
+            .build();
+
+        let body = serde_json::to_value(to_google_request(request).unwrap()).unwrap();
+
+        let parts = &body["contents"][0]["parts"];
+        assert_eq!(parts[0]["text"], "Summarize");
+        assert_eq!(parts[1]["inline_data"]["mimeType"], "image/png");
+        assert_eq!(parts[2]["inline_data"]["mimeType"], "application/pdf");
+    }
+
+    #[test]
+    fn test_to_google_request_rejects_image_urls() {
+        let request = ChatRequestBuilder::new()
+            .user_with_parts(
+                "Look",
+                vec![ContentPart::image_url("https://example.com/cat.png")],
+            )
+            .build();
+
+        assert!(matches!(
+            to_google_request(request),
+            Err(LlmError::Validation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_to_google_request_translates_response_format() {
+        let request = ChatRequestBuilder::new()
+            .user("Extract")
+            .response_format(ResponseFormat::json_schema(
+                "invoice",
+                serde_json::json!({
+                    "$schema": "https://json-schema.org/draft/2020-12/schema",
+                    "type": "object",
+                    "properties": {"total": {"type": "number"}},
+                    "additionalProperties": false
+                }),
+            ))
+            .build();
+
+        let config = to_google_request(request)
+            .unwrap()
+            .generation_config
+            .unwrap();
+
+        assert_eq!(
+            config.response_mime_type.as_deref(),
+            Some("application/json")
+        );
+        assert_eq!(
+            config.response_schema,
+            Some(serde_json::json!({
+                "type": "object",
+                "properties": {"total": {"type": "number"}}
+            }))
+        );
+    }
+}
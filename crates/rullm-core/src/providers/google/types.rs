@@ -47,14 +47,25 @@ pub struct Content {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Part {
+    /// Thought summary from a thinking model (listed first so it wins over `Text`)
+    Thought { text: String, thought: bool },
     /// Text part
     Text { text: String },
     /// Inline data (image, etc.)
-    InlineData { inline_data: InlineData },
+    InlineData {
+        #[serde(alias = "inlineData")]
+        inline_data: InlineData,
+    },
     /// Function call
-    FunctionCall { function_call: FunctionCall },
+    FunctionCall {
+        #[serde(alias = "functionCall")]
+        function_call: FunctionCall,
+    },
     /// Function response
-    FunctionResponse { function_response: FunctionResponse },
+    FunctionResponse {
+        #[serde(alias = "functionResponse")]
+        function_response: FunctionResponse,
+    },
 }
 
 /// Inline data (base64-encoded image, etc.)
@@ -296,10 +307,13 @@ pub struct PromptFeedback {
 #[serde(rename_all = "camelCase")]
 pub struct UsageMetadata {
     /// Prompt token count
+    #[serde(default)]
     pub prompt_token_count: u32,
-    /// Candidates token count
+    /// Candidates token count, left out by Gemini when nothing was generated
+    #[serde(default)]
     pub candidates_token_count: u32,
     /// Total token count
+    #[serde(default)]
     pub total_token_count: u32,
 }
 
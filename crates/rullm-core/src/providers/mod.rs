@@ -9,3 +9,8 @@ pub use anthropic::AnthropicClient;
 pub use google::GoogleClient;
 pub use openai::OpenAIClient;
 pub use openai_compatible::{OpenAICompatibleProvider, ProviderIdentity, identities};
+
+/// Type aliases for backwards compatibility
+pub type OpenAIProvider = OpenAIClient;
+pub type AnthropicProvider = AnthropicClient;
+pub type GoogleProvider = GoogleClient;
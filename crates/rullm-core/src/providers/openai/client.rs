@@ -1,6 +1,7 @@
 use super::types::*;
-use crate::config::{OpenAIConfig, ProviderConfig};
+use crate::config::{OpenAIConfig, ProviderConfig, RetryPolicy};
 use crate::error::LlmError;
+use crate::utils::http::{client_for, send_with_retry, with_idle_timeout};
 use crate::utils::sse::sse_lines;
 use futures::Stream;
 use futures::StreamExt;
@@ -18,6 +19,12 @@ pub struct OpenAIClient {
 impl OpenAIClient {
     /// Create a new OpenAI client
     pub fn new(config: OpenAIConfig) -> Result<Self, LlmError> {
+        let client = client_for(&config)?;
+        Self::with_client(config, client)
+    }
+
+    /// Create a new OpenAI client using an existing HTTP client
+    pub fn with_client(config: OpenAIConfig, client: Client) -> Result<Self, LlmError> {
         config.validate()?;
         let base_url = config
             .base_url
@@ -26,7 +33,7 @@ impl OpenAIClient {
 
         Ok(Self {
             config,
-            client: Client::new(),
+            client,
             base_url,
         })
     }
@@ -44,29 +51,20 @@ impl OpenAIClient {
     ) -> Result<ChatCompletionResponse, LlmError> {
         let url = format!("{}/chat/completions", self.base_url);
 
-        let mut req = self.client.post(&url);
+        let mut req = self.client.post(&url).timeout(self.config.timeout());
 
         // Add headers from config
         for (key, value) in self.config.headers() {
             req = req.header(key, value);
         }
 
-        let response = req.json(&request).send().await?;
-
-        if !response.status().is_success() {
-            let status = response.status().to_string();
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-
-            return Err(LlmError::api(
-                "openai",
-                format!("API Error: {status} - {error_text}"),
-                Some(status),
-                None,
-            ));
-        }
+        let response = send_with_retry(
+            &self.config.retry_policy,
+            "openai",
+            req.json(&request),
+            self.config.timeout(),
+        )
+        .await?;
 
         let response_data: ChatCompletionResponse = response.json().await.map_err(|e| {
             LlmError::serialization("Failed to parse ChatCompletionResponse", Box::new(e))
@@ -81,8 +79,11 @@ impl OpenAIClient {
         mut request: ChatCompletionRequest,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<ChatCompletionChunk, LlmError>> + Send>>, LlmError>
     {
-        // Force streaming
+        // Force streaming, with a final usage chunk
         request.stream = Some(true);
+        request.stream_options.get_or_insert(StreamOptions {
+            include_usage: true,
+        });
 
         let url = format!("{}/chat/completions", self.base_url);
 
@@ -101,31 +102,17 @@ impl OpenAIClient {
             reqwest::header::HeaderValue::from_static("text/event-stream"),
         );
 
-        let response = self
-            .client
-            .post(&url)
-            .headers(header_map)
-            .json(&request)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let status = response.status().to_string();
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-
-            return Err(LlmError::api(
-                "openai",
-                format!("API Error: {status} - {error_text}"),
-                Some(status),
-                None,
-            ));
-        }
+        let response = send_with_retry(
+            &self.config.retry_policy,
+            "openai",
+            self.client.post(&url).headers(header_map).json(&request),
+            self.config.timeout(),
+        )
+        .await?;
 
         let byte_stream = response.bytes_stream();
-        let sse_stream = sse_lines(byte_stream);
+        let sse_stream =
+            with_idle_timeout(sse_lines(byte_stream), self.config.stream_idle_timeout());
 
         Ok(Box::pin(sse_stream.map(|event_result| {
             event_result.and_then(|data| {
@@ -150,21 +137,18 @@ impl OpenAIClient {
     pub async fn list_models(&self) -> Result<Vec<String>, LlmError> {
         let url = format!("{}/models", self.base_url);
 
-        let mut req = self.client.get(&url);
+        let mut req = self.client.get(&url).timeout(self.config.timeout());
         for (key, value) in self.config.headers() {
             req = req.header(key, value);
         }
 
-        let response = req.send().await?;
-
-        if !response.status().is_success() {
-            return Err(LlmError::api(
-                "openai",
-                "Failed to fetch available models",
-                Some(response.status().to_string()),
-                None,
-            ));
-        }
+        let response = send_with_retry(
+            &self.config.retry_policy,
+            "openai",
+            req,
+            self.config.timeout(),
+        )
+        .await?;
 
         let json: serde_json::Value = response
             .json()
@@ -197,22 +181,12 @@ impl OpenAIClient {
     pub async fn health_check(&self) -> Result<(), LlmError> {
         let url = format!("{}/models", self.base_url);
 
-        let mut req = self.client.get(&url);
+        let mut req = self.client.get(&url).timeout(self.config.timeout());
         for (key, value) in self.config.headers() {
             req = req.header(key, value);
         }
 
-        let response = req.send().await?;
-
-        if response.status().is_success() {
-            Ok(())
-        } else {
-            Err(LlmError::api(
-                "openai",
-                "Health check failed",
-                Some(response.status().to_string()),
-                None,
-            ))
-        }
+        send_with_retry(&RetryPolicy::none(), "openai", req, self.config.timeout()).await?;
+        Ok(())
     }
 }
@@ -20,12 +20,13 @@
 //! );
 //!
 //! let response = client.chat_completion(request).await?;
-//! println!("{}", response.choices[0].message.content.as_ref().unwrap());
+//! println!("{:?}", response.choices[0].message.content.as_ref().unwrap());
 //! # Ok(())
 //! # }
 //! ```
 
 pub mod client;
+pub(crate) mod provider;
 pub mod types;
 
 pub use client::OpenAIClient;
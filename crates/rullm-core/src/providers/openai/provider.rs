@@ -0,0 +1,479 @@
+//! [`ChatCompletion`] implementation for [`OpenAIClient`]
+
+use super::client::OpenAIClient;
+use super::types::{
+    ChatCompletionRequest, ChatCompletionResponse, ChatMessage, ContentPart, FileContent,
+    FunctionCall, FunctionChoice, FunctionDefinition, ImageUrl, JsonSchema, MessageContent,
+    ResponseFormat, Role, Tool, ToolCall, ToolChoice,
+};
+use crate::compat_types::{
+    self, ChatRequest, ChatResponse, ChatRole, ChatStreamEvent, TokenUsage, ToolDefinition,
+};
+use crate::error::LlmError;
+use crate::providers::openai_compatible::identities;
+use crate::stream::{cancel_with, chat_stream_events, openai_events, unless_cancelled};
+use crate::types::{ChatCompletion, LlmProvider, StreamConfig, StreamResult};
+
+/// Convert a provider-neutral content part into an OpenAI content part
+fn to_openai_part(part: compat_types::ContentPart) -> Result<ContentPart, LlmError> {
+    Ok(match part {
+        compat_types::ContentPart::Text { text } => ContentPart::Text { text },
+        compat_types::ContentPart::ImageUrl { url } => ContentPart::ImageUrl {
+            image_url: ImageUrl { url, detail: None },
+        },
+        compat_types::ContentPart::ImageBase64 { media_type, data } => ContentPart::ImageUrl {
+            image_url: ImageUrl {
+                url: format!("data:{media_type};base64,{data}"),
+                detail: None,
+            },
+        },
+        compat_types::ContentPart::Document { media_type, .. }
+            if media_type != "application/pdf" =>
+        {
+            return Err(LlmError::validation(format!(
+                "OpenAI does not accept {media_type} documents, only application/pdf"
+            )));
+        }
+        compat_types::ContentPart::Document {
+            media_type,
+            data,
+            name,
+        } => ContentPart::File {
+            file: FileContent {
+                filename: Some(name.unwrap_or_else(|| "document.pdf".to_string())),
+                file_data: Some(format!("data:{media_type};base64,{data}")),
+                file_id: None,
+            },
+        },
+    })
+}
+
+/// Convert a provider-neutral request into an OpenAI chat completion request
+///
+/// Images and documents are only accepted in user messages.
+pub fn to_openai_request(
+    request: ChatRequest,
+    model: &str,
+) -> Result<ChatCompletionRequest, LlmError> {
+    request.reject_sampling("OpenAI", &["top_k", "extra_params"])?;
+    let messages = request
+        .messages
+        .into_iter()
+        .map(|msg| {
+            if msg.has_media() && msg.role != ChatRole::User {
+                return Err(LlmError::validation(
+                    "OpenAI only accepts images and files in user messages",
+                ));
+            }
+
+            let tool_calls = (!msg.tool_calls.is_empty()).then(|| {
+                msg.tool_calls
+                    .into_iter()
+                    .map(|call| ToolCall {
+                        id: call.id,
+                        tool_type: "function".to_string(),
+                        function: FunctionCall {
+                            name: call.name,
+                            arguments: call.arguments.to_string(),
+                        },
+                    })
+                    .collect()
+            });
+            // Assistant messages that only call tools carry no content
+            let content = if !msg.parts.is_empty() {
+                let text =
+                    (!msg.content.is_empty()).then_some(ContentPart::Text { text: msg.content });
+                let parts = msg
+                    .parts
+                    .into_iter()
+                    .map(to_openai_part)
+                    .collect::<Result<Vec<_>, _>>()?;
+                Some(MessageContent::Parts(
+                    text.into_iter().chain(parts).collect(),
+                ))
+            } else if msg.content.is_empty() && tool_calls.is_some() {
+                None
+            } else {
+                Some(MessageContent::Text(msg.content))
+            };
+
+            Ok(ChatMessage {
+                role: match msg.role {
+                    ChatRole::System => Role::System,
+                    ChatRole::User => Role::User,
+                    ChatRole::Assistant => Role::Assistant,
+                    ChatRole::Tool => Role::Tool,
+                },
+                content,
+                name: None,
+                tool_calls,
+                tool_call_id: msg.tool_call_id,
+            })
+        })
+        .collect::<Result<_, _>>()?;
+
+    let mut openai_request = ChatCompletionRequest::new(model, messages);
+    openai_request.temperature = request.temperature;
+    openai_request.max_tokens = request.max_tokens;
+    openai_request.top_p = request.top_p;
+    openai_request.stop = request.stop;
+    openai_request.seed = request.seed;
+    openai_request.frequency_penalty = request.frequency_penalty;
+    openai_request.presence_penalty = request.presence_penalty;
+    openai_request.logit_bias = request.logit_bias;
+    openai_request.tools = request
+        .tools
+        .map(|tools| tools.into_iter().map(to_openai_tool).collect());
+    openai_request.tool_choice = request.tool_choice.map(|choice| match choice {
+        compat_types::ToolChoice::Auto => ToolChoice::Auto,
+        compat_types::ToolChoice::None => ToolChoice::None,
+        compat_types::ToolChoice::Required => ToolChoice::Required,
+        compat_types::ToolChoice::Tool(name) => ToolChoice::Specific {
+            tool_type: "function".to_string(),
+            function: FunctionChoice { name },
+        },
+    });
+    openai_request.response_format = request.response_format.map(|format| match format {
+        compat_types::ResponseFormat::Text => ResponseFormat::Text,
+        compat_types::ResponseFormat::Json => ResponseFormat::JsonObject,
+        compat_types::ResponseFormat::JsonSchema { name, schema } => ResponseFormat::JsonSchema {
+            json_schema: JsonSchema {
+                name,
+                description: None,
+                schema,
+                strict: None,
+            },
+        },
+    });
+    Ok(openai_request)
+}
+
+fn to_openai_tool(tool: ToolDefinition) -> Tool {
+    Tool {
+        tool_type: "function".to_string(),
+        function: FunctionDefinition {
+            name: tool.name,
+            description: Some(tool.description),
+            parameters: tool.parameters,
+        },
+    }
+}
+
+/// Convert an OpenAI tool call, parsing its JSON-encoded arguments
+fn from_openai_tool_call(call: ToolCall) -> compat_types::ToolCall {
+    let arguments = serde_json::from_str(&call.function.arguments)
+        .unwrap_or(serde_json::Value::String(call.function.arguments));
+    compat_types::ToolCall {
+        id: call.id,
+        name: call.function.name,
+        arguments,
+    }
+}
+
+/// Convert an OpenAI chat completion response into the provider-neutral response
+pub fn from_openai_response(response: ChatCompletionResponse) -> Result<ChatResponse, LlmError> {
+    let choice = response.choices.into_iter().next().ok_or_else(|| {
+        LlmError::serialization(
+            "No choices in OpenAI response",
+            Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Empty choices array",
+            )),
+        )
+    })?;
+
+    let content = match choice.message.content {
+        Some(MessageContent::Text(text)) => text,
+        Some(MessageContent::Parts(parts)) => parts
+            .into_iter()
+            .filter_map(|part| match part {
+                super::types::ContentPart::Text { text } => Some(text),
+                _ => None,
+            })
+            .collect(),
+        None => String::new(),
+    };
+    let tool_calls = choice
+        .message
+        .tool_calls
+        .unwrap_or_default()
+        .into_iter()
+        .map(from_openai_tool_call)
+        .collect();
+
+    Ok(ChatResponse {
+        message: compat_types::ChatMessage::assistant_tool_calls(content, tool_calls),
+        model: response.model,
+        usage: TokenUsage {
+            prompt_tokens: response.usage.prompt_tokens,
+            completion_tokens: response.usage.completion_tokens,
+            total_tokens: response.usage.total_tokens,
+        },
+        finish_reason: Some(choice.finish_reason),
+        provider_metadata: Some(
+            [("id".to_string(), serde_json::Value::String(response.id))]
+                .into_iter()
+                .collect(),
+        ),
+    })
+}
+
+#[async_trait::async_trait]
+impl LlmProvider for OpenAIClient {
+    fn name(&self) -> &'static str {
+        identities::OPENAI.name
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        identities::OPENAI.aliases
+    }
+
+    fn default_base_url(&self) -> Option<&'static str> {
+        Some(identities::OPENAI.default_base_url)
+    }
+
+    fn env_key(&self) -> &'static str {
+        identities::OPENAI.env_key
+    }
+
+    async fn available_models(&self) -> Result<Vec<String>, LlmError> {
+        OpenAIClient::list_models(self).await
+    }
+
+    async fn health_check(&self) -> Result<(), LlmError> {
+        OpenAIClient::health_check(self).await
+    }
+}
+
+#[async_trait::async_trait]
+impl ChatCompletion for OpenAIClient {
+    async fn chat_completion(
+        &self,
+        request: ChatRequest,
+        model: &str,
+    ) -> Result<ChatResponse, LlmError> {
+        let response =
+            OpenAIClient::chat_completion(self, to_openai_request(request, model)?).await?;
+        from_openai_response(response)
+    }
+
+    async fn chat_completion_stream(
+        &self,
+        request: ChatRequest,
+        model: &str,
+        config: Option<StreamConfig>,
+    ) -> StreamResult<ChatStreamEvent> {
+        let cancel = config.and_then(|config| config.cancel);
+        let request = match to_openai_request(request, model) {
+            Ok(request) => request,
+            Err(e) => return Box::pin(futures::stream::once(async move { Err(e) })),
+        };
+        let stream = match unless_cancelled(
+            cancel.as_ref(),
+            OpenAIClient::chat_completion_stream(self, request),
+        )
+        .await
+        {
+            Some(Ok(stream)) => stream,
+            Some(Err(e)) => return Box::pin(futures::stream::once(async move { Err(e) })),
+            None => return Box::pin(futures::stream::empty()),
+        };
+
+        cancel_with(chat_stream_events(openai_events(stream)), cancel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_openai_request_maps_roles_and_sampling() {
+        let request = compat_types::ChatRequestBuilder::new()
+            .system("Be brief")
+            .user("Hi")
+            .temperature(0.5)
+            .max_tokens(42)
+            .build();
+
+        let openai_request = to_openai_request(request, "gpt-4o").unwrap();
+
+        assert_eq!(openai_request.model, "gpt-4o");
+        assert_eq!(openai_request.messages.len(), 2);
+        assert_eq!(openai_request.messages[0].role, Role::System);
+        assert_eq!(openai_request.messages[1].role, Role::User);
+        assert_eq!(openai_request.temperature, Some(0.5));
+        assert_eq!(openai_request.max_tokens, Some(42));
+    }
+
+    #[test]
+    fn test_from_openai_response() {
+        let response: ChatCompletionResponse = serde_json::from_value(serde_json::json!({
+            "id": "chatcmpl-123",
+            "object": "chat.completion",
+            "created": 1,
+            "model": "gpt-4o",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "Hello!"},
+                "finish_reason": "stop"
+            }],
+            "usage": {"prompt_tokens": 3, "completion_tokens": 2, "total_tokens": 5}
+        }))
+        .unwrap();
+
+        let response = from_openai_response(response).unwrap();
+
+        assert_eq!(response.message.role, ChatRole::Assistant);
+        assert_eq!(response.message.content, "Hello!");
+        assert_eq!(response.model, "gpt-4o");
+        assert_eq!(response.usage.total_tokens, 5);
+        assert_eq!(response.finish_reason, Some("stop".to_string()));
+    }
+
+    #[test]
+    fn test_to_openai_request_translates_tools() {
+        let call = compat_types::ToolCall {
+            id: "call_1".to_string(),
+            name: "get_weather".to_string(),
+            arguments: serde_json::json!({"city": "Paris"}),
+        };
+        let request = compat_types::ChatRequestBuilder::new()
+            .user("Weather in Paris?")
+            .message(compat_types::ChatMessage::assistant_tool_calls(
+                "",
+                vec![call],
+            ))
+            .tool_result("call_1", "sunny")
+            .tool(ToolDefinition::new(
+                "get_weather",
+                "Look up the weather",
+                serde_json::json!({"type": "object"}),
+            ))
+            .tool_choice(compat_types::ToolChoice::Tool("get_weather".to_string()))
+            .build();
+
+        let body = serde_json::to_value(to_openai_request(request, "gpt-4o").unwrap()).unwrap();
+
+        assert_eq!(body["tools"][0]["type"], "function");
+        assert_eq!(body["tools"][0]["function"]["name"], "get_weather");
+        assert_eq!(body["tool_choice"]["function"]["name"], "get_weather");
+        assert!(body["messages"][1].get("content").is_none());
+        assert_eq!(
+            body["messages"][1]["tool_calls"][0]["function"]["arguments"],
+            r#"{"city":"Paris"}"#
+        );
+        assert_eq!(body["messages"][2]["role"], "tool");
+        assert_eq!(body["messages"][2]["tool_call_id"], "call_1");
+    }
+
+    #[test]
+    fn test_tool_choice_serializes_modes_as_strings() {
+        assert_eq!(
+            serde_json::to_value(ToolChoice::Auto).unwrap(),
+            serde_json::json!("auto")
+        );
+        let choice: ToolChoice = serde_json::from_value(serde_json::json!("required")).unwrap();
+        assert!(matches!(choice, ToolChoice::Required));
+    }
+
+    #[test]
+    fn test_from_openai_response_parses_tool_calls() {
+        let response: ChatCompletionResponse = serde_json::from_value(serde_json::json!({
+            "id": "chatcmpl-123",
+            "object": "chat.completion",
+            "created": 1,
+            "model": "gpt-4o",
+            "choices": [{
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [{
+                        "id": "call_1",
+                        "type": "function",
+                        "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}
+                    }]
+                },
+                "finish_reason": "tool_calls"
+            }],
+            "usage": {"prompt_tokens": 3, "completion_tokens": 2, "total_tokens": 5}
+        }))
+        .unwrap();
+
+        let response = from_openai_response(response).unwrap();
+
+        assert_eq!(response.message.content, "");
+        assert_eq!(response.message.tool_calls.len(), 1);
+        assert_eq!(response.message.tool_calls[0].id, "call_1");
+        assert_eq!(
+            response.message.tool_calls[0].arguments,
+            serde_json::json!({"city": "Paris"})
+        );
+        assert_eq!(response.finish_reason, Some("tool_calls".to_string()));
+    }
+
+    #[test]
+    fn test_to_openai_request_translates_content_parts() {
+        let request = compat_types::ChatRequestBuilder::new()
+            .user_with_parts(
+                "Describe these",
+                vec![
+                    compat_types::ContentPart::image_url("https://example.com/cat.png"),
+                    compat_types::ContentPart::image_base64("image/png", "aGVsbG8="),
+                    compat_types::ContentPart::pdf("JVBERi0="),
+                ],
+            )
+            .build();
+
+        let body = serde_json::to_value(to_openai_request(request, "gpt-4o").unwrap()).unwrap();
+
+        let content = &body["messages"][0]["content"];
+        assert_eq!(content[0]["type"], "text");
+        assert_eq!(
+            content[1]["image_url"]["url"],
+            "https://example.com/cat.png"
+        );
+        assert_eq!(
+            content[2]["image_url"]["url"],
+            "data:image/png;base64,aGVsbG8="
+        );
+        assert_eq!(content[3]["type"], "file");
+        assert_eq!(
+            content[3]["file"]["file_data"],
+            "data:application/pdf;base64,JVBERi0="
+        );
+    }
+
+    #[test]
+    fn test_to_openai_request_rejects_media_outside_user_messages() {
+        let request = compat_types::ChatRequestBuilder::new()
+            .message(compat_types::ChatMessage::assistant("Here").with_part(
+                compat_types::ContentPart::image_url("https://example.com/a.png"),
+            ))
+            .build();
+
+        assert!(matches!(
+            to_openai_request(request, "gpt-4o"),
+            Err(LlmError::Validation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_to_openai_request_translates_response_format() {
+        let schema = serde_json::json!({"type": "object"});
+        let request = compat_types::ChatRequestBuilder::new()
+            .user("Extract")
+            .response_format(compat_types::ResponseFormat::json_schema(
+                "invoice",
+                schema.clone(),
+            ))
+            .build();
+
+        let body = serde_json::to_value(to_openai_request(request, "gpt-4o").unwrap()).unwrap();
+
+        assert_eq!(body["response_format"]["type"], "json_schema");
+        assert_eq!(body["response_format"]["json_schema"]["name"], "invoice");
+        assert_eq!(body["response_format"]["json_schema"]["schema"], schema);
+    }
+}
@@ -82,6 +82,17 @@ pub struct ChatCompletionRequest {
     /// Whether to enable parallel function calling
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parallel_tool_calls: Option<bool>,
+
+    /// Options for streaming responses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<StreamOptions>,
+}
+
+/// Options for streaming responses
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamOptions {
+    /// Send a final chunk with token usage for the whole request
+    pub include_usage: bool,
 }
 
 /// A message in the conversation
@@ -125,6 +136,22 @@ pub enum ContentPart {
     Text { text: String },
     /// Image URL content part
     ImageUrl { image_url: ImageUrl },
+    /// File content part (e.g. a PDF)
+    File { file: FileContent },
+}
+
+/// File sent inline or by uploaded file id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileContent {
+    /// Name of the file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    /// Base64 data URL of the file contents
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_data: Option<String>,
+    /// ID of a file uploaded through the Files API
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_id: Option<String>,
 }
 
 /// Image URL with optional detail level
@@ -213,8 +240,9 @@ pub struct FunctionDefinition {
 }
 
 /// Controls which function the model calls
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(untagged)]
+///
+/// Serialized as `"auto"`, `"required"`, `"none"` or a `{"type": "function", ...}` object.
+#[derive(Debug, Clone)]
 pub enum ToolChoice {
     /// Let the model decide
     Auto,
@@ -224,12 +252,70 @@ pub enum ToolChoice {
     None,
     /// Force a specific function
     Specific {
-        #[serde(rename = "type")]
         tool_type: String,
         function: FunctionChoice,
     },
 }
 
+impl Serialize for ToolChoice {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Specific<'a> {
+            #[serde(rename = "type")]
+            tool_type: &'a str,
+            function: &'a FunctionChoice,
+        }
+
+        match self {
+            Self::Auto => serializer.serialize_str("auto"),
+            Self::Required => serializer.serialize_str("required"),
+            Self::None => serializer.serialize_str("none"),
+            Self::Specific {
+                tool_type,
+                function,
+            } => Specific {
+                tool_type,
+                function,
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ToolChoice {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Mode(String),
+            Specific {
+                #[serde(rename = "type")]
+                tool_type: String,
+                function: FunctionChoice,
+            },
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Mode(mode) => match mode.as_str() {
+                "auto" => Ok(Self::Auto),
+                "required" => Ok(Self::Required),
+                "none" => Ok(Self::None),
+                other => Err(serde::de::Error::unknown_variant(
+                    other,
+                    &["auto", "required", "none"],
+                )),
+            },
+            Repr::Specific {
+                tool_type,
+                function,
+            } => Ok(Self::Specific {
+                tool_type,
+                function,
+            }),
+        }
+    }
+}
+
 /// Specific function to call
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FunctionChoice {
@@ -360,24 +446,32 @@ pub struct CompletionTokensDetails {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatCompletionChunk {
     /// Unique identifier for the chunk
+    #[serde(default)]
     pub id: String,
     /// Object type (always "chat.completion.chunk")
+    #[serde(default)]
     pub object: String,
     /// Unix timestamp
+    #[serde(default)]
     pub created: u64,
     /// Model used
+    #[serde(default)]
     pub model: String,
     /// System fingerprint
     #[serde(skip_serializing_if = "Option::is_none")]
     pub system_fingerprint: Option<String>,
     /// List of delta choices
     pub choices: Vec<ChunkChoice>,
+    /// Token usage (final chunk only, when requested with `stream_options`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
 }
 
 /// A choice in a streaming chunk
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChunkChoice {
     /// Index of the choice
+    #[serde(default)]
     pub index: u32,
     /// Delta content (incremental changes)
     pub delta: Delta,
@@ -398,6 +492,9 @@ pub struct Delta {
     /// Content delta
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
+    /// Reasoning delta, sent by reasoning models on some OpenAI-compatible APIs
+    #[serde(default, alias = "reasoning", skip_serializing_if = "Option::is_none")]
+    pub reasoning_content: Option<String>,
     /// Tool calls delta
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_calls: Option<Vec<ToolCallDelta>>,
@@ -454,6 +551,7 @@ impl ChatCompletionRequest {
             tools: None,
             tool_choice: None,
             parallel_tool_calls: None,
+            stream_options: None,
         }
     }
 }
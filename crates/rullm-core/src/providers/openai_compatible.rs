@@ -1,12 +1,20 @@
 use crate::compat_types::{
-    ChatMessage, ChatRequest, ChatResponse, ChatRole, ChatStreamEvent, TokenUsage,
+    ChatMessage, ChatRequest, ChatResponse, ChatRole, ChatStreamEvent, ContentPart, ResponseFormat,
+    TokenUsage, ToolCall, ToolChoice,
 };
-use crate::config::ProviderConfig;
+use crate::config::{ProviderConfig, RetryPolicy};
 use crate::error::LlmError;
+use crate::providers::openai::ChatCompletionChunk;
+use crate::stream::{cancel_with, chat_stream_events, openai_events};
+use crate::types::{ChatCompletion, LlmProvider, StreamConfig, StreamResult};
+use crate::utils::http::{client_for, send_with_retry, with_idle_timeout};
 use crate::utils::sse::sse_lines;
 use futures::StreamExt;
+use once_cell::sync::Lazy;
 use reqwest::Client;
+use std::collections::HashSet;
 use std::pin::Pin;
+use std::sync::Mutex;
 
 /// Provider identity metadata
 #[derive(Debug, Clone)]
@@ -17,6 +25,40 @@ pub struct ProviderIdentity {
     pub default_base_url: &'static str,
 }
 
+impl ProviderIdentity {
+    /// Identity for a user-defined endpoint such as a vLLM or LM Studio server
+    ///
+    /// `env_key` is `None` for servers that need no API key. The strings are
+    /// interned to give them the `'static` lifetime [`LlmProvider`] expects,
+    /// so rebuilding the same identity does not allocate again.
+    pub fn custom(
+        name: impl Into<String>,
+        env_key: Option<String>,
+        base_url: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: intern(name.into()),
+            aliases: &[],
+            env_key: env_key.map_or("", intern),
+            default_base_url: intern(base_url.into()),
+        }
+    }
+}
+
+/// Strings leaked for custom identities, each kept once
+static INTERNED: Lazy<Mutex<HashSet<&'static str>>> = Lazy::new(Mutex::default);
+
+/// Return the `'static` copy of `value`, leaking it only the first time it is seen
+fn intern(value: String) -> &'static str {
+    let mut interned = INTERNED.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(existing) = interned.get(value.as_str()) {
+        return existing;
+    }
+    let leaked: &'static str = Box::leak(value.into_boxed_str());
+    interned.insert(leaked);
+    leaked
+}
+
 /// Predefined provider identities for OpenAI-compatible APIs
 pub mod identities {
     use super::ProviderIdentity;
@@ -43,6 +85,193 @@ pub mod identities {
     };
 }
 
+/// JSON number for a sampling option, which must be finite
+fn finite_number(key: &str, value: f32) -> Result<serde_json::Value, LlmError> {
+    serde_json::Number::from_f64(value as f64)
+        .map(serde_json::Value::Number)
+        .ok_or_else(|| LlmError::validation(format!("{key} must be a finite number")))
+}
+
+/// Convert a provider-neutral request into the JSON body of an
+/// OpenAI-compatible `/chat/completions` call
+///
+/// `provider` names the endpoint in error messages.
+pub fn to_compatible_request(
+    request: &ChatRequest,
+    model: &str,
+    provider: &str,
+) -> Result<serde_json::Value, LlmError> {
+    let messages = request
+        .messages
+        .iter()
+        .map(|msg| to_openai_message(msg, provider))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut openai_request = serde_json::json!({
+        "model": model,
+        "messages": messages
+    });
+
+    if let Some(temp) = request.temperature {
+        openai_request["temperature"] = finite_number("temperature", temp)?;
+    }
+
+    if let Some(max_tokens) = request.max_tokens {
+        openai_request["max_tokens"] =
+            serde_json::Value::Number(serde_json::Number::from(max_tokens));
+    }
+
+    if let Some(top_p) = request.top_p {
+        openai_request["top_p"] = finite_number("top_p", top_p)?;
+    }
+
+    // Servers that do not know an option typically ignore it
+    let sampling = [
+        ("top_k", serde_json::json!(request.top_k)),
+        ("stop", serde_json::json!(request.stop)),
+        ("seed", serde_json::json!(request.seed)),
+        (
+            "frequency_penalty",
+            serde_json::json!(request.frequency_penalty),
+        ),
+        (
+            "presence_penalty",
+            serde_json::json!(request.presence_penalty),
+        ),
+        ("logit_bias", serde_json::json!(request.logit_bias)),
+    ];
+    for (key, value) in sampling {
+        if !value.is_null() {
+            openai_request[key] = value;
+        }
+    }
+
+    if let Some(stream) = request.stream {
+        openai_request["stream"] = serde_json::Value::Bool(stream);
+        if stream {
+            openai_request["stream_options"] = serde_json::json!({"include_usage": true});
+        }
+    }
+
+    if let Some(tools) = &request.tools {
+        openai_request["tools"] = tools
+            .iter()
+            .map(|tool| {
+                serde_json::json!({
+                    "type": "function",
+                    "function": {
+                        "name": tool.name,
+                        "description": tool.description,
+                        "parameters": tool.parameters
+                    }
+                })
+            })
+            .collect();
+    }
+
+    if let Some(tool_choice) = &request.tool_choice {
+        openai_request["tool_choice"] = match tool_choice {
+            ToolChoice::Auto => serde_json::json!("auto"),
+            ToolChoice::None => serde_json::json!("none"),
+            ToolChoice::Required => serde_json::json!("required"),
+            ToolChoice::Tool(name) => {
+                serde_json::json!({"type": "function", "function": {"name": name}})
+            }
+        };
+    }
+
+    if let Some(response_format) = &request.response_format {
+        openai_request["response_format"] = match response_format {
+            ResponseFormat::Text => serde_json::json!({"type": "text"}),
+            ResponseFormat::Json => serde_json::json!({"type": "json_object"}),
+            ResponseFormat::JsonSchema { name, schema } => serde_json::json!({
+                "type": "json_schema",
+                "json_schema": {"name": name, "schema": schema}
+            }),
+        };
+    }
+
+    if let Some(extra_params) = &request.extra_params {
+        for (key, value) in extra_params {
+            openai_request[key.as_str()] = value.clone();
+        }
+    }
+
+    Ok(openai_request)
+}
+
+fn to_openai_message(msg: &ChatMessage, provider: &str) -> Result<serde_json::Value, LlmError> {
+    if msg.has_media() && msg.role != ChatRole::User {
+        return Err(LlmError::validation(format!(
+            "{provider} only accepts images and files in user messages"
+        )));
+    }
+
+    let mut message = serde_json::json!({
+        "role": msg.role,
+        "content": msg.content
+    });
+
+    if !msg.parts.is_empty() {
+        let text = (!msg.content.is_empty())
+            .then(|| serde_json::json!({"type": "text", "text": msg.content}));
+        message["content"] = text
+            .into_iter()
+            .chain(msg.parts.iter().map(to_openai_part))
+            .collect();
+    }
+
+    if !msg.tool_calls.is_empty() {
+        // Assistant messages that only call tools carry no content
+        if msg.content.is_empty() {
+            message["content"] = serde_json::Value::Null;
+        }
+        message["tool_calls"] = msg
+            .tool_calls
+            .iter()
+            .map(|call| {
+                serde_json::json!({
+                    "id": call.id,
+                    "type": "function",
+                    "function": {
+                        "name": call.name,
+                        "arguments": call.arguments.to_string()
+                    }
+                })
+            })
+            .collect();
+    }
+
+    if let Some(tool_call_id) = &msg.tool_call_id {
+        message["tool_call_id"] = serde_json::Value::String(tool_call_id.clone());
+    }
+
+    Ok(message)
+}
+
+fn to_openai_part(part: &ContentPart) -> serde_json::Value {
+    match part {
+        ContentPart::Text { text } => serde_json::json!({"type": "text", "text": text}),
+        ContentPart::ImageUrl { url } => {
+            serde_json::json!({"type": "image_url", "image_url": {"url": url}})
+        }
+        ContentPart::ImageBase64 { media_type, data } => serde_json::json!({
+            "type": "image_url",
+            "image_url": {"url": format!("data:{media_type};base64,{data}")}
+        }),
+        ContentPart::Document {
+            media_type,
+            data,
+            name,
+        } => serde_json::json!({
+            "type": "file",
+            "file": {
+                "filename": name.as_deref().unwrap_or("document.pdf"),
+                "file_data": format!("data:{media_type};base64,{data}")
+            }
+        }),
+    }
+}
+
 /// Generic OpenAI-compatible provider implementation
 #[derive(Clone)]
 pub struct OpenAICompatibleProvider {
@@ -56,9 +285,18 @@ impl OpenAICompatibleProvider {
     pub fn new(
         config: crate::config::OpenAICompatibleConfig,
         identity: ProviderIdentity,
+    ) -> Result<Self, LlmError> {
+        let client = client_for(&config)?;
+        Self::with_client(config, identity, client)
+    }
+
+    /// Create a new OpenAI-compatible provider using an existing HTTP client
+    pub fn with_client(
+        config: crate::config::OpenAICompatibleConfig,
+        identity: ProviderIdentity,
+        client: Client,
     ) -> Result<Self, LlmError> {
         config.validate()?;
-        let client = Client::new();
         Ok(Self {
             config,
             client,
@@ -81,36 +319,31 @@ impl OpenAICompatibleProvider {
         Self::new(config, identities::OPENROUTER)
     }
 
-    /// Convert our ChatRequest to OpenAI's API format
-    fn to_openai_request(&self, request: &ChatRequest, model: &str) -> serde_json::Value {
-        let mut openai_request = serde_json::json!({
-            "model": model,
-            "messages": request.messages.iter().map(|msg| serde_json::json!({
-                "role": msg.role,
-                "content": msg.content
-            })).collect::<Vec<_>>()
-        });
-
-        if let Some(temp) = request.temperature {
-            openai_request["temperature"] =
-                serde_json::Value::Number(serde_json::Number::from_f64(temp as f64).unwrap());
-        }
-
-        if let Some(max_tokens) = request.max_tokens {
-            openai_request["max_tokens"] =
-                serde_json::Value::Number(serde_json::Number::from(max_tokens));
-        }
-
-        if let Some(top_p) = request.top_p {
-            openai_request["top_p"] =
-                serde_json::Value::Number(serde_json::Number::from_f64(top_p as f64).unwrap());
-        }
-
-        if let Some(stream) = request.stream {
-            openai_request["stream"] = serde_json::Value::Bool(stream);
-        }
+    /// The configured base URL, falling back to the identity's default
+    fn base_url(&self) -> &str {
+        self.config
+            .base_url
+            .as_deref()
+            .unwrap_or(self.identity.default_base_url)
+    }
 
-        openai_request
+    /// Parse the `tool_calls` of a response message, decoding JSON-encoded arguments
+    fn parse_tool_calls(message: &serde_json::Value) -> Vec<ToolCall> {
+        message["tool_calls"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|call| {
+                let function = &call["function"];
+                let arguments = function["arguments"].as_str().unwrap_or("{}");
+                Some(ToolCall {
+                    id: call["id"].as_str()?.to_string(),
+                    name: function["name"].as_str()?.to_string(),
+                    arguments: serde_json::from_str(arguments)
+                        .unwrap_or_else(|_| serde_json::Value::String(arguments.to_string())),
+                })
+            })
+            .collect()
     }
 
     /// Parse OpenAI's response format into our ChatResponse
@@ -136,15 +369,20 @@ impl OpenAICompatibleProvider {
         })?;
 
         let message = &first_choice["message"];
-        let content = message["content"].as_str().ok_or_else(|| {
-            LlmError::serialization(
-                "Missing content in OpenAI response",
-                Box::new(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    "Missing content field",
-                )),
-            )
-        })?;
+        let tool_calls = Self::parse_tool_calls(message);
+        let content = match message["content"].as_str() {
+            Some(content) => content,
+            None if !tool_calls.is_empty() => "",
+            None => {
+                return Err(LlmError::serialization(
+                    "Missing content in OpenAI response",
+                    Box::new(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "Missing content field",
+                    )),
+                ));
+            }
+        };
 
         let role = message["role"].as_str().ok_or_else(|| {
             LlmError::serialization(
@@ -185,35 +423,37 @@ impl OpenAICompatibleProvider {
             .as_str()
             .map(|s| s.to_string());
 
+        let provider_metadata = response
+            .get("id")
+            .map(|id| [("id".to_string(), id.clone())].into_iter().collect());
+
         Ok(ChatResponse {
             message: ChatMessage {
-                role: parsed_role,
-                content: content.to_string(),
+                tool_calls,
+                ..ChatMessage::new(parsed_role, content)
             },
             model,
             usage: token_usage,
             finish_reason,
+            provider_metadata,
         })
     }
 
     /// Get list of available models
     pub async fn available_models(&self) -> Result<Vec<String>, LlmError> {
-        let url = format!("{}/models", self.config.base_url());
+        let url = format!("{}/models", self.base_url());
 
-        let mut req = self.client.get(&url);
+        let mut req = self.client.get(&url).timeout(self.config.timeout());
         for (key, value) in self.config.headers() {
             req = req.header(&key, &value);
         }
-        let resp = req.send().await?;
-
-        if !resp.status().is_success() {
-            return Err(LlmError::api(
-                self.identity.name,
-                "Failed to fetch available models",
-                Some(resp.status().to_string()),
-                None,
-            ));
-        }
+        let resp = send_with_retry(
+            &self.config.retry_policy,
+            self.identity.name,
+            req,
+            self.config.timeout(),
+        )
+        .await?;
 
         let json: serde_json::Value = resp
             .json()
@@ -253,24 +493,20 @@ impl OpenAICompatibleProvider {
 
     /// Health check
     pub async fn health_check(&self) -> Result<(), LlmError> {
-        let url = format!("{}/models", self.config.base_url());
+        let url = format!("{}/models", self.base_url());
 
-        let mut req = self.client.get(&url);
+        let mut req = self.client.get(&url).timeout(self.config.timeout());
         for (key, value) in self.config.headers() {
             req = req.header(&key, &value);
         }
-        let response = req.send().await?;
-
-        if response.status().is_success() {
-            Ok(())
-        } else {
-            Err(LlmError::api(
-                self.identity.name,
-                "Health check failed",
-                Some(response.status().to_string()),
-                None,
-            ))
-        }
+        send_with_retry(
+            &RetryPolicy::none(),
+            self.identity.name,
+            req,
+            self.config.timeout(),
+        )
+        .await?;
+        Ok(())
     }
 
     /// Chat completion
@@ -279,29 +515,20 @@ impl OpenAICompatibleProvider {
         request: ChatRequest,
         model: &str,
     ) -> Result<ChatResponse, LlmError> {
-        let url = format!("{}/chat/completions", self.config.base_url());
-        let body = self.to_openai_request(&request, model);
+        let url = format!("{}/chat/completions", self.base_url());
+        let body = to_compatible_request(&request, model, self.identity.name)?;
 
-        let mut req = self.client.post(&url);
+        let mut req = self.client.post(&url).timeout(self.config.timeout());
         for (key, value) in self.config.headers() {
             req = req.header(&key, &value);
         }
-        let response = req.json(&body).send().await?;
-
-        if !response.status().is_success() {
-            let status = response.status().to_string();
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-
-            return Err(LlmError::api(
-                self.identity.name,
-                format!("API Error: {status} - {error_text}"),
-                Some(status),
-                None,
-            ));
-        }
+        let response = send_with_retry(
+            &self.config.retry_policy,
+            self.identity.name,
+            req.json(&body),
+            self.config.timeout(),
+        )
+        .await?;
 
         let response_json: serde_json::Value = response
             .json()
@@ -318,15 +545,16 @@ impl OpenAICompatibleProvider {
         model: &str,
         _buffer_size: Option<usize>,
     ) -> Pin<Box<dyn futures::Stream<Item = Result<ChatStreamEvent, LlmError>> + Send>> {
-        let url = format!("{}/chat/completions", self.config.base_url());
+        let url = format!("{}/chat/completions", self.base_url());
 
         // Create streaming request with stream: true
         let mut streaming_request = request.clone();
         streaming_request.stream = Some(true);
-        let body = self.to_openai_request(&streaming_request, model);
+        let body = match to_compatible_request(&streaming_request, model, self.identity.name) {
+            Ok(body) => body,
+            Err(e) => return Box::pin(futures::stream::once(async move { Err(e) })),
+        };
 
-        // Make the streaming HTTP request using reqwest Client directly
-        let client = reqwest::Client::new();
         let headers = self.config.headers();
 
         // Convert HashMap to HeaderMap
@@ -344,75 +572,87 @@ impl OpenAICompatibleProvider {
             reqwest::header::HeaderValue::from_static("text/event-stream"),
         );
 
-        let response_future = client.post(&url).headers(header_map).json(&body).send();
+        let request = self.client.post(&url).headers(header_map).json(&body);
+        let retry_policy = self.config.retry_policy.clone();
+        let timeout = self.config.timeout();
+        let idle_timeout = self.config.stream_idle_timeout();
         let provider_name = self.identity.name;
 
-        Box::pin(async_stream::stream! {
-            // Handle the initial request
-            let response = match response_future.await {
-                Ok(resp) => {
-                    if !resp.status().is_success() {
-                        let status = resp.status().to_string();
-                        let error_text = resp
-                            .text()
-                            .await
-                            .unwrap_or_else(|_| "Unknown error".to_string());
-                        yield Err(LlmError::api(
-                            provider_name,
-                            format!("API Error: {status} - {error_text}"),
-                            Some(status),
-                            None,
-                        ));
-                        return;
-                    }
-                    resp
-                }
-                Err(e) => {
-                    yield Err(LlmError::network(format!("Request failed: {e}")));
-                    return;
-                }
-            };
-
-            // Get the byte stream and parse SSE events
-            let byte_stream = response.bytes_stream();
-            let mut sse_stream = sse_lines(byte_stream);
-
-            while let Some(event_result) = sse_stream.next().await {
-                match event_result {
-                    Ok(data) => {
-                        // Parse the JSON chunk
-                        match serde_json::from_str::<serde_json::Value>(&data) {
-                            Ok(chunk) => {
-                                // Extract content from choices[0].delta.content
-                                if let Some(choices) = chunk["choices"].as_array() {
-                                    if let Some(first_choice) = choices.first() {
-                                        if let Some(delta) = first_choice.get("delta") {
-                                            if let Some(content) = delta["content"].as_str() {
-                                                yield Ok(ChatStreamEvent::Token(content.to_string()));
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                yield Err(LlmError::serialization(
-                                    format!("Failed to parse chunk JSON: {e}"),
-                                    Box::new(e),
-                                ));
-                                return;
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        yield Err(e);
-                        return;
-                    }
-                }
+        let response = futures::stream::once(async move {
+            send_with_retry(&retry_policy, provider_name, request, timeout).await
+        });
+        Box::pin(response.flat_map(move |response| match response {
+            Ok(response) => {
+                let sse_stream =
+                    with_idle_timeout(sse_lines(response.bytes_stream()), idle_timeout);
+                chat_stream_events(openai_events(sse_stream.map(|event_result| {
+                    event_result.and_then(|data| {
+                        serde_json::from_str::<ChatCompletionChunk>(&data).map_err(|e| {
+                            LlmError::serialization(
+                                format!("Failed to parse chunk JSON: {e}"),
+                                Box::new(e),
+                            )
+                        })
+                    })
+                })))
             }
+            Err(e) => Box::pin(futures::stream::once(async move { Err(e) })),
+        }))
+    }
+}
 
-            // Emit Done event when streaming completes
-            yield Ok(ChatStreamEvent::Done);
-        })
+#[async_trait::async_trait]
+impl LlmProvider for OpenAICompatibleProvider {
+    fn name(&self) -> &'static str {
+        self.identity.name
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        self.identity.aliases
+    }
+
+    fn default_base_url(&self) -> Option<&'static str> {
+        Some(self.identity.default_base_url)
+    }
+
+    fn env_key(&self) -> &'static str {
+        self.identity.env_key
+    }
+
+    async fn available_models(&self) -> Result<Vec<String>, LlmError> {
+        OpenAICompatibleProvider::available_models(self).await
+    }
+
+    async fn health_check(&self) -> Result<(), LlmError> {
+        OpenAICompatibleProvider::health_check(self).await
+    }
+}
+
+#[async_trait::async_trait]
+impl ChatCompletion for OpenAICompatibleProvider {
+    async fn chat_completion(
+        &self,
+        request: ChatRequest,
+        model: &str,
+    ) -> Result<ChatResponse, LlmError> {
+        OpenAICompatibleProvider::chat_completion(self, request, model).await
+    }
+
+    async fn chat_completion_stream(
+        &self,
+        request: ChatRequest,
+        model: &str,
+        config: Option<StreamConfig>,
+    ) -> StreamResult<ChatStreamEvent> {
+        let config = config.unwrap_or_default();
+        let stream = OpenAICompatibleProvider::chat_completion_stream(
+            self,
+            request,
+            model,
+            config.buffer_size,
+        )
+        .await;
+        cancel_with(stream, config.cancel)
     }
 }
 
@@ -422,6 +662,19 @@ mod tests {
     use crate::utils::test_helpers::fake_sse_response;
     use futures::StreamExt;
 
+    #[test]
+    fn test_custom_identities_reuse_interned_strings() {
+        let first = ProviderIdentity::custom("lab", Some("LAB_KEY".into()), "http://lab/v1");
+        let second = ProviderIdentity::custom("lab", Some("LAB_KEY".into()), "http://lab/v1");
+        assert!(std::ptr::eq(first.name, second.name));
+        assert!(std::ptr::eq(first.env_key, second.env_key));
+        assert!(std::ptr::eq(
+            first.default_base_url,
+            second.default_base_url
+        ));
+        assert_eq!(ProviderIdentity::custom("lab", None, "x").env_key, "");
+    }
+
     #[tokio::test]
     async fn test_openai_compatible_stream_parsing() {
         // Create fake OpenAI-style SSE events
@@ -471,4 +724,64 @@ mod tests {
         let full_content: String = tokens.join("");
         assert_eq!(full_content, "Hello world!");
     }
+
+    #[test]
+    fn test_non_finite_sampling_is_rejected() {
+        let request = crate::compat_types::ChatRequestBuilder::new()
+            .user("Hi")
+            .temperature(f32::INFINITY)
+            .build();
+
+        let err = to_compatible_request(&request, "llama3-8b-8192", "groq").unwrap_err();
+        assert!(err.to_string().contains("temperature"));
+    }
+
+    #[test]
+    fn test_tool_calls_round_trip_through_openai_format() {
+        let provider =
+            OpenAICompatibleProvider::groq(crate::config::OpenAICompatibleConfig::groq("test-key"))
+                .unwrap();
+        let call = ToolCall {
+            id: "call_1".to_string(),
+            name: "get_weather".to_string(),
+            arguments: serde_json::json!({"city": "Paris"}),
+        };
+        let request = crate::compat_types::ChatRequestBuilder::new()
+            .user("Weather in Paris?")
+            .message(ChatMessage::assistant_tool_calls("", vec![call.clone()]))
+            .tool_result("call_1", "sunny")
+            .tool(crate::compat_types::ToolDefinition::new(
+                "get_weather",
+                "Look up the weather",
+                serde_json::json!({"type": "object"}),
+            ))
+            .tool_choice(ToolChoice::Auto)
+            .build();
+
+        let body = to_compatible_request(&request, "llama3-8b-8192", "groq").unwrap();
+
+        assert_eq!(body["tools"][0]["function"]["name"], "get_weather");
+        assert_eq!(body["tool_choice"], "auto");
+        assert!(body["messages"][1]["content"].is_null());
+        assert_eq!(body["messages"][2]["tool_call_id"], "call_1");
+
+        let response = provider
+            .parse_openai_response(serde_json::json!({
+                "id": "chatcmpl-1",
+                "model": "llama3-8b-8192",
+                "choices": [{
+                    "message": {
+                        "role": "assistant",
+                        "content": null,
+                        "tool_calls": body["messages"][1]["tool_calls"].clone()
+                    },
+                    "finish_reason": "tool_calls"
+                }],
+                "usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2}
+            }))
+            .unwrap();
+
+        assert_eq!(response.message.tool_calls, vec![call]);
+        assert_eq!(response.message.content, "");
+    }
 }
@@ -0,0 +1,268 @@
+//! String-in, string-out facade over every provider
+//!
+//! [`SimpleLlmClient`] pairs a [`ChatCompletion`] provider with a default
+//! model, and the [`SimpleLlm`] trait exposes plain-text helpers for the
+//! common cases. Use the provider types directly when you need tools,
+//! attachments or full responses.
+//!
+//! ```rust,no_run
+//! use rullm_core::simple::{SimpleLlm, SimpleLlmClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = SimpleLlmClient::anthropic("your-api-key")?.with_model("claude-3-5-sonnet-latest");
+//! let answer = client
+//!     .chat_with_system("Answer in one word", "What is the capital of France?")
+//!     .await?;
+//! println!("{answer}");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::compat_types::{ChatMessage, ChatRequestBuilder, ChatStreamEvent};
+use crate::config::{
+    AnthropicConfig, ConfigBuilder, GoogleAiConfig, OpenAICompatibleConfig, OpenAIConfig,
+};
+use crate::error::LlmError;
+use crate::providers::{AnthropicClient, GoogleClient, OpenAIClient, OpenAICompatibleProvider};
+use crate::types::{ChatCompletion, StreamResult};
+use futures::StreamExt;
+
+/// Model used by [`SimpleLlmClient::openai`]
+pub const DEFAULT_OPENAI_MODEL: &str = "gpt-4o-mini";
+/// Model used by [`SimpleLlmClient::anthropic`]
+pub const DEFAULT_ANTHROPIC_MODEL: &str = "claude-3-5-haiku-latest";
+/// Model used by [`SimpleLlmClient::google`]
+pub const DEFAULT_GOOGLE_MODEL: &str = "gemini-2.0-flash";
+/// Model used by [`SimpleLlmClient::groq`]
+pub const DEFAULT_GROQ_MODEL: &str = "llama-3.1-8b-instant";
+/// Model used by [`SimpleLlmClient::openrouter`]
+pub const DEFAULT_OPENROUTER_MODEL: &str = "openai/gpt-4o-mini";
+
+/// Plain-text chat helpers
+#[async_trait::async_trait]
+pub trait SimpleLlm: Send + Sync {
+    /// Send a single user message and return the reply text
+    async fn chat(&self, prompt: &str) -> Result<String, LlmError>;
+
+    /// Send a user message with a system prompt and return the reply text
+    async fn chat_with_system(&self, system: &str, prompt: &str) -> Result<String, LlmError>;
+
+    /// Send `(role, content)` pairs and return the reply text
+    ///
+    /// Roles are `system`, `user` and `assistant`.
+    async fn conversation(&self, messages: &[(&str, &str)]) -> Result<String, LlmError>;
+
+    /// Send a single user message and stream the reply text
+    async fn stream_chat(&self, prompt: &str) -> StreamResult<String>;
+}
+
+/// A provider and the model to send requests to
+pub struct SimpleLlmClient {
+    provider: Box<dyn ChatCompletion>,
+    model: String,
+}
+
+impl SimpleLlmClient {
+    /// Wrap any provider
+    pub fn new(provider: impl ChatCompletion + 'static, model: impl Into<String>) -> Self {
+        Self {
+            provider: Box::new(provider),
+            model: model.into(),
+        }
+    }
+
+    /// OpenAI client using [`DEFAULT_OPENAI_MODEL`]
+    pub fn openai(api_key: impl Into<String>) -> Result<Self, LlmError> {
+        let provider = OpenAIClient::new(OpenAIConfig::new(api_key))?;
+        Ok(Self::new(provider, DEFAULT_OPENAI_MODEL))
+    }
+
+    /// Anthropic client using [`DEFAULT_ANTHROPIC_MODEL`]
+    pub fn anthropic(api_key: impl Into<String>) -> Result<Self, LlmError> {
+        let provider = AnthropicClient::new(AnthropicConfig::new(api_key))?;
+        Ok(Self::new(provider, DEFAULT_ANTHROPIC_MODEL))
+    }
+
+    /// Google AI client using [`DEFAULT_GOOGLE_MODEL`]
+    pub fn google(api_key: impl Into<String>) -> Result<Self, LlmError> {
+        let provider = GoogleClient::new(GoogleAiConfig::new(api_key))?;
+        Ok(Self::new(provider, DEFAULT_GOOGLE_MODEL))
+    }
+
+    /// Groq client using [`DEFAULT_GROQ_MODEL`]
+    pub fn groq(api_key: impl Into<String>) -> Result<Self, LlmError> {
+        let provider = OpenAICompatibleProvider::groq(OpenAICompatibleConfig::groq(api_key))?;
+        Ok(Self::new(provider, DEFAULT_GROQ_MODEL))
+    }
+
+    /// OpenRouter client using [`DEFAULT_OPENROUTER_MODEL`]
+    pub fn openrouter(api_key: impl Into<String>) -> Result<Self, LlmError> {
+        let provider =
+            OpenAICompatibleProvider::openrouter(OpenAICompatibleConfig::openrouter(api_key))?;
+        Ok(Self::new(provider, DEFAULT_OPENROUTER_MODEL))
+    }
+
+    /// Client for the first provider with an API key in the environment
+    ///
+    /// Checks `OPENAI_API_KEY`, `ANTHROPIC_API_KEY`, `GOOGLE_AI_API_KEY`,
+    /// `GROQ_API_KEY` and `OPENROUTER_API_KEY`, in that order.
+    pub fn from_env() -> Result<Self, LlmError> {
+        if let Ok(config) = ConfigBuilder::openai_from_env() {
+            return Ok(Self::new(OpenAIClient::new(config)?, DEFAULT_OPENAI_MODEL));
+        }
+        if let Ok(config) = ConfigBuilder::anthropic_from_env() {
+            return Ok(Self::new(
+                AnthropicClient::new(config)?,
+                DEFAULT_ANTHROPIC_MODEL,
+            ));
+        }
+        if let Ok(config) = ConfigBuilder::google_ai_from_env() {
+            return Ok(Self::new(GoogleClient::new(config)?, DEFAULT_GOOGLE_MODEL));
+        }
+        if let Ok(config) = ConfigBuilder::groq_from_env() {
+            return Ok(Self::new(
+                OpenAICompatibleProvider::groq(config)?,
+                DEFAULT_GROQ_MODEL,
+            ));
+        }
+        if let Ok(config) = ConfigBuilder::openrouter_from_env() {
+            return Ok(Self::new(
+                OpenAICompatibleProvider::openrouter(config)?,
+                DEFAULT_OPENROUTER_MODEL,
+            ));
+        }
+        Err(LlmError::configuration(
+            "No API key found; set OPENAI_API_KEY, ANTHROPIC_API_KEY, GOOGLE_AI_API_KEY, GROQ_API_KEY or OPENROUTER_API_KEY",
+        ))
+    }
+
+    /// Send requests to `model` instead of the provider default
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Name of the wrapped provider, e.g. "openai"
+    pub fn provider_name(&self) -> &'static str {
+        self.provider.name()
+    }
+
+    async fn send(&self, messages: Vec<ChatMessage>) -> Result<String, LlmError> {
+        let request = messages
+            .into_iter()
+            .fold(ChatRequestBuilder::new(), ChatRequestBuilder::message)
+            .build();
+        let response = self.provider.chat_completion(request, &self.model).await?;
+        Ok(response.message.content)
+    }
+}
+
+#[async_trait::async_trait]
+impl SimpleLlm for SimpleLlmClient {
+    async fn chat(&self, prompt: &str) -> Result<String, LlmError> {
+        self.send(vec![ChatMessage::user(prompt)]).await
+    }
+
+    async fn chat_with_system(&self, system: &str, prompt: &str) -> Result<String, LlmError> {
+        self.send(vec![ChatMessage::system(system), ChatMessage::user(prompt)])
+            .await
+    }
+
+    async fn conversation(&self, messages: &[(&str, &str)]) -> Result<String, LlmError> {
+        let messages = messages
+            .iter()
+            .map(|&(role, content)| match role {
+                "system" => Ok(ChatMessage::system(content)),
+                "user" => Ok(ChatMessage::user(content)),
+                "assistant" => Ok(ChatMessage::assistant(content)),
+                other => Err(LlmError::validation(format!(
+                    "Unknown role '{other}': expected system, user or assistant"
+                ))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.send(messages).await
+    }
+
+    async fn stream_chat(&self, prompt: &str) -> StreamResult<String> {
+        let request = ChatRequestBuilder::new().user(prompt).stream(true).build();
+        let events = self
+            .provider
+            .chat_completion_stream(request, &self.model, None)
+            .await;
+        Box::pin(events.filter_map(|event| async move {
+            match event {
+                Ok(ChatStreamEvent::Token(token)) => Some(Ok(token)),
+                Ok(ChatStreamEvent::Error(message)) => Some(Err(LlmError::model(message))),
+                Ok(_) => None,
+                Err(e) => Some(Err(e)),
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compat_types::ChatRole;
+    use crate::utils::test_helpers::ScriptedProvider;
+
+    #[tokio::test]
+    async fn test_chat_helpers_build_requests() {
+        let provider = ScriptedProvider::text(&["Paris"; 3]);
+        let client = SimpleLlmClient::new(provider.clone(), "echo-1").with_model("echo-2");
+
+        assert_eq!(client.chat("Capital of France?").await.unwrap(), "Paris");
+        client.chat_with_system("Be brief", "Hi").await.unwrap();
+        client
+            .conversation(&[("user", "Hi"), ("assistant", "Hello"), ("user", "Bye")])
+            .await
+            .unwrap();
+
+        assert!(provider.models().iter().all(|model| model == "echo-2"));
+        let requests = provider.requests();
+        assert_eq!(requests[0].messages.len(), 1);
+        assert_eq!(requests[1].messages[0].role, ChatRole::System);
+        assert_eq!(requests[2].messages[1].role, ChatRole::Assistant);
+        assert_eq!(requests[2].messages[2].content, "Bye");
+    }
+
+    #[tokio::test]
+    async fn test_conversation_rejects_unknown_roles() {
+        let client = SimpleLlmClient::new(ScriptedProvider::default(), "echo");
+        let err = client.conversation(&[("robot", "Hi")]).await.unwrap_err();
+        assert!(matches!(err, LlmError::Validation { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_stream_chat_yields_only_text() {
+        let provider = ScriptedProvider::default().with_stream(vec![
+            ChatStreamEvent::Token("Par".to_string()),
+            ChatStreamEvent::Finish("stop".to_string()),
+            ChatStreamEvent::Token("is".to_string()),
+            ChatStreamEvent::Done,
+        ]);
+        let client = SimpleLlmClient::new(provider, "echo");
+        let tokens: Vec<String> = client
+            .stream_chat("Capital of France?")
+            .await
+            .map(|token| token.unwrap())
+            .collect()
+            .await;
+        assert_eq!(tokens, vec!["Par", "is"]);
+    }
+
+    #[test]
+    fn test_provider_constructors_use_default_models() {
+        let client = SimpleLlmClient::google("key").unwrap();
+        assert_eq!(client.provider_name(), "google");
+        assert_eq!(client.model(), DEFAULT_GOOGLE_MODEL);
+        assert_eq!(
+            SimpleLlmClient::groq("key").unwrap().model(),
+            DEFAULT_GROQ_MODEL
+        );
+    }
+}
@@ -0,0 +1,752 @@
+//! Unified streaming events across providers
+//!
+//! Each provider streams text, reasoning, tool calls, usage and stop reasons
+//! in its own wire format, and tool call arguments arrive as partial JSON
+//! fragments. The adapters in this module turn the native stream of each
+//! client into a stream of [`ChatStreamEvent`]s, reassembling tool calls with
+//! a [`ToolCallAssembler`] so that a [`ChatStreamEvent::ToolCallComplete`]
+//! carries the parsed arguments.
+//!
+//! ```rust,no_run
+//! use futures::StreamExt;
+//! use rullm_core::providers::anthropic::{AnthropicClient, Message, MessagesRequest};
+//! use rullm_core::ChatStreamEvent;
+//! use rullm_core::stream::anthropic_events;
+//!
+//! # async fn example(client: AnthropicClient) -> Result<(), Box<dyn std::error::Error>> {
+//! let request = MessagesRequest::new(
+//!     "claude-3-haiku-20240307",
+//!     vec![Message::user("Hello")],
+//!     1024,
+//! );
+//! let mut events = anthropic_events(client.messages_stream(request).await?);
+//! while let Some(event) = events.next().await {
+//!     match event? {
+//!         ChatStreamEvent::Token(text) => print!("{text}"),
+//!         ChatStreamEvent::ToolCallComplete { call, .. } => println!("\n{call:?}"),
+//!         _ => {}
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::compat_types::{ChatStreamEvent, TokenUsage, ToolCall};
+use crate::error::LlmError;
+use crate::providers::anthropic::{self, ContentBlockStart, Delta, ErrorData};
+use crate::providers::google::provider::new_call_id;
+use crate::providers::google::{GenerateContentResponse, Part};
+use crate::providers::openai::ChatCompletionChunk;
+use crate::types::StreamResult;
+use crate::utils::http::decode_error;
+use futures::{Stream, StreamExt};
+use std::collections::BTreeMap;
+use std::future::Future;
+use tokio_util::sync::CancellationToken;
+
+#[derive(Debug)]
+struct PendingCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+/// Accumulates streamed tool call fragments into complete [`ToolCall`]s
+#[derive(Debug, Default)]
+pub struct ToolCallAssembler {
+    pending: BTreeMap<usize, PendingCall>,
+}
+
+impl ToolCallAssembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Begin assembling the call at `index`
+    pub fn start(&mut self, index: usize, id: impl Into<String>, name: impl Into<String>) {
+        self.pending.insert(
+            index,
+            PendingCall {
+                id: id.into(),
+                name: name.into(),
+                arguments: String::new(),
+            },
+        );
+    }
+
+    /// Whether a call is being assembled at `index`
+    pub fn is_pending(&self, index: usize) -> bool {
+        self.pending.contains_key(&index)
+    }
+
+    /// Append an argument fragment to the call at `index`
+    ///
+    /// Returns `false` if no call was started at `index`.
+    pub fn push(&mut self, index: usize, fragment: &str) -> bool {
+        match self.pending.get_mut(&index) {
+            Some(call) => {
+                call.arguments.push_str(fragment);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Complete the call at `index`, parsing its accumulated arguments
+    pub fn finish(&mut self, index: usize) -> Option<Result<ToolCall, LlmError>> {
+        self.pending.remove(&index).map(complete)
+    }
+
+    /// Complete every pending call, in index order
+    pub fn finish_all(&mut self) -> Vec<(usize, Result<ToolCall, LlmError>)> {
+        std::mem::take(&mut self.pending)
+            .into_iter()
+            .map(|(index, call)| (index, complete(call)))
+            .collect()
+    }
+}
+
+fn complete(call: PendingCall) -> Result<ToolCall, LlmError> {
+    // Calls without parameters may stream no argument fragments at all
+    let arguments = if call.arguments.trim().is_empty() {
+        serde_json::Value::Object(Default::default())
+    } else {
+        serde_json::from_str(&call.arguments).map_err(|e| {
+            LlmError::serialization(
+                format!("Invalid arguments for tool call '{}'", call.name),
+                Box::new(e),
+            )
+        })?
+    };
+    Ok(ToolCall {
+        id: call.id,
+        name: call.name,
+        arguments,
+    })
+}
+
+fn complete_event(
+    index: usize,
+    call: Result<ToolCall, LlmError>,
+) -> Result<ChatStreamEvent, LlmError> {
+    call.map(|call| ChatStreamEvent::ToolCallComplete { index, call })
+}
+
+/// Adapt an OpenAI chat completion chunk stream
+pub fn openai_events<S>(chunks: S) -> StreamResult<ChatStreamEvent>
+where
+    S: Stream<Item = Result<ChatCompletionChunk, LlmError>> + Send + 'static,
+{
+    Box::pin(async_stream::stream! {
+        let mut chunks = Box::pin(chunks);
+        let mut assembler = ToolCallAssembler::new();
+
+        while let Some(chunk) = chunks.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            };
+
+            if let Some(choice) = chunk.choices.into_iter().next() {
+                if let Some(reasoning) = choice.delta.reasoning_content.filter(|r| !r.is_empty()) {
+                    yield Ok(ChatStreamEvent::ReasoningDelta(reasoning));
+                }
+                if let Some(text) = choice.delta.content.filter(|text| !text.is_empty()) {
+                    yield Ok(ChatStreamEvent::Token(text));
+                }
+                for delta in choice.delta.tool_calls.unwrap_or_default() {
+                    let index = delta.index as usize;
+                    let function = delta.function.unwrap_or(
+                        crate::providers::openai::FunctionCallDelta {
+                            name: None,
+                            arguments: None,
+                        },
+                    );
+                    if !assembler.is_pending(index) {
+                        let id = delta.id.unwrap_or_default();
+                        let name = function.name.clone().unwrap_or_default();
+                        assembler.start(index, id.clone(), name.clone());
+                        yield Ok(ChatStreamEvent::ToolCallStart { index, id, name });
+                    }
+                    if let Some(arguments) = function.arguments.filter(|a| !a.is_empty()) {
+                        assembler.push(index, &arguments);
+                        yield Ok(ChatStreamEvent::ToolCallDelta { index, arguments });
+                    }
+                }
+                if let Some(reason) = choice.finish_reason {
+                    for (index, call) in assembler.finish_all() {
+                        yield complete_event(index, call);
+                    }
+                    yield Ok(ChatStreamEvent::Finish(reason));
+                }
+            }
+
+            if let Some(usage) = chunk.usage {
+                yield Ok(ChatStreamEvent::Usage(TokenUsage {
+                    prompt_tokens: usage.prompt_tokens,
+                    completion_tokens: usage.completion_tokens,
+                    total_tokens: usage.total_tokens,
+                }));
+            }
+        }
+
+        for (index, call) in assembler.finish_all() {
+            yield complete_event(index, call);
+        }
+    })
+}
+
+/// Adapt an Anthropic messages event stream
+pub fn anthropic_events<S>(events: S) -> StreamResult<ChatStreamEvent>
+where
+    S: Stream<Item = Result<anthropic::StreamEvent, LlmError>> + Send + 'static,
+{
+    Box::pin(async_stream::stream! {
+        let mut events = Box::pin(events);
+        let mut assembler = ToolCallAssembler::new();
+        let mut input_tokens = 0;
+
+        while let Some(event) = events.next().await {
+            match event {
+                Ok(anthropic::StreamEvent::MessageStart { message }) => {
+                    input_tokens = message.usage.input_tokens;
+                }
+                Ok(anthropic::StreamEvent::ContentBlockStart {
+                    index,
+                    content_block: ContentBlockStart::ToolUse { id, name },
+                }) => {
+                    let index = index as usize;
+                    assembler.start(index, id.clone(), name.clone());
+                    yield Ok(ChatStreamEvent::ToolCallStart { index, id, name });
+                }
+                Ok(anthropic::StreamEvent::ContentBlockStart {
+                    content_block: ContentBlockStart::Text { text },
+                    ..
+                }) if !text.is_empty() => {
+                    yield Ok(ChatStreamEvent::Token(text));
+                }
+                Ok(anthropic::StreamEvent::ContentBlockStart {
+                    content_block: ContentBlockStart::Thinking { thinking },
+                    ..
+                }) if !thinking.is_empty() => {
+                    yield Ok(ChatStreamEvent::ReasoningDelta(thinking));
+                }
+                Ok(anthropic::StreamEvent::ContentBlockDelta { index, delta }) => match delta {
+                    Delta::TextDelta { text } => yield Ok(ChatStreamEvent::Token(text)),
+                    Delta::ThinkingDelta { thinking } => {
+                        yield Ok(ChatStreamEvent::ReasoningDelta(thinking))
+                    }
+                    Delta::SignatureDelta { .. } => {}
+                    Delta::InputJsonDelta { partial_json } => {
+                        let index = index as usize;
+                        if assembler.push(index, &partial_json) {
+                            yield Ok(ChatStreamEvent::ToolCallDelta {
+                                index,
+                                arguments: partial_json,
+                            });
+                        }
+                    }
+                },
+                Ok(anthropic::StreamEvent::ContentBlockStop { index }) => {
+                    if let Some(call) = assembler.finish(index as usize) {
+                        yield complete_event(index as usize, call);
+                    }
+                }
+                Ok(anthropic::StreamEvent::MessageDelta { delta, usage }) => {
+                    let input_tokens = input_tokens.max(usage.input_tokens);
+                    yield Ok(ChatStreamEvent::Usage(TokenUsage {
+                        prompt_tokens: input_tokens,
+                        completion_tokens: usage.output_tokens,
+                        total_tokens: input_tokens + usage.output_tokens,
+                    }));
+                    let reason = delta
+                        .stop_reason
+                        .and_then(|reason| serde_json::to_value(reason).ok())
+                        .and_then(|value| value.as_str().map(|s| s.to_string()));
+                    if let Some(reason) = reason {
+                        yield Ok(ChatStreamEvent::Finish(reason));
+                    }
+                }
+                Ok(anthropic::StreamEvent::MessageStop) => break,
+                Ok(anthropic::StreamEvent::Error { error }) => {
+                    yield Err(anthropic_error(error));
+                    return;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            }
+        }
+
+        for (index, call) in assembler.finish_all() {
+            yield complete_event(index, call);
+        }
+    })
+}
+
+/// Convert an `error` event received mid-stream into an [`LlmError`]
+///
+/// Anthropic reports the same error types in streams as in HTTP error
+/// responses, so they are decoded as if they came with the matching status.
+fn anthropic_error(error: ErrorData) -> LlmError {
+    let status = match error.error_type.as_str() {
+        "invalid_request_error" => 400,
+        "authentication_error" => 401,
+        "permission_error" => 403,
+        "not_found_error" => 404,
+        "request_too_large" => 413,
+        "rate_limit_error" => 429,
+        "overloaded_error" => 529,
+        _ => 500,
+    };
+    let body = serde_json::json!({ "type": "error", "error": error }).to_string();
+    decode_error(
+        "anthropic",
+        reqwest::StatusCode::from_u16(status).unwrap_or(reqwest::StatusCode::INTERNAL_SERVER_ERROR),
+        &body,
+        None,
+        std::time::Duration::ZERO,
+    )
+}
+
+/// Adapt a Gemini stream generate content stream
+///
+/// Gemini sends each function call whole, so every call yields its start,
+/// argument and complete events together. Gemini does not provide call ids,
+/// so each call gets a random one. Usage is reported cumulatively on
+/// each chunk, so only the final figure is emitted.
+pub fn google_events<S>(responses: S) -> StreamResult<ChatStreamEvent>
+where
+    S: Stream<Item = Result<GenerateContentResponse, LlmError>> + Send + 'static,
+{
+    Box::pin(async_stream::stream! {
+        let mut responses = Box::pin(responses);
+        let mut calls = 0;
+        let mut usage = None;
+        let mut finish_reason = None;
+
+        while let Some(response) = responses.next().await {
+            let response = match response {
+                Ok(response) => response,
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            };
+
+            if let Some(metadata) = response.usage_metadata {
+                usage = Some(TokenUsage {
+                    prompt_tokens: metadata.prompt_token_count,
+                    completion_tokens: metadata.candidates_token_count,
+                    total_tokens: metadata.total_token_count,
+                });
+            }
+
+            let Some(candidate) = response.candidates.into_iter().next() else {
+                continue;
+            };
+            for part in candidate.content.parts {
+                match part {
+                    Part::Text { text } if !text.is_empty() => {
+                        yield Ok(ChatStreamEvent::Token(text));
+                    }
+                    Part::Thought { text, thought: true } if !text.is_empty() => {
+                        yield Ok(ChatStreamEvent::ReasoningDelta(text));
+                    }
+                    Part::FunctionCall { function_call } => {
+                        let index = calls;
+                        calls += 1;
+                        let id = new_call_id();
+                        let name = function_call.name;
+                        yield Ok(ChatStreamEvent::ToolCallStart {
+                            index,
+                            id: id.clone(),
+                            name: name.clone(),
+                        });
+                        yield Ok(ChatStreamEvent::ToolCallDelta {
+                            index,
+                            arguments: function_call.args.to_string(),
+                        });
+                        yield Ok(ChatStreamEvent::ToolCallComplete {
+                            index,
+                            call: ToolCall {
+                                id,
+                                name,
+                                arguments: function_call.args,
+                            },
+                        });
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(reason) = candidate.finish_reason {
+                finish_reason = serde_json::to_value(reason)
+                    .ok()
+                    .and_then(|value| value.as_str().map(|s| s.to_string()));
+            }
+        }
+
+        if let Some(usage) = usage {
+            yield Ok(ChatStreamEvent::Usage(usage));
+        }
+        if let Some(reason) = finish_reason {
+            yield Ok(ChatStreamEvent::Finish(reason));
+        }
+    })
+}
+
+/// Forward adapted events and finish with [`ChatStreamEvent::Done`], as the
+/// `ChatCompletion` trait promises; a stream error ends the stream instead
+pub(crate) fn chat_stream_events(
+    mut events: StreamResult<ChatStreamEvent>,
+) -> StreamResult<ChatStreamEvent> {
+    Box::pin(async_stream::stream! {
+        while let Some(event) = events.next().await {
+            let failed = event.is_err();
+            yield event;
+            if failed {
+                return;
+            }
+        }
+
+        yield Ok(ChatStreamEvent::Done);
+    })
+}
+
+/// End `events` as soon as `token` is cancelled
+///
+/// The wrapped stream, and with it the HTTP connection, is dropped right
+/// away rather than when the caller gets around to dropping the result.
+pub fn cancellable<T: Send + 'static>(
+    mut events: StreamResult<T>,
+    token: CancellationToken,
+) -> StreamResult<T> {
+    Box::pin(async_stream::stream! {
+        loop {
+            let event = tokio::select! {
+                biased;
+                _ = token.cancelled() => break,
+                event = events.next() => event,
+            };
+            match event {
+                Some(event) => yield event,
+                None => break,
+            }
+        }
+    })
+}
+
+/// Apply the cancellation token from a [`StreamConfig`](crate::StreamConfig), if any
+pub(crate) fn cancel_with<T: Send + 'static>(
+    events: StreamResult<T>,
+    cancel: Option<CancellationToken>,
+) -> StreamResult<T> {
+    match cancel {
+        Some(token) => cancellable(events, token),
+        None => events,
+    }
+}
+
+/// Await `request` unless `cancel` fires first, in which case `None` is
+/// returned and the request dropped
+pub(crate) async fn unless_cancelled<F: Future>(
+    cancel: Option<&CancellationToken>,
+    request: F,
+) -> Option<F::Output> {
+    match cancel {
+        Some(token) => token.run_until_cancelled(request).await,
+        None => Some(request.await),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::DeserializeOwned;
+
+    /// Deserialize native events and collect the adapted stream
+    async fn adapt<T, F>(events: Vec<serde_json::Value>, adapter: F) -> Vec<ChatStreamEvent>
+    where
+        T: DeserializeOwned + Send + 'static,
+        F: FnOnce(
+            futures::stream::Iter<std::vec::IntoIter<Result<T, LlmError>>>,
+        ) -> StreamResult<ChatStreamEvent>,
+    {
+        let native: Vec<Result<T, LlmError>> = events
+            .into_iter()
+            .map(|event| Ok(serde_json::from_value(event).unwrap()))
+            .collect();
+        adapter(futures::stream::iter(native))
+            .map(|event| event.unwrap())
+            .collect()
+            .await
+    }
+
+    #[test]
+    fn test_assembler_parses_fragments() {
+        let mut assembler = ToolCallAssembler::new();
+        assembler.start(0, "call_1", "get_weather");
+        assert!(assembler.push(0, r#"{"ci"#));
+        assert!(assembler.push(0, r#"ty": "Paris"}"#));
+        assert!(!assembler.push(1, "{}"));
+
+        let call = assembler.finish(0).unwrap().unwrap();
+        assert_eq!(call.arguments, serde_json::json!({"city": "Paris"}));
+        assert!(assembler.finish(0).is_none());
+
+        assembler.start(1, "call_2", "now");
+        let finished = assembler.finish_all();
+        assert_eq!(
+            finished[0].1.as_ref().unwrap().arguments,
+            serde_json::json!({})
+        );
+
+        assembler.start(2, "call_3", "broken");
+        assembler.push(2, "{");
+        assert!(matches!(
+            assembler.finish(2),
+            Some(Err(LlmError::Serialization { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_openai_events_assemble_tool_calls() {
+        let chunk = |delta: serde_json::Value, finish: Option<&str>| {
+            serde_json::json!({
+                "id": "chatcmpl-1", "object": "chat.completion.chunk", "created": 1, "model": "gpt-4o",
+                "choices": [{"index": 0, "delta": delta, "finish_reason": finish}]
+            })
+        };
+        let events = adapt(
+            vec![
+                chunk(serde_json::json!({"content": "Checking"}), None),
+                chunk(
+                    serde_json::json!({"tool_calls": [{"index": 0, "id": "call_1", "type": "function",
+                        "function": {"name": "get_weather", "arguments": ""}}]}),
+                    None,
+                ),
+                chunk(
+                    serde_json::json!({"tool_calls": [{"index": 0, "function": {"arguments": "{\"city\":"}}]}),
+                    None,
+                ),
+                chunk(
+                    serde_json::json!({"tool_calls": [{"index": 0, "function": {"arguments": "\"Paris\"}"}}]}),
+                    None,
+                ),
+                chunk(serde_json::json!({}), Some("tool_calls")),
+                serde_json::json!({
+                    "id": "chatcmpl-1", "object": "chat.completion.chunk", "created": 1, "model": "gpt-4o",
+                    "choices": [],
+                    "usage": {"prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15}
+                }),
+            ],
+            openai_events,
+        )
+        .await;
+
+        assert_eq!(events[0], ChatStreamEvent::Token("Checking".to_string()));
+        assert_eq!(
+            events[1],
+            ChatStreamEvent::ToolCallStart {
+                index: 0,
+                id: "call_1".to_string(),
+                name: "get_weather".to_string()
+            }
+        );
+        assert!(matches!(
+            events[2],
+            ChatStreamEvent::ToolCallDelta { index: 0, .. }
+        ));
+        assert!(matches!(
+            &events[4],
+            ChatStreamEvent::ToolCallComplete { call, .. } if call.arguments["city"] == "Paris"
+        ));
+        assert_eq!(events[5], ChatStreamEvent::Finish("tool_calls".to_string()));
+        assert!(matches!(&events[6], ChatStreamEvent::Usage(usage) if usage.total_tokens == 15));
+    }
+
+    #[tokio::test]
+    async fn test_anthropic_events_assemble_tool_use() {
+        let events = adapt(
+            vec![
+                serde_json::json!({"type": "message_start", "message": {
+                    "id": "msg_1", "type": "message", "role": "assistant", "content": [],
+                    "model": "claude-3-haiku-20240307", "stop_reason": null, "stop_sequence": null,
+                    "usage": {"input_tokens": 12, "output_tokens": 1}}}),
+                serde_json::json!({"type": "content_block_start", "index": 0,
+                    "content_block": {"type": "text", "text": ""}}),
+                serde_json::json!({"type": "content_block_delta", "index": 0,
+                    "delta": {"type": "text_delta", "text": "Checking"}}),
+                serde_json::json!({"type": "content_block_stop", "index": 0}),
+                serde_json::json!({"type": "content_block_start", "index": 1,
+                    "content_block": {"type": "tool_use", "id": "toolu_1", "name": "get_weather", "input": {}}}),
+                serde_json::json!({"type": "content_block_delta", "index": 1,
+                    "delta": {"type": "input_json_delta", "partial_json": "{\"city\": \"Pa"}}),
+                serde_json::json!({"type": "content_block_delta", "index": 1,
+                    "delta": {"type": "input_json_delta", "partial_json": "ris\"}"}}),
+                serde_json::json!({"type": "content_block_stop", "index": 1}),
+                serde_json::json!({"type": "message_delta",
+                    "delta": {"stop_reason": "tool_use", "stop_sequence": null},
+                    "usage": {"output_tokens": 20}}),
+                serde_json::json!({"type": "message_stop"}),
+            ],
+            anthropic_events,
+        )
+        .await;
+
+        assert_eq!(events[0], ChatStreamEvent::Token("Checking".to_string()));
+        assert!(
+            matches!(&events[1], ChatStreamEvent::ToolCallStart { index: 1, name, .. } if name == "get_weather")
+        );
+        assert!(matches!(
+            &events[4],
+            ChatStreamEvent::ToolCallComplete { index: 1, call } if call.id == "toolu_1" && call.arguments["city"] == "Paris"
+        ));
+        assert!(matches!(
+            &events[5],
+            ChatStreamEvent::Usage(usage) if usage.prompt_tokens == 12 && usage.total_tokens == 32
+        ));
+        assert_eq!(events[6], ChatStreamEvent::Finish("tool_use".to_string()));
+        assert_eq!(events.len(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_google_events_emit_whole_function_calls() {
+        let events = adapt(
+            vec![
+                serde_json::json!({
+                    "candidates": [{"content": {"role": "model", "parts": [{"text": "Checking"}]}}],
+                    "usageMetadata": {"promptTokenCount": 4, "candidatesTokenCount": 1, "totalTokenCount": 5}
+                }),
+                serde_json::json!({
+                    "candidates": [{
+                        "content": {"role": "model", "parts": [
+                            {"functionCall": {"name": "get_weather", "args": {"city": "Paris"}}}
+                        ]},
+                        "finishReason": "STOP"
+                    }],
+                    "usageMetadata": {"promptTokenCount": 4, "candidatesTokenCount": 6, "totalTokenCount": 10}
+                }),
+            ],
+            google_events,
+        )
+        .await;
+
+        assert_eq!(events[0], ChatStreamEvent::Token("Checking".to_string()));
+        assert!(
+            matches!(&events[1], ChatStreamEvent::ToolCallStart { index: 0, id, .. } if id.starts_with("call_"))
+        );
+        assert!(matches!(
+            &events[3],
+            ChatStreamEvent::ToolCallComplete { call, .. } if call.arguments["city"] == "Paris"
+        ));
+        assert!(matches!(&events[4], ChatStreamEvent::Usage(usage) if usage.total_tokens == 10));
+        assert_eq!(events[5], ChatStreamEvent::Finish("STOP".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_reasoning_deltas_from_each_provider() {
+        let reasoning = |text: &str| ChatStreamEvent::ReasoningDelta(text.to_string());
+
+        let openai = adapt(
+            vec![serde_json::json!({
+                "choices": [{"delta": {"reasoning_content": "Let me think"}}]
+            })],
+            openai_events,
+        )
+        .await;
+        assert_eq!(openai, vec![reasoning("Let me think")]);
+
+        let anthropic = adapt(
+            vec![
+                serde_json::json!({"type": "content_block_start", "index": 0,
+                    "content_block": {"type": "thinking", "thinking": ""}}),
+                serde_json::json!({"type": "content_block_delta", "index": 0,
+                    "delta": {"type": "thinking_delta", "thinking": "Let me think"}}),
+                serde_json::json!({"type": "content_block_delta", "index": 0,
+                    "delta": {"type": "signature_delta", "signature": "sig"}}),
+                serde_json::json!({"type": "content_block_stop", "index": 0}),
+            ],
+            anthropic_events,
+        )
+        .await;
+        assert_eq!(anthropic, vec![reasoning("Let me think")]);
+
+        let google = adapt(
+            vec![serde_json::json!({
+                "candidates": [{"content": {"role": "model", "parts": [
+                    {"text": "Let me think", "thought": true},
+                    {"text": "Answer"}
+                ]}}]
+            })],
+            google_events,
+        )
+        .await;
+        assert_eq!(
+            google,
+            vec![
+                reasoning("Let me think"),
+                ChatStreamEvent::Token("Answer".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_anthropic_error_maps_to_typed_error() {
+        let overloaded = anthropic_error(ErrorData {
+            error_type: "overloaded_error".to_string(),
+            message: "Overloaded".to_string(),
+        });
+        assert!(matches!(
+            overloaded,
+            LlmError::ServiceUnavailable { message: Some(ref message), .. } if message == "Overloaded"
+        ));
+
+        let invalid = anthropic_error(ErrorData {
+            error_type: "invalid_request_error".to_string(),
+            message: "bad".to_string(),
+        });
+        assert!(matches!(
+            invalid,
+            LlmError::Api { code: Some(ref c), .. } if c == "invalid_request_error"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cancellable_drops_stream_when_cancelled() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        /// Sets the flag when dropped, standing in for the HTTP connection
+        struct Connection(Arc<AtomicBool>);
+        impl Drop for Connection {
+            fn drop(&mut self) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        let dropped = Arc::new(AtomicBool::new(false));
+        let connection = Connection(dropped.clone());
+        let events: StreamResult<ChatStreamEvent> = Box::pin(async_stream::stream! {
+            let _connection = connection;
+            yield Ok(ChatStreamEvent::Token("Hel".to_string()));
+            futures::future::pending::<()>().await;
+        });
+
+        let token = CancellationToken::new();
+        let mut events = cancellable(events, token.clone());
+        assert_eq!(
+            events.next().await.unwrap().unwrap(),
+            ChatStreamEvent::Token("Hel".to_string())
+        );
+
+        token.cancel();
+        assert!(events.next().await.is_none());
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,406 @@
+//! Structured output: ask for JSON matching a schema and deserialize it
+//!
+//! [`StructuredOutput`] sets the request's [`ResponseFormat`] so each provider
+//! uses its native JSON mode (OpenAI `response_format`, Gemini
+//! `response_schema`) or, for Anthropic, a forced tool call. The reply is
+//! checked against the schema and deserialized; if either step fails the
+//! error is sent back to the model and the request retried.
+//!
+//! ```rust,no_run
+//! use rullm_core::structured::StructuredOutput;
+//! use rullm_core::{ChatRequestBuilder, OpenAIConfig, OpenAIProvider};
+//! use serde::Deserialize;
+//! use serde_json::json;
+//!
+//! #[derive(Deserialize)]
+//! struct Invoice {
+//!     number: String,
+//!     total: f64,
+//! }
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let provider = OpenAIProvider::new(OpenAIConfig::new("your-api-key"))?;
+//! let schema = json!({
+//!     "title": "invoice",
+//!     "type": "object",
+//!     "properties": {"number": {"type": "string"}, "total": {"type": "number"}},
+//!     "required": ["number", "total"]
+//! });
+//! let request = ChatRequestBuilder::new()
+//!     .user("Invoice #A-17, amount due $120.50")
+//!     .build();
+//!
+//! let invoice: Invoice = StructuredOutput::new(&provider, "gpt-4o-mini")
+//!     .with_max_retries(3)
+//!     .generate_structured(request, schema)
+//!     .await?;
+//! println!("{} {}", invoice.number, invoice.total);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::compat_types::{ChatMessage, ChatRequest, ResponseFormat};
+use crate::error::LlmError;
+use crate::types::ChatCompletion;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// Default number of times an invalid reply is sent back for correction
+pub const DEFAULT_MAX_RETRIES: usize = 2;
+
+/// Schema name used when the schema has no usable `title`
+const DEFAULT_SCHEMA_NAME: &str = "response";
+
+/// Requests JSON output from a provider and deserializes it into a type
+pub struct StructuredOutput<'a, P: ?Sized> {
+    provider: &'a P,
+    model: String,
+    max_retries: usize,
+}
+
+impl<'a, P: ChatCompletion + ?Sized> StructuredOutput<'a, P> {
+    pub fn new(provider: &'a P, model: impl Into<String>) -> Self {
+        Self {
+            provider,
+            model: model.into(),
+            max_retries: DEFAULT_MAX_RETRIES,
+        }
+    }
+
+    /// Set how many times an invalid reply is re-prompted before giving up
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Send `request` asking for JSON matching `schema` and deserialize the reply
+    pub async fn generate_structured<T: DeserializeOwned>(
+        &self,
+        mut request: ChatRequest,
+        schema: Value,
+    ) -> Result<T, LlmError> {
+        request.response_format = Some(ResponseFormat::json_schema(
+            schema_name(&schema),
+            schema.clone(),
+        ));
+
+        let mut attempt = 0;
+        loop {
+            let response =
+                ChatCompletion::chat_completion(self.provider, request.clone(), &self.model)
+                    .await?;
+            let text = response.message.content;
+            let error = match parse_reply::<T>(&text, &schema) {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+
+            if attempt >= self.max_retries {
+                return Err(LlmError::validation(format!(
+                    "Model output did not match the schema after {} attempt(s): {error}",
+                    attempt + 1
+                )));
+            }
+            attempt += 1;
+            request.messages.push(ChatMessage::assistant(text));
+            request.messages.push(ChatMessage::user(format!(
+                "That response was invalid: {error}. Reply again with only JSON that matches the schema."
+            )));
+        }
+    }
+}
+
+/// Parse, validate and deserialize a model reply
+fn parse_reply<T: DeserializeOwned>(text: &str, schema: &Value) -> Result<T, String> {
+    let value: Value =
+        serde_json::from_str(extract_json(text)).map_err(|e| format!("not valid JSON ({e})"))?;
+    validate_schema(&value, schema)?;
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+/// Strip a Markdown code fence that models sometimes wrap JSON in
+pub fn extract_json(text: &str) -> &str {
+    let text = text.trim();
+    let Some(inner) = text.strip_prefix("```") else {
+        return text;
+    };
+    let inner = inner.strip_suffix("```").unwrap_or(inner);
+    // Skip the language tag on the opening fence line
+    match inner.split_once('\n') {
+        Some((_, body)) => body.trim(),
+        None => inner.trim(),
+    }
+}
+
+/// Name for the schema, from its `title` when that is a valid identifier
+fn schema_name(schema: &Value) -> String {
+    schema
+        .get("title")
+        .and_then(Value::as_str)
+        .filter(|title| {
+            !title.is_empty()
+                && title
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        })
+        .unwrap_or(DEFAULT_SCHEMA_NAME)
+        .to_string()
+}
+
+/// Check `value` against a JSON Schema
+///
+/// Supports the subset used for structured output: `type`, `enum`, `const`,
+/// `properties`, `required`, `additionalProperties`, `items` and `anyOf`.
+/// Other keywords are ignored. The error names the path of the first
+/// mismatch, e.g. `$.items[2].price: expected number`.
+pub fn validate_schema(value: &Value, schema: &Value) -> Result<(), String> {
+    validate_at(value, schema, "$")
+}
+
+fn validate_at(value: &Value, schema: &Value, path: &str) -> Result<(), String> {
+    let Some(schema) = schema.as_object() else {
+        // `true` and `{}`-like schemas accept everything; `false` accepts nothing
+        return match schema {
+            Value::Bool(false) => Err(format!("{path}: not allowed")),
+            _ => Ok(()),
+        };
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|t| has_type(value, t)) {
+            return Err(format!(
+                "{path}: expected {}, got {}",
+                types.join(" or "),
+                type_name(value)
+            ));
+        }
+    }
+
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            return Err(format!(
+                "{path}: {value} is not one of {}",
+                Value::from(options.clone())
+            ));
+        }
+    }
+
+    if let Some(expected) = schema.get("const") {
+        if value != expected {
+            return Err(format!("{path}: expected {expected}, got {value}"));
+        }
+    }
+
+    if let Some(branches) = schema.get("anyOf").and_then(Value::as_array) {
+        if !branches
+            .iter()
+            .any(|branch| validate_at(value, branch, path).is_ok())
+        {
+            return Err(format!("{path}: does not match any allowed schema"));
+        }
+    }
+
+    if let Value::Object(object) = value {
+        let properties = schema.get("properties").and_then(Value::as_object);
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(key) {
+                    return Err(format!("{path}: missing required property \"{key}\""));
+                }
+            }
+        }
+        for (key, item) in object {
+            let item_path = format!("{path}.{key}");
+            match properties.and_then(|p| p.get(key)) {
+                Some(property) => validate_at(item, property, &item_path)?,
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {
+                        return Err(format!("{path}: unexpected property \"{key}\""));
+                    }
+                    Some(extra) => validate_at(item, extra, &item_path)?,
+                    None => {}
+                },
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (i, item) in items.iter().enumerate() {
+            validate_at(item, item_schema, &format!("{path}[{i}]"))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn has_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compat_types::{ChatRequestBuilder, ChatRole};
+    use crate::utils::test_helpers::ScriptedProvider;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Person {
+        name: String,
+        age: u32,
+    }
+
+    fn person_schema() -> Value {
+        json!({
+            "title": "person",
+            "type": "object",
+            "properties": {"name": {"type": "string"}, "age": {"type": "integer"}},
+            "required": ["name", "age"]
+        })
+    }
+
+    #[tokio::test]
+    async fn test_generate_structured_sets_response_format() {
+        let provider = ScriptedProvider::text(&["```json\n{\"name\": \"Ada\", \"age\": 36}\n```"]);
+        let request = ChatRequestBuilder::new()
+            .user("Who wrote the first program?")
+            .build();
+
+        let person: Person = StructuredOutput::new(&provider, "model")
+            .generate_structured(request, person_schema())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            person,
+            Person {
+                name: "Ada".to_string(),
+                age: 36
+            }
+        );
+        let requests = provider.requests();
+        assert_eq!(
+            requests[0].response_format,
+            Some(ResponseFormat::json_schema("person", person_schema()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_generate_structured_reprompts_with_error() {
+        let provider = ScriptedProvider::text(&[
+            "not json",
+            r#"{"name": "Ada"}"#,
+            r#"{"name": "Ada", "age": 36}"#,
+        ]);
+        let request = ChatRequestBuilder::new().user("Who?").build();
+
+        let person: Person = StructuredOutput::new(&provider, "model")
+            .generate_structured(request, person_schema())
+            .await
+            .unwrap();
+
+        assert_eq!(person.age, 36);
+        let requests = provider.requests();
+        assert_eq!(requests.len(), 3);
+        let last = &requests[2].messages;
+        assert_eq!(last.len(), 5);
+        assert_eq!(last[3].role, ChatRole::Assistant);
+        assert!(
+            last[4]
+                .content
+                .contains("missing required property \"age\"")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_generate_structured_gives_up_after_max_retries() {
+        let provider = ScriptedProvider::text(&["nope", "still nope"]);
+        let request = ChatRequestBuilder::new().user("Who?").build();
+
+        let result: Result<Person, _> = StructuredOutput::new(&provider, "model")
+            .with_max_retries(1)
+            .generate_structured(request, person_schema())
+            .await;
+
+        assert!(matches!(result, Err(LlmError::Validation { .. })));
+        assert_eq!(provider.requests().len(), 2);
+    }
+
+    #[test]
+    fn test_validate_schema_reports_path() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "items": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {"price": {"type": "number"}},
+                        "additionalProperties": false
+                    }
+                },
+                "status": {"enum": ["paid", "due"]}
+            }
+        });
+
+        assert!(
+            validate_schema(
+                &json!({"items": [{"price": 1.5}], "status": "paid"}),
+                &schema
+            )
+            .is_ok()
+        );
+        assert_eq!(
+            validate_schema(&json!({"items": [{"price": 1}, {"price": "2"}]}), &schema),
+            Err("$.items[1].price: expected number, got string".to_string())
+        );
+        assert!(validate_schema(&json!({"items": [{"cost": 1}]}), &schema).is_err());
+        assert!(validate_schema(&json!({"status": "late"}), &schema).is_err());
+        assert!(validate_schema(&json!(3.0), &json!({"type": "integer"})).is_ok());
+    }
+
+    #[test]
+    fn test_extract_json_strips_code_fence() {
+        assert_eq!(extract_json("  {\"a\": 1} "), "{\"a\": 1}");
+        assert_eq!(extract_json("```json\n{\"a\": 1}\n```"), "{\"a\": 1}");
+        assert_eq!(extract_json("```\n[1]\n```"), "[1]");
+    }
+
+    #[test]
+    fn test_schema_name_falls_back_for_invalid_titles() {
+        assert_eq!(schema_name(&json!({"title": "invoice_v2"})), "invoice_v2");
+        assert_eq!(
+            schema_name(&json!({"title": "An invoice"})),
+            DEFAULT_SCHEMA_NAME
+        );
+        assert_eq!(schema_name(&json!({})), DEFAULT_SCHEMA_NAME);
+    }
+}
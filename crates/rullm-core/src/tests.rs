@@ -3,7 +3,7 @@ use crate::error::LlmError;
 use crate::middleware::{LlmServiceBuilder, MiddlewareConfig, RateLimit};
 use crate::types::{
     ChatCompletion, ChatMessage, ChatRequest, ChatRequestBuilder, ChatResponse, ChatRole,
-    LlmProvider, StreamConfig, TokenUsage,
+    ChatStreamEvent, LlmProvider, StreamConfig, TokenUsage,
 };
 use std::time::Duration;
 
@@ -78,10 +78,7 @@ impl ChatCompletion for MockProvider {
         }
 
         Ok(ChatResponse {
-            message: ChatMessage {
-                role: ChatRole::Assistant,
-                content: "Mock response".to_string(),
-            },
+            message: ChatMessage::assistant("Mock response"),
             model: model.to_string(),
             usage: TokenUsage {
                 prompt_tokens: 10,
@@ -366,6 +363,15 @@ fn test_all_llm_error_variants() {
         service_error.to_string(),
         "Service unavailable: anthropic is currently unavailable"
     );
+    let overloaded = LlmError::service_unavailable_with_message(
+        "anthropic",
+        "Overloaded",
+        Some("overloaded_error".to_string()),
+    );
+    assert_eq!(
+        overloaded.to_string(),
+        "Service unavailable: anthropic is currently unavailable: Overloaded"
+    );
 
     // Test Unknown error
     let unknown_error = LlmError::unknown("Unexpected error");
@@ -1042,3 +1048,186 @@ async fn test_middleware_performance_timing() {
     // The call should complete relatively quickly for a mock provider
     assert!(duration < Duration::from_secs(1));
 }
+
+// =============================================================================
+// Retry Tests
+// =============================================================================
+
+#[tokio::test]
+async fn test_client_retries_transient_failures() {
+    use crate::utils::test_helpers::{MockHttpServer, MockResponse};
+
+    let server = MockHttpServer::start(vec![
+        MockResponse::new(429, "rate limited").with_header("Retry-After", "0"),
+        MockResponse::new(503, "unavailable"),
+        MockResponse::json(serde_json::json!({
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-3-haiku-20240307",
+            "content": [{"type": "text", "text": "Hi"}],
+            "stop_reason": "end_turn",
+            "usage": {"input_tokens": 3, "output_tokens": 1}
+        })),
+    ])
+    .await;
+
+    let config = AnthropicConfig::new("test-key")
+        .with_base_url(server.base_url())
+        .with_retry_policy(
+            RetryPolicy::default()
+                .with_base_delay(Duration::from_millis(1))
+                .with_jitter(false),
+        );
+    let provider = crate::providers::AnthropicProvider::new(config).unwrap();
+    let request = ChatRequestBuilder::new().user("Hello").build();
+
+    let response = ChatCompletion::chat_completion(&provider, request, "claude-3-haiku-20240307")
+        .await
+        .unwrap();
+
+    assert_eq!(response.message.content, "Hi");
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn test_client_surfaces_rate_limit_after_retries() {
+    use crate::utils::test_helpers::{MockHttpServer, MockResponse};
+
+    let server = MockHttpServer::start(vec![
+        MockResponse::new(429, "rate limited").with_header("Retry-After", "0"),
+        MockResponse::new(429, "rate limited").with_header("Retry-After", "0"),
+    ])
+    .await;
+
+    let config = OpenAIConfig::new("test-key")
+        .with_base_url(server.base_url())
+        .with_retry_policy(RetryPolicy::default().with_max_retries(1));
+    let provider = crate::providers::OpenAIProvider::new(config).unwrap();
+    let request = ChatRequestBuilder::new().user("Hello").build();
+
+    let result = ChatCompletion::chat_completion(&provider, request, "gpt-4o").await;
+
+    assert!(matches!(result, Err(LlmError::RateLimit { .. })));
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_custom_provider_sends_extra_headers() {
+    use crate::providers::{OpenAICompatibleProvider, ProviderIdentity};
+    use crate::utils::test_helpers::{MockHttpServer, MockResponse};
+
+    let server = MockHttpServer::start(vec![MockResponse::new(500, "boom")]).await;
+    let config = OpenAICompatibleConfig::new("test-key")
+        .with_retry_policy(RetryPolicy::none())
+        .with_header("X-Team", "research");
+    let identity = ProviderIdentity::custom(
+        "mylab",
+        Some("MYLAB_API_KEY".to_string()),
+        server.base_url(),
+    );
+    let provider = OpenAICompatibleProvider::new(config, identity).unwrap();
+
+    assert_eq!(provider.name(), "mylab");
+    assert_eq!(provider.env_key(), "MYLAB_API_KEY");
+
+    let request = ChatRequestBuilder::new().user("Hello").build();
+    let result = ChatCompletion::chat_completion(&provider, request, "qwen2").await;
+
+    match result {
+        Err(LlmError::Api { provider, .. }) => assert_eq!(provider, "mylab"),
+        other => panic!("expected an API error, got {other:?}"),
+    }
+    let recorded = &server.requests()[0];
+    assert_eq!(recorded.header("x-team"), Some("research"));
+    assert_eq!(recorded.header("authorization"), Some("Bearer test-key"));
+}
+
+#[tokio::test]
+async fn test_keyless_provider_sends_no_authorization() {
+    use crate::providers::{OpenAICompatibleProvider, ProviderIdentity};
+    use crate::utils::test_helpers::{MockHttpServer, MockResponse};
+
+    let server = MockHttpServer::start(vec![MockResponse::new(500, "boom")]).await;
+    let config = OpenAICompatibleConfig::without_api_key(server.base_url())
+        .with_retry_policy(RetryPolicy::none());
+    let identity = ProviderIdentity::custom("local", None, server.base_url());
+    let provider = OpenAICompatibleProvider::new(config, identity).unwrap();
+
+    let request = ChatRequestBuilder::new().user("Hello").build();
+    let _ = ChatCompletion::chat_completion(&provider, request, "qwen2").await;
+
+    assert_eq!(server.requests()[0].header("authorization"), None);
+}
+
+// =============================================================================
+// Streaming Event Tests
+// =============================================================================
+
+#[tokio::test]
+async fn test_cancelled_stream_sends_no_request() {
+    use crate::utils::test_helpers::{MockHttpServer, MockResponse};
+    use futures::StreamExt;
+
+    let server = MockHttpServer::start(vec![MockResponse::sse(&["[DONE]"])]).await;
+    let config = OpenAICompatibleConfig::groq("test-key").with_base_url(server.base_url());
+    let provider = crate::providers::OpenAICompatibleProvider::groq(config).unwrap();
+    let request = ChatRequestBuilder::new().user("Hello").stream(true).build();
+
+    let token = tokio_util::sync::CancellationToken::new();
+    token.cancel();
+    let stream_config = StreamConfig::default().with_cancellation(token);
+    let events: Vec<_> = ChatCompletion::chat_completion_stream(
+        &provider,
+        request,
+        "llama3-8b-8192",
+        Some(stream_config),
+    )
+    .await
+    .collect()
+    .await;
+
+    assert!(events.is_empty());
+    assert!(server.requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_compatible_stream_reports_usage_and_finish() {
+    use crate::utils::test_helpers::{MockHttpServer, MockResponse};
+    use futures::StreamExt;
+
+    let server = MockHttpServer::start(vec![MockResponse::sse(&[
+        r#"{"choices":[{"index":0,"delta":{"role":"assistant","content":"Hi"}}]}"#,
+        r#"{"choices":[{"index":0,"delta":{},"finish_reason":"stop"}]}"#,
+        r#"{"choices":[],"usage":{"prompt_tokens":3,"completion_tokens":1,"total_tokens":4}}"#,
+        "[DONE]",
+    ])])
+    .await;
+
+    let config = OpenAICompatibleConfig::groq("test-key").with_base_url(server.base_url());
+    let provider = crate::providers::OpenAICompatibleProvider::groq(config).unwrap();
+    let request = ChatRequestBuilder::new().user("Hello").stream(true).build();
+
+    let events: Vec<ChatStreamEvent> =
+        ChatCompletion::chat_completion_stream(&provider, request, "llama3-8b-8192", None)
+            .await
+            .map(|event| event.unwrap())
+            .collect()
+            .await;
+
+    assert_eq!(
+        events,
+        vec![
+            ChatStreamEvent::Token("Hi".to_string()),
+            ChatStreamEvent::Finish("stop".to_string()),
+            ChatStreamEvent::Usage(TokenUsage {
+                prompt_tokens: 3,
+                completion_tokens: 1,
+                total_tokens: 4,
+            }),
+            ChatStreamEvent::Done,
+        ]
+    );
+    let body = server.requests()[0].json();
+    assert_eq!(body["stream_options"]["include_usage"], true);
+}
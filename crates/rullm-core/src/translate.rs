@@ -0,0 +1,199 @@
+//! Translation between the provider-neutral [`ChatRequest`](crate::ChatRequest) /
+//! [`ChatResponse`](crate::ChatResponse) and each provider's native types
+//!
+//! These are the conversions the [`ChatCompletion`](crate::ChatCompletion)
+//! implementations use. Call them directly to inspect or adjust a native
+//! request before sending it with a concrete client.
+//!
+//! ```rust
+//! use rullm_core::ChatRequestBuilder;
+//! use rullm_core::translate::to_anthropic_request;
+//!
+//! let request = ChatRequestBuilder::new()
+//!     .system("Answer in French")
+//!     .user("Hello")
+//!     .top_k(40)
+//!     .build();
+//! let native = to_anthropic_request(request, "claude-3-5-haiku-latest").unwrap();
+//! assert!(native.system.is_some());
+//! assert_eq!(native.top_k, Some(40));
+//! ```
+//!
+//! Sampling options a provider does not accept are rejected with
+//! [`LlmError::Validation`](crate::LlmError::Validation) rather than dropped.
+
+pub use crate::providers::anthropic::provider::{from_anthropic_response, to_anthropic_request};
+pub use crate::providers::google::provider::{from_google_response, to_google_request};
+pub use crate::providers::openai::provider::{from_openai_response, to_openai_request};
+pub use crate::providers::openai_compatible::to_compatible_request;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compat_types::{ChatRequest, ChatRequestBuilder, ChatResponse, ToolDefinition};
+    use crate::providers::{anthropic, google, openai};
+    use serde_json::json;
+
+    fn weather_tool() -> ToolDefinition {
+        ToolDefinition::new(
+            "get_weather",
+            "Current weather for a city",
+            json!({"type": "object", "properties": {"city": {"type": "string"}}}),
+        )
+    }
+
+    fn request() -> ChatRequestBuilder {
+        ChatRequestBuilder::new()
+            .system("Be brief")
+            .user("Weather in Paris?")
+            .tool(weather_tool())
+            .temperature(0.5)
+            .max_tokens(200)
+            .stop(vec!["END".to_string()])
+    }
+
+    /// Continue `request` with the assistant's reply and a tool result
+    fn follow_up(response: ChatResponse) -> ChatRequest {
+        let call_id = response.message.tool_calls[0].id.clone();
+        let mut next = request().message(response.message);
+        next = next.tool_result(call_id, r#"{"temp_c": 18}"#);
+        next.build()
+    }
+
+    /// Serialize a native value and parse it back, as if sent over the wire
+    fn wire<T: serde::Serialize + serde::de::DeserializeOwned>(value: &T) -> serde_json::Value {
+        let body = serde_json::to_value(value).unwrap();
+        let parsed: T = serde_json::from_value(body.clone()).unwrap();
+        assert_eq!(serde_json::to_value(parsed).unwrap(), body);
+        body
+    }
+
+    #[test]
+    fn test_openai_round_trip() {
+        let native = to_openai_request(request().seed(7).build(), "gpt-4o").unwrap();
+        let body = wire(&native);
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(body["stop"], json!(["END"]));
+        assert_eq!(body["seed"], 7);
+        assert_eq!(body["tools"][0]["function"]["name"], "get_weather");
+
+        let response: openai::ChatCompletionResponse = serde_json::from_value(json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1,
+            "model": "gpt-4o",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": null, "tool_calls": [{
+                    "id": "call_abc",
+                    "type": "function",
+                    "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}
+                }]},
+                "finish_reason": "tool_calls"
+            }],
+            "usage": {"prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15}
+        }))
+        .unwrap();
+        let response = from_openai_response(response).unwrap();
+        assert_eq!(response.message.tool_calls[0].arguments["city"], "Paris");
+
+        let body = wire(&to_openai_request(follow_up(response), "gpt-4o").unwrap());
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages[2]["tool_calls"][0]["id"], "call_abc");
+        assert_eq!(
+            messages[2]["tool_calls"][0]["function"]["arguments"],
+            "{\"city\":\"Paris\"}"
+        );
+        assert_eq!(messages[3]["role"], "tool");
+        assert_eq!(messages[3]["tool_call_id"], "call_abc");
+    }
+
+    #[test]
+    fn test_anthropic_round_trip() {
+        let native = to_anthropic_request(request().top_k(40).build(), "claude-3-haiku").unwrap();
+        let body = wire(&native);
+        assert_eq!(body["system"], "Be brief");
+        assert_eq!(body["max_tokens"], 200);
+        assert_eq!(body["top_k"], 40);
+        assert_eq!(body["stop_sequences"], json!(["END"]));
+
+        let response: anthropic::MessagesResponse = serde_json::from_value(json!({
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "content": [
+                {"type": "tool_use", "id": "toolu_1", "name": "get_weather", "input": {"city": "Paris"}}
+            ],
+            "model": "claude-3-haiku",
+            "stop_reason": "tool_use",
+            "stop_sequence": null,
+            "usage": {"input_tokens": 10, "output_tokens": 5}
+        }))
+        .unwrap();
+        let response = from_anthropic_response(response).unwrap();
+        assert_eq!(response.usage.total_tokens, 15);
+
+        let body = wire(&to_anthropic_request(follow_up(response), "claude-3-haiku").unwrap());
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages[1]["content"][0]["type"], "tool_use");
+        assert_eq!(messages[1]["content"][0]["input"]["city"], "Paris");
+        assert_eq!(messages[2]["content"][0]["type"], "tool_result");
+        assert_eq!(messages[2]["content"][0]["tool_use_id"], "toolu_1");
+    }
+
+    #[test]
+    fn test_google_round_trip() {
+        let native = to_google_request(request().top_k(40).build()).unwrap();
+        let body = wire(&native);
+        assert_eq!(body["systemInstruction"]["parts"][0]["text"], "Be brief");
+        assert_eq!(body["generationConfig"]["maxOutputTokens"], 200);
+        assert_eq!(body["generationConfig"]["topK"], 40);
+        assert_eq!(body["generationConfig"]["stopSequences"], json!(["END"]));
+
+        let response: google::GenerateContentResponse = serde_json::from_value(json!({
+            "candidates": [{
+                "content": {"role": "model", "parts": [
+                    {"functionCall": {"name": "get_weather", "args": {"city": "Paris"}}}
+                ]},
+                "finishReason": "STOP"
+            }],
+            "usageMetadata": {"promptTokenCount": 10, "candidatesTokenCount": 5, "totalTokenCount": 15}
+        }))
+        .unwrap();
+        let response = from_google_response(response, "gemini-2.0-flash").unwrap();
+        assert_eq!(response.usage.total_tokens, 15);
+
+        let body = wire(&to_google_request(follow_up(response)).unwrap());
+        let contents = body["contents"].as_array().unwrap();
+        assert_eq!(contents[1]["role"], "model");
+        assert_eq!(
+            contents[1]["parts"][0]["function_call"]["args"]["city"],
+            "Paris"
+        );
+        assert_eq!(
+            contents[2]["parts"][0]["function_response"],
+            json!({"name": "get_weather", "response": {"temp_c": 18}})
+        );
+    }
+
+    #[test]
+    fn test_unsupported_sampling_is_rejected() {
+        let err = to_openai_request(request().top_k(40).build(), "gpt-4o").unwrap_err();
+        assert!(err.to_string().contains("top_k"));
+        assert!(to_anthropic_request(request().seed(1).build(), "claude-3-haiku").is_err());
+        assert!(to_google_request(request().presence_penalty(0.5).build()).is_err());
+    }
+
+    #[test]
+    fn test_extra_params_are_rejected_by_native_clients() {
+        let extra = || {
+            request()
+                .extra_param("reasoning_effort", json!("low"))
+                .build()
+        };
+        let err = to_openai_request(extra(), "gpt-4o").unwrap_err();
+        assert!(err.to_string().contains("extra_params"));
+        assert!(to_anthropic_request(extra(), "claude-3-haiku").is_err());
+        assert!(to_google_request(extra()).is_err());
+    }
+}
@@ -0,0 +1,82 @@
+//! Provider-neutral traits shared by every client
+//!
+//! [`LlmProvider`] describes a provider (identity, model listing, health) and
+//! [`ChatCompletion`] adds chat on top of it using the [`compat_types`](crate::compat_types)
+//! request/response types. All concrete clients implement both, so callers can
+//! hold a `Box<dyn ChatCompletion>` and pick the vendor at runtime.
+
+use crate::error::LlmError;
+use futures::Stream;
+use std::pin::Pin;
+use tokio_util::sync::CancellationToken;
+
+pub use crate::compat_types::{
+    ChatMessage, ChatRequest, ChatRequestBuilder, ChatResponse, ChatRole, ChatStreamEvent,
+    TokenUsage,
+};
+
+/// Boxed stream of results returned by streaming calls
+pub type StreamResult<T> = Pin<Box<dyn Stream<Item = Result<T, LlmError>> + Send>>;
+
+/// Options for streaming requests
+#[derive(Debug, Clone, Default)]
+pub struct StreamConfig {
+    /// Buffer size hint for providers that buffer events internally
+    pub buffer_size: Option<usize>,
+    /// Ends the stream and drops its HTTP connection when cancelled
+    pub cancel: Option<CancellationToken>,
+}
+
+impl StreamConfig {
+    /// End the stream as soon as `token` is cancelled
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+}
+
+/// Identity and housekeeping operations shared by all providers
+#[async_trait::async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// Canonical provider name (e.g. "openai")
+    fn name(&self) -> &'static str;
+
+    /// Alternative names the provider can be referred to by
+    fn aliases(&self) -> &'static [&'static str];
+
+    /// Default API base URL, if the provider has one
+    fn default_base_url(&self) -> Option<&'static str>;
+
+    /// Environment variable holding the API key
+    fn env_key(&self) -> &'static str;
+
+    /// List the models available to the configured account
+    async fn available_models(&self) -> Result<Vec<String>, LlmError>;
+
+    /// Check that the provider is reachable with the configured credentials
+    async fn health_check(&self) -> Result<(), LlmError>;
+}
+
+/// Provider-neutral chat completion
+#[async_trait::async_trait]
+pub trait ChatCompletion: LlmProvider {
+    /// Send a chat request and wait for the full response
+    async fn chat_completion(
+        &self,
+        request: ChatRequest,
+        model: &str,
+    ) -> Result<ChatResponse, LlmError>;
+
+    /// Send a chat request and stream the response token by token
+    async fn chat_completion_stream(
+        &self,
+        request: ChatRequest,
+        model: &str,
+        config: Option<StreamConfig>,
+    ) -> StreamResult<ChatStreamEvent>;
+
+    /// Rough token estimate for `text`, roughly four characters per token
+    async fn estimate_tokens(&self, text: &str, _model: &str) -> Result<u32, LlmError> {
+        Ok((text.chars().count() as f32 / 4.0).ceil() as u32)
+    }
+}
@@ -0,0 +1,560 @@
+//! Shared HTTP helpers for provider clients
+
+use crate::config::{DEFAULT_CONNECT_TIMEOUT_SECONDS, ProviderConfig, RetryPolicy};
+use crate::error::LlmError;
+use futures::{Stream, StreamExt};
+use once_cell::sync::Lazy;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Client shared by every provider using the default connect timeout, so
+/// they all draw from one connection pool
+static SHARED_CLIENT: Lazy<Result<Client, String>> = Lazy::new(|| {
+    build_client(Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECONDS)).map_err(|e| e.to_string())
+});
+
+fn build_client(connect_timeout: Duration) -> Result<Client, reqwest::Error> {
+    Client::builder()
+        .connect_timeout(connect_timeout)
+        .pool_idle_timeout(Duration::from_secs(90))
+        .tcp_keepalive(Duration::from_secs(60))
+        .build()
+}
+
+/// HTTP client for a provider config
+///
+/// Returns a handle to the shared client unless the config asks for a
+/// non-default connect timeout. Callers that need their own connection pool,
+/// proxies or TLS settings pass a `reqwest::Client` to a provider's
+/// `with_client` constructor instead.
+pub(crate) fn client_for(config: &dyn ProviderConfig) -> Result<Client, LlmError> {
+    let connect_timeout = config.connect_timeout();
+    if connect_timeout == Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECONDS) {
+        return SHARED_CLIENT
+            .clone()
+            .map_err(|e| LlmError::configuration(format!("Failed to build HTTP client: {e}")));
+    }
+    build_client(connect_timeout)
+        .map_err(|e| LlmError::configuration(format!("Failed to build HTTP client: {e}")))
+}
+
+/// End a stream with [`LlmError::Timeout`] if no item arrives within `idle`
+pub(crate) fn with_idle_timeout<S, T>(
+    stream: S,
+    idle: Duration,
+) -> impl Stream<Item = Result<T, LlmError>> + Send
+where
+    S: Stream<Item = Result<T, LlmError>> + Send,
+    T: Send,
+{
+    async_stream::stream! {
+        futures::pin_mut!(stream);
+        loop {
+            match tokio::time::timeout(idle, stream.next()).await {
+                Ok(Some(item)) => yield item,
+                Ok(None) => break,
+                Err(_) => {
+                    yield Err(LlmError::timeout(idle));
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Anthropic's "overloaded" status code
+const STATUS_OVERLOADED: u16 = 529;
+
+/// Whether a response status is worth retrying
+fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status.as_u16(),
+        408 | 429 | 500 | 502 | 503 | 504 | STATUS_OVERLOADED
+    )
+}
+
+/// Whether a transport error is worth retrying
+fn is_retryable_error(error: &LlmError) -> bool {
+    matches!(error, LlmError::Timeout { .. } | LlmError::Network { .. })
+}
+
+/// Send a single request, failing with [`LlmError::Timeout`] if no response
+/// arrives within `timeout`
+async fn send_within(request: RequestBuilder, timeout: Duration) -> Result<Response, LlmError> {
+    match tokio::time::timeout(timeout, request.send()).await {
+        Ok(Ok(response)) => Ok(response),
+        Ok(Err(e)) if e.is_timeout() => Err(LlmError::timeout(timeout)),
+        Ok(Err(e)) => Err(e.into()),
+        Err(_) => Err(LlmError::timeout(timeout)),
+    }
+}
+
+/// Parse a `Retry-After` header given in seconds or as an HTTP date
+pub(crate) fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?;
+    parse_retry_after(value, SystemTime::now())
+}
+
+/// Delay until `value` relative to `now`; dates in the past mean no delay
+fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<f64>() {
+        return Duration::try_from_secs_f64(secs).ok();
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(now).unwrap_or(Duration::ZERO))
+}
+
+/// Send a request, retrying transient failures according to `policy`
+///
+/// Each attempt must receive response headers within `timeout`; timed out
+/// attempts are retried like connection errors. Returns the response once it
+/// succeeds. Non-success responses are turned
+/// into an [`LlmError`] with [`error_from_response`] once they are either not
+/// retryable or the retries are exhausted. Requests whose body cannot be
+/// cloned are sent exactly once.
+pub(crate) async fn send_with_retry(
+    policy: &RetryPolicy,
+    provider: &str,
+    request: RequestBuilder,
+    timeout: Duration,
+) -> Result<Response, LlmError> {
+    let mut retry = 0;
+
+    loop {
+        let attempt = match request.try_clone() {
+            Some(attempt) => attempt,
+            None => {
+                let start = Instant::now();
+                let response = send_within(request, timeout).await?;
+                return check_status(provider, response, start.elapsed()).await;
+            }
+        };
+        let can_retry = retry < policy.max_retries;
+
+        let start = Instant::now();
+        let response = match send_within(attempt, timeout).await {
+            Ok(response) => response,
+            Err(e) if can_retry && is_retryable_error(&e) => {
+                let delay = policy.backoff(retry);
+                log::debug!("{provider}: {e}, retrying in {delay:?}");
+                tokio::time::sleep(delay).await;
+                retry += 1;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        let elapsed = start.elapsed();
+        let status = response.status();
+        if status.is_success() || !can_retry || !is_retryable_status(status) {
+            return check_status(provider, response, elapsed).await;
+        }
+
+        let delay = match retry_after(&response) {
+            // The server asked us to wait longer than we are willing to
+            Some(delay) if delay > policy.max_delay => {
+                return check_status(provider, response, elapsed).await;
+            }
+            Some(delay) => delay,
+            None => policy.backoff(retry),
+        };
+        log::debug!("{provider}: received {status}, retrying in {delay:?}");
+        tokio::time::sleep(delay).await;
+        retry += 1;
+    }
+}
+
+/// Pass successful responses through and convert everything else into an error
+async fn check_status(
+    provider: &str,
+    response: Response,
+    elapsed: Duration,
+) -> Result<Response, LlmError> {
+    if response.status().is_success() {
+        Ok(response)
+    } else {
+        Err(error_from_response(provider, response, elapsed).await)
+    }
+}
+
+/// Convert a non-success response into an [`LlmError`]
+///
+/// `elapsed` is how long the request took and is reported for `408` responses.
+pub(crate) async fn error_from_response(
+    provider: &str,
+    response: Response,
+    elapsed: Duration,
+) -> LlmError {
+    let status = response.status();
+    let retry_after = retry_after(&response);
+    let body = response.text().await.unwrap_or_default();
+    decode_error(provider, status, &body, retry_after, elapsed)
+}
+
+/// Map an HTTP status and error body onto the matching [`LlmError`] variant
+///
+/// Understands the error envelopes used by all supported providers, which
+/// all nest the details under a top-level `error` key:
+///
+/// - OpenAI: `{"error": {"message", "type", "param", "code"}}`
+/// - Anthropic: `{"type": "error", "error": {"type", "message"}}`
+/// - Google: `{"error": {"code", "message", "status", "details"}}`
+///
+/// The provider's own error code (`status` for Google, `code` or `type`
+/// otherwise) ends up in `code`, and the remaining fields in `details`.
+pub(crate) fn decode_error(
+    provider: &str,
+    status: StatusCode,
+    body: &str,
+    retry_after: Option<Duration>,
+    elapsed: Duration,
+) -> LlmError {
+    let error = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|json| json.get("error").cloned());
+
+    let message = error
+        .as_ref()
+        .and_then(|e| e.get("message"))
+        .and_then(|m| m.as_str())
+        .map(|m| m.to_string())
+        .or_else(|| Some(body.trim().to_string()).filter(|b| !b.is_empty()))
+        .unwrap_or_else(|| status.to_string());
+
+    let error_code = error.as_ref().and_then(|e| {
+        ["status", "code", "type"]
+            .iter()
+            .find_map(|key| e.get(*key).and_then(|v| v.as_str()))
+            .map(|code| code.to_string())
+    });
+
+    let mut details: HashMap<String, serde_json::Value> = HashMap::new();
+    details.insert("http_status".to_string(), status.as_u16().into());
+    match error {
+        Some(serde_json::Value::Object(fields)) => {
+            details.extend(fields.into_iter().filter(|(key, _)| key != "message"));
+        }
+        _ if !body.trim().is_empty() => {
+            details.insert("body".to_string(), body.trim().into());
+        }
+        _ => {}
+    }
+
+    let is_quota = matches!(
+        error_code.as_deref(),
+        Some("insufficient_quota" | "billing_error")
+    );
+
+    match status.as_u16() {
+        401 | 403 => LlmError::authentication(message),
+        402 => LlmError::resource(message),
+        404 => LlmError::model(message),
+        408 => LlmError::timeout(elapsed),
+        429 if is_quota => LlmError::resource(message),
+        429 => LlmError::rate_limit(message, retry_after),
+        _ if is_quota => LlmError::resource(message),
+        503 | STATUS_OVERLOADED => LlmError::service_unavailable_with_message(
+            provider,
+            message,
+            Some(error_code.unwrap_or_else(|| status.as_u16().to_string())),
+        ),
+        _ => LlmError::api(
+            provider,
+            message,
+            Some(error_code.unwrap_or_else(|| status.as_u16().to_string())),
+            Some(details),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_helpers::{MockHttpServer, MockResponse};
+
+    fn fast_policy(max_retries: u32) -> RetryPolicy {
+        RetryPolicy::default()
+            .with_max_retries(max_retries)
+            .with_base_delay(Duration::from_millis(1))
+            .with_jitter(false)
+    }
+
+    #[tokio::test]
+    async fn test_retries_until_success() {
+        let server = MockHttpServer::start(vec![
+            MockResponse::new(429, "slow down").with_header("Retry-After", "0"),
+            MockResponse::new(529, "overloaded"),
+            MockResponse::new(200, "ok"),
+        ])
+        .await;
+
+        let request = reqwest::Client::new()
+            .post(server.url("/v1/test"))
+            .body("{}");
+        let response = send_with_retry(&fast_policy(3), "test", request, Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        assert_eq!(response.text().await.unwrap(), "ok");
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_retries_surface_rate_limit() {
+        let server = MockHttpServer::start(vec![
+            MockResponse::new(429, "slow down").with_header("Retry-After", "0"),
+            MockResponse::new(429, "still slow").with_header("Retry-After", "0"),
+        ])
+        .await;
+
+        let request = reqwest::Client::new().get(server.url("/"));
+        let error = send_with_retry(&fast_policy(1), "test", request, Duration::from_secs(5))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            LlmError::RateLimit { retry_after: Some(d), .. } if d == Duration::ZERO
+        ));
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_are_not_retried() {
+        let server = MockHttpServer::start(vec![MockResponse::new(400, "bad request")]).await;
+
+        let request = reqwest::Client::new().get(server.url("/"));
+        let error = send_with_retry(&fast_policy(3), "test", request, Duration::from_secs(5))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, LlmError::Api { code: Some(ref c), .. } if c == "400"));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn test_retry_after_accepts_seconds_and_dates() {
+        let now = httpdate::parse_http_date("Wed, 21 Oct 2015 07:28:00 GMT").unwrap();
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+        assert_eq!(parse_retry_after("-1", now), None);
+        assert_eq!(parse_retry_after("1e30", now), None);
+        assert_eq!(parse_retry_after("inf", now), None);
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_beyond_max_delay_gives_up() {
+        let server = MockHttpServer::start(vec![
+            MockResponse::new(429, "come back later").with_header("Retry-After", "3600"),
+        ])
+        .await;
+
+        let request = reqwest::Client::new().get(server.url("/"));
+        let error = send_with_retry(&fast_policy(3), "test", request, Duration::from_secs(5))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            LlmError::RateLimit { retry_after: Some(d), .. } if d == Duration::from_secs(3600)
+        ));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    fn decode(status: u16, body: &str) -> LlmError {
+        decode_error(
+            "test",
+            StatusCode::from_u16(status).unwrap(),
+            body,
+            None,
+            Duration::from_secs(5),
+        )
+    }
+
+    #[test]
+    fn test_decode_openai_error() {
+        let error = decode(
+            400,
+            r#"{"error": {"message": "Unknown parameter: 'foo'.", "type": "invalid_request_error", "param": "foo", "code": "unknown_parameter"}}"#,
+        );
+
+        match error {
+            LlmError::Api {
+                message,
+                code,
+                details,
+                ..
+            } => {
+                assert_eq!(message, "Unknown parameter: 'foo'.");
+                assert_eq!(code.as_deref(), Some("unknown_parameter"));
+                let details = details.unwrap();
+                assert_eq!(details["param"], "foo");
+                assert_eq!(details["type"], "invalid_request_error");
+                assert_eq!(details["http_status"], 400);
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_decode_anthropic_error() {
+        let error = decode(
+            400,
+            r#"{"type": "error", "error": {"type": "invalid_request_error", "message": "max_tokens: Field required"}}"#,
+        );
+
+        assert!(matches!(
+            error,
+            LlmError::Api { ref message, code: Some(ref c), .. }
+                if message == "max_tokens: Field required" && c == "invalid_request_error"
+        ));
+    }
+
+    #[test]
+    fn test_decode_google_error() {
+        let error = decode(
+            400,
+            r#"{"error": {"code": 400, "message": "API key not valid.", "status": "INVALID_ARGUMENT", "details": [{"reason": "API_KEY_INVALID"}]}}"#,
+        );
+
+        match error {
+            LlmError::Api { code, details, .. } => {
+                assert_eq!(code.as_deref(), Some("INVALID_ARGUMENT"));
+                assert_eq!(details.unwrap()["details"][0]["reason"], "API_KEY_INVALID");
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_decode_maps_status_codes() {
+        let auth = decode(
+            401,
+            r#"{"error": {"message": "Incorrect API key provided", "type": "invalid_request_error"}}"#,
+        );
+        assert!(
+            matches!(auth, LlmError::Authentication { ref message } if message == "Incorrect API key provided")
+        );
+        assert!(matches!(
+            decode(403, "forbidden"),
+            LlmError::Authentication { .. }
+        ));
+        assert!(matches!(
+            decode(
+                404,
+                r#"{"error": {"type": "not_found_error", "message": "model: foo"}}"#
+            ),
+            LlmError::Model { .. }
+        ));
+        assert!(matches!(
+            decode(408, ""),
+            LlmError::Timeout { duration } if duration == Duration::from_secs(5)
+        ));
+        assert!(matches!(decode(429, ""), LlmError::RateLimit { .. }));
+        assert!(matches!(
+            decode(
+                429,
+                r#"{"error": {"message": "You exceeded your current quota", "type": "insufficient_quota", "code": "insufficient_quota"}}"#
+            ),
+            LlmError::Resource { .. }
+        ));
+        assert!(matches!(
+            decode(402, "no credits"),
+            LlmError::Resource { .. }
+        ));
+        assert!(matches!(
+            decode(
+                529,
+                r#"{"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}"#
+            ),
+            LlmError::ServiceUnavailable { message: Some(ref message), ref code, .. }
+                if message == "Overloaded" && code.as_deref() == Some("overloaded_error")
+        ));
+        assert!(matches!(
+            decode(503, ""),
+            LlmError::ServiceUnavailable { ref code, .. } if code.as_deref() == Some("503")
+        ));
+        assert!(matches!(
+            decode(502, "bad gateway"),
+            LlmError::Api { ref message, .. } if message == "bad gateway"
+        ));
+        assert!(matches!(
+            decode(500, "internal"),
+            LlmError::Api { ref message, .. } if message == "internal"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unresponsive_server_times_out() {
+        // Accepts connections but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut sockets = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                sockets.push(socket);
+            }
+        });
+
+        let request = reqwest::Client::new().get(url);
+        let error = send_with_retry(
+            &RetryPolicy::none(),
+            "test",
+            request,
+            Duration::from_millis(50),
+        )
+        .await
+        .unwrap_err();
+
+        assert!(
+            matches!(error, LlmError::Timeout { duration } if duration == Duration::from_millis(50))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_idle_stream_times_out() {
+        let stream = futures::stream::iter(vec![Ok::<_, LlmError>("first")])
+            .chain(futures::stream::pending());
+        let items: Vec<_> = with_idle_timeout(stream, Duration::from_millis(20))
+            .collect()
+            .await;
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].as_ref().unwrap(), &"first");
+        assert!(matches!(items[1], Err(LlmError::Timeout { .. })));
+    }
+
+    #[test]
+    fn test_backoff_grows_exponentially_and_caps() {
+        let policy = RetryPolicy::default()
+            .with_base_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_millis(500))
+            .with_jitter(false);
+
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff(3), Duration::from_millis(500));
+        assert_eq!(policy.backoff(40), Duration::from_millis(500));
+
+        let jittered = policy.with_jitter(true).backoff(1);
+        assert!(jittered >= Duration::from_millis(100) && jittered <= Duration::from_millis(200));
+    }
+}
@@ -1,3 +1,4 @@
+pub(crate) mod http;
 pub mod sse;
 
 #[cfg(test)]
@@ -1,21 +1,66 @@
+//! Server-Sent Events parsing following the WHATWG event stream format
+//!
+//! Raw bytes are buffered until a full line is available, so events and
+//! multibyte characters may be split across network chunks anywhere. Lines
+//! may end in CRLF, LF or CR, `:` lines are comments, multi-line `data:`
+//! fields are joined with `\n`, and `event:`, `id:` and `retry:` are kept on
+//! the emitted [`SseEvent`].
+
 use crate::error::LlmError;
-use futures::Stream;
+use futures::{Stream, StreamExt, future};
+use std::collections::VecDeque;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
+/// A dispatched Server-Sent Event
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    /// Value of the `event:` field, `None` for the default `message` type
+    pub event: Option<String>,
+    /// `data:` lines joined with `\n`
+    pub data: String,
+    /// Last event id seen on the stream, which carries over between events
+    pub id: Option<String>,
+    /// Reconnection time in milliseconds, if this event set one
+    pub retry: Option<u64>,
+}
+
+/// Parses Server-Sent Events (SSE) from a byte stream
+pub fn sse_events<S>(stream: S) -> impl Stream<Item = Result<SseEvent, LlmError>>
+where
+    S: Stream<Item = Result<bytes::Bytes, reqwest::Error>> + Unpin,
+{
+    SseParser::new(stream)
+}
+
 /// Parses Server-Sent Events (SSE) from a byte stream, extracting data payloads
 /// and filtering out [DONE] messages.
 pub fn sse_lines<S>(stream: S) -> impl Stream<Item = Result<String, LlmError>>
 where
     S: Stream<Item = Result<bytes::Bytes, reqwest::Error>> + Unpin,
 {
-    SseParser::new(stream)
+    SseParser::new(stream).filter_map(|event| {
+        future::ready(match event {
+            Ok(event) if event.data.trim() == "[DONE]" => None,
+            Ok(event) => Some(Ok(event.data)),
+            Err(e) => Some(Err(e)),
+        })
+    })
 }
 
 struct SseParser<S> {
     stream: S,
-    buffer: String,
-    event_queue: Vec<String>,
+    /// Bytes not yet terminated by a line ending
+    buffer: Vec<u8>,
+    /// The previous line ended in CR, so a leading LF belongs to it
+    skip_lf: bool,
+    /// The UTF-8 byte order mark, if any, has been handled
+    started: bool,
+    /// Fields of the event being assembled
+    pending: SseEvent,
+    has_data: bool,
+    last_id: Option<String>,
+    ready: VecDeque<SseEvent>,
 }
 
 impl<S> SseParser<S>
@@ -25,28 +70,93 @@ where
     fn new(stream: S) -> Self {
         Self {
             stream,
-            buffer: String::new(),
-            event_queue: Vec::new(),
+            buffer: Vec::new(),
+            skip_lf: false,
+            started: false,
+            pending: SseEvent::default(),
+            has_data: false,
+            last_id: None,
+            ready: VecDeque::new(),
         }
     }
 
-    fn parse_events(&mut self) {
-        // Split by SSE event delimiter "\n\n"
-        while let Some(double_newline_pos) = self.buffer.find("\n\n") {
-            let event_block = self.buffer[..double_newline_pos].to_string();
-            self.buffer.drain(..double_newline_pos + 2);
-
-            // Process the event block
-            for line in event_block.lines() {
-                if let Some(data) = line.strip_prefix("data: ") {
-                    // Skip [DONE] messages
-                    if data.trim() != "[DONE]" {
-                        self.event_queue.push(data.to_string());
-                    }
+    /// Process every complete line in the buffer
+    fn parse_lines(&mut self) {
+        if !self.started {
+            if self.buffer.len() < 3 && b"\xEF\xBB\xBF".starts_with(&self.buffer) {
+                return;
+            }
+            if self.buffer.starts_with(b"\xEF\xBB\xBF") {
+                self.buffer.drain(..3);
+            }
+            self.started = true;
+        }
+
+        let mut start = 0;
+        if self.skip_lf && !self.buffer.is_empty() {
+            if self.buffer[0] == b'\n' {
+                start = 1;
+            }
+            self.skip_lf = false;
+        }
+
+        while let Some(offset) = self.buffer[start..]
+            .iter()
+            .position(|&b| b == b'\n' || b == b'\r')
+        {
+            let end = start + offset;
+            let line = String::from_utf8_lossy(&self.buffer[start..end]).into_owned();
+            self.process_line(&line);
+
+            start = end + 1;
+            if self.buffer[end] == b'\r' {
+                match self.buffer.get(start) {
+                    Some(b'\n') => start += 1,
+                    Some(_) => {}
+                    None => self.skip_lf = true,
                 }
-                // Ignore lines without "data: " prefix
             }
         }
+        self.buffer.drain(..start);
+    }
+
+    fn process_line(&mut self, line: &str) {
+        if line.is_empty() {
+            self.dispatch();
+            return;
+        }
+        if line.starts_with(':') {
+            return;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.pending.event = Some(value.to_string()),
+            "data" => {
+                if self.has_data {
+                    self.pending.data.push('\n');
+                }
+                self.pending.data.push_str(value);
+                self.has_data = true;
+            }
+            "id" if !value.contains('\0') => self.last_id = Some(value.to_string()),
+            "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                self.pending.retry = value.parse().ok();
+            }
+            _ => {}
+        }
+    }
+
+    /// Emit the assembled event; events without data are dropped
+    fn dispatch(&mut self) {
+        let mut event = std::mem::take(&mut self.pending);
+        if std::mem::take(&mut self.has_data) {
+            event.id = self.last_id.clone();
+            self.ready.push_back(event);
+        }
     }
 }
 
@@ -54,51 +164,24 @@ impl<S> Stream for SseParser<S>
 where
     S: Stream<Item = Result<bytes::Bytes, reqwest::Error>> + Unpin,
 {
-    type Item = Result<String, LlmError>;
+    type Item = Result<SseEvent, LlmError>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         loop {
-            // First, check if we have events in the queue
-            if !self.event_queue.is_empty() {
-                return Poll::Ready(Some(Ok(self.event_queue.remove(0))));
-            }
-
-            // Parse any complete events from the buffer
-            self.parse_events();
-            if !self.event_queue.is_empty() {
-                return Poll::Ready(Some(Ok(self.event_queue.remove(0))));
+            if let Some(event) = self.ready.pop_front() {
+                return Poll::Ready(Some(Ok(event)));
             }
 
-            // No complete events in buffer, try to get more data
             match Pin::new(&mut self.stream).poll_next(cx) {
                 Poll::Ready(Some(Ok(bytes))) => {
-                    // Add new bytes to buffer
-                    match std::str::from_utf8(&bytes) {
-                        Ok(text) => {
-                            // Normalize CRLF to LF to handle Windows-style/HTTP CRLF delimiters
-                            let normalized = text.replace("\r\n", "\n");
-                            self.buffer.push_str(&normalized);
-                            // Continue loop to try parsing again
-                        }
-                        Err(e) => {
-                            return Poll::Ready(Some(Err(LlmError::serialization(
-                                "Invalid UTF-8 in SSE stream",
-                                Box::new(e),
-                            ))));
-                        }
-                    }
+                    self.buffer.extend_from_slice(&bytes);
+                    self.parse_lines();
                 }
                 Poll::Ready(Some(Err(e))) => {
                     return Poll::Ready(Some(Err(LlmError::network(format!("Stream error: {e}")))));
                 }
-                Poll::Ready(None) => {
-                    // Stream ended, parse any remaining events
-                    self.parse_events();
-                    if !self.event_queue.is_empty() {
-                        return Poll::Ready(Some(Ok(self.event_queue.remove(0))));
-                    }
-                    return Poll::Ready(None);
-                }
+                // An event not terminated by a blank line is incomplete and dropped
+                Poll::Ready(None) => return Poll::Ready(None),
                 Poll::Pending => return Poll::Pending,
             }
         }
@@ -256,4 +339,132 @@ mod tests {
         assert!(parsed_events[1].contains(" there"));
         assert!(parsed_events[2].contains("!"));
     }
+
+    /// Parse `chunks` as raw SSE bytes
+    async fn parse(chunks: Vec<Vec<u8>>) -> Vec<SseEvent> {
+        let chunks = chunks
+            .into_iter()
+            .map(|chunk| Ok(bytes::Bytes::from(chunk)));
+        sse_events(stream::iter(chunks))
+            .map(|event| event.unwrap())
+            .collect()
+            .await
+    }
+
+    fn data(data: &str) -> SseEvent {
+        SseEvent {
+            data: data.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_event_fields() {
+        let raw = "event: ping\nid: 7\nretry: 1500\ndata: a\ndata:b\n\ndata: next\n\n";
+        let events = parse(vec![raw.as_bytes().to_vec()]).await;
+
+        assert_eq!(
+            events,
+            vec![
+                SseEvent {
+                    event: Some("ping".to_string()),
+                    data: "a\nb".to_string(),
+                    id: Some("7".to_string()),
+                    retry: Some(1500),
+                },
+                SseEvent {
+                    id: Some("7".to_string()),
+                    ..data("next")
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_comments_and_events_without_data_are_skipped() {
+        let raw = ": keep-alive\n\nevent: empty\n\nretry: soon\ndata\n\ndata: cut off";
+        let events = parse(vec![raw.as_bytes().to_vec()]).await;
+
+        // A bare `data` line is an empty payload; the unterminated event is dropped
+        assert_eq!(events, vec![data("")]);
+    }
+
+    #[tokio::test]
+    async fn test_line_endings_and_bom() {
+        let raw = b"\xEF\xBB\xBFdata: crlf\r\n\r\ndata: cr\r\rdata: lf\n\n";
+        let expected = vec![data("crlf"), data("cr"), data("lf")];
+
+        assert_eq!(parse(vec![raw.to_vec()]).await, expected);
+        // One byte at a time splits the BOM and every CRLF pair
+        let bytes = raw.iter().map(|&b| vec![b]).collect();
+        assert_eq!(parse(bytes).await, expected);
+    }
+
+    #[tokio::test]
+    async fn test_multibyte_characters_split_at_every_boundary() {
+        let events = ["héllo wörld", "日本語のテキスト", "🦀 crab 🦀"];
+        let total: usize = events.iter().map(|e| e.len() + 8).sum();
+
+        for chunk_size in 1..=total {
+            let stream = fake_sse_response_chunked(&events, chunk_size);
+            let parsed: Vec<String> = sse_lines(stream)
+                .map(|event| event.unwrap())
+                .collect()
+                .await;
+            assert_eq!(parsed, events, "chunk size {chunk_size}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_random_streams_parse_like_whole_input() {
+        use rand::{Rng, SeedableRng, rngs::StdRng};
+
+        let mut rng = StdRng::seed_from_u64(0x55e);
+        let words = ["alpha", "β", "ünï", "🦀", "{\"k\": 1}", "a:b", " lead"];
+        let endings = ["\n", "\r\n", "\r"];
+
+        for _ in 0..200 {
+            let mut raw = String::new();
+            let mut expected = Vec::new();
+            for _ in 0..rng.gen_range(1..5) {
+                let mut event = SseEvent::default();
+                if rng.gen_bool(0.3) {
+                    raw.push_str(": comment");
+                    raw.push_str(endings[rng.gen_range(0..3)]);
+                }
+                if rng.gen_bool(0.5) {
+                    let name = words[rng.gen_range(0..words.len())];
+                    raw.push_str(&format!("event: {name}"));
+                    raw.push_str(endings[rng.gen_range(0..3)]);
+                    event.event = Some(name.to_string());
+                }
+                let lines: Vec<&str> = (0..rng.gen_range(1..4))
+                    .map(|_| words[rng.gen_range(0..words.len())])
+                    .collect();
+                for line in &lines {
+                    raw.push_str(&format!("data: {line}"));
+                    raw.push_str(endings[rng.gen_range(0..3)]);
+                }
+                // After a lone CR, an LF would merge into one CRLF line ending
+                let blank = if raw.ends_with('\r') {
+                    "\r"
+                } else {
+                    endings[rng.gen_range(0..3)]
+                };
+                raw.push_str(blank);
+                event.data = lines.join("\n");
+                expected.push(event);
+            }
+
+            let bytes = raw.as_bytes();
+            let mut chunks = Vec::new();
+            let mut position = 0;
+            while position < bytes.len() {
+                let end = (position + rng.gen_range(1..8)).min(bytes.len());
+                chunks.push(bytes[position..end].to_vec());
+                position = end;
+            }
+            assert_eq!(parse(chunks).await, expected, "input {raw:?}");
+        }
+    }
 }
@@ -1,11 +1,19 @@
-//! Test utilities for simulating Server-Sent Events (SSE) responses
+//! Test utilities for simulating Server-Sent Events (SSE) responses and HTTP servers
 //!
 //! This module provides helpers for creating realistic SSE streams in unit tests,
-//! allowing testing of streaming parsers with various edge cases and chunk boundaries.
+//! allowing testing of streaming parsers with various edge cases and chunk boundaries,
+//! a tiny local HTTP server for exercising provider clients end to end, and a
+//! scripted in-process provider for code that sits above the providers.
 
+use crate::compat_types::{ChatMessage, ChatRequest, ChatResponse, ChatStreamEvent, TokenUsage};
+use crate::error::LlmError;
+use crate::types::{ChatCompletion, LlmProvider, StreamConfig, StreamResult};
 use futures::Stream;
+use std::collections::VecDeque;
 use std::pin::Pin;
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 /// Configuration for fake SSE response generation
 #[derive(Debug, Clone, Default)]
@@ -124,6 +132,302 @@ pub fn fake_sse_response_with_done(
     )
 }
 
+/// A canned HTTP response served by [`MockHttpServer`]
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl MockResponse {
+    pub fn new(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    /// A 200 response with a JSON body
+    pub fn json(body: serde_json::Value) -> Self {
+        Self::new(200, body.to_string()).with_header("Content-Type", "application/json")
+    }
+
+    /// A 200 `text/event-stream` response with one `data:` line per event
+    pub fn sse(events: &[&str]) -> Self {
+        let body: String = events
+            .iter()
+            .map(|event| format!("data: {event}\n\n"))
+            .collect();
+        Self::new(200, body).with_header("Content-Type", "text/event-stream")
+    }
+
+    pub fn with_header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((key.into(), value.into()));
+        self
+    }
+}
+
+/// A request captured by [`MockHttpServer`]
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    /// Look up a header by case-insensitive name
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Parse the request body as JSON
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_str(&self.body).expect("request body is not JSON")
+    }
+}
+
+/// Minimal HTTP/1.1 server on localhost that replays canned responses in order
+/// and records every request it receives
+///
+/// Once the canned responses run out it answers with `500`.
+pub struct MockHttpServer {
+    addr: std::net::SocketAddr,
+    requests: std::sync::Arc<std::sync::Mutex<Vec<RecordedRequest>>>,
+}
+
+impl MockHttpServer {
+    pub async fn start(responses: Vec<MockResponse>) -> Self {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+
+        tokio::spawn(async move {
+            let mut responses = responses.into_iter();
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = Vec::new();
+                let mut chunk = [0u8; 4096];
+                let header_end = loop {
+                    let n = socket.read(&mut chunk).await.unwrap_or(0);
+                    if n == 0 {
+                        break None;
+                    }
+                    buf.extend_from_slice(&chunk[..n]);
+                    if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                        break Some(pos);
+                    }
+                };
+                let Some(header_end) = header_end else {
+                    continue;
+                };
+
+                let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+                let mut lines = head.split("\r\n");
+                let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+                let method = request_line.next().unwrap_or_default().to_string();
+                let path = request_line.next().unwrap_or_default().to_string();
+                let headers: Vec<(String, String)> = lines
+                    .filter_map(|line| line.split_once(':'))
+                    .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+                    .collect();
+                let content_length = headers
+                    .iter()
+                    .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+                    .and_then(|(_, v)| v.parse::<usize>().ok())
+                    .unwrap_or(0);
+
+                let mut body = buf[header_end + 4..].to_vec();
+                while body.len() < content_length {
+                    let n = socket.read(&mut chunk).await.unwrap_or(0);
+                    if n == 0 {
+                        break;
+                    }
+                    body.extend_from_slice(&chunk[..n]);
+                }
+
+                recorded.lock().unwrap().push(RecordedRequest {
+                    method,
+                    path,
+                    headers,
+                    body: String::from_utf8_lossy(&body).to_string(),
+                });
+
+                let response = responses
+                    .next()
+                    .unwrap_or_else(|| MockResponse::new(500, "no more mock responses"));
+                let mut out = format!(
+                    "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+                    response.status,
+                    response.body.len()
+                );
+                for (key, value) in &response.headers {
+                    out.push_str(&format!("{key}: {value}\r\n"));
+                }
+                out.push_str("\r\n");
+                out.push_str(&response.body);
+                let _ = socket.write_all(out.as_bytes()).await;
+                let _ = socket.shutdown().await;
+            }
+        });
+
+        Self { addr, requests }
+    }
+
+    /// Base URL of the server, e.g. `http://127.0.0.1:12345`
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Absolute URL for `path`
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base_url())
+    }
+
+    /// Requests received so far
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// In-process provider that replays canned replies in order and records every
+/// request it receives
+///
+/// Each reply reports 10 prompt and 5 completion tokens. Once the replies run out
+/// `chat_completion` fails with a model error.
+#[derive(Clone, Default)]
+pub struct ScriptedProvider {
+    replies: Arc<Mutex<VecDeque<ChatMessage>>>,
+    stream: Vec<ChatStreamEvent>,
+    delay: Duration,
+    requests: Arc<Mutex<Vec<(ChatRequest, String)>>>,
+}
+
+impl ScriptedProvider {
+    pub fn new(replies: Vec<ChatMessage>) -> Self {
+        Self {
+            replies: Arc::new(Mutex::new(replies.into())),
+            ..Default::default()
+        }
+    }
+
+    /// Reply with one assistant message per text
+    pub fn text(replies: &[&str]) -> Self {
+        Self::new(replies.iter().map(|r| ChatMessage::assistant(*r)).collect())
+    }
+
+    /// Events yielded by every `chat_completion_stream` call
+    pub fn with_stream(mut self, events: Vec<ChatStreamEvent>) -> Self {
+        self.stream = events;
+        self
+    }
+
+    /// Wait before answering each `chat_completion` call
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Requests received so far
+    pub fn requests(&self) -> Vec<ChatRequest> {
+        let requests = self.requests.lock().unwrap();
+        requests
+            .iter()
+            .map(|(request, _)| request.clone())
+            .collect()
+    }
+
+    /// Models the requests were sent to, in order
+    pub fn models(&self) -> Vec<String> {
+        let requests = self.requests.lock().unwrap();
+        requests.iter().map(|(_, model)| model.clone()).collect()
+    }
+}
+
+#[async_trait::async_trait]
+impl LlmProvider for ScriptedProvider {
+    fn name(&self) -> &'static str {
+        "scripted"
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn default_base_url(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn env_key(&self) -> &'static str {
+        ""
+    }
+
+    async fn available_models(&self) -> Result<Vec<String>, LlmError> {
+        Ok(vec![])
+    }
+
+    async fn health_check(&self) -> Result<(), LlmError> {
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ChatCompletion for ScriptedProvider {
+    async fn chat_completion(
+        &self,
+        request: ChatRequest,
+        model: &str,
+    ) -> Result<ChatResponse, LlmError> {
+        self.requests
+            .lock()
+            .unwrap()
+            .push((request, model.to_string()));
+        if !self.delay.is_zero() {
+            tokio::time::sleep(self.delay).await;
+        }
+        let message = self
+            .replies
+            .lock()
+            .unwrap()
+            .pop_front()
+            .ok_or_else(|| LlmError::model("No scripted response left"))?;
+        Ok(ChatResponse {
+            message,
+            model: model.to_string(),
+            usage: TokenUsage {
+                prompt_tokens: 10,
+                completion_tokens: 5,
+                total_tokens: 15,
+            },
+            finish_reason: None,
+            provider_metadata: None,
+        })
+    }
+
+    async fn chat_completion_stream(
+        &self,
+        request: ChatRequest,
+        model: &str,
+        _config: Option<StreamConfig>,
+    ) -> StreamResult<ChatStreamEvent> {
+        self.requests
+            .lock()
+            .unwrap()
+            .push((request, model.to_string()));
+        Box::pin(futures::stream::iter(
+            self.stream.clone().into_iter().map(Ok),
+        ))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;